@@ -18,6 +18,10 @@ pub enum ActionBinding {
     Select3,
     Select4,
     Select5,
+    Melee,
+    Scoreboard,
+    NextTarget,
+    FreeCamera,
 }
 
 #[derive(Clone, Debug, Hash, PartialEq, Eq, Serialize, Deserialize)]
@@ -0,0 +1,36 @@
+use amethyst::ecs::{Component, VecStorage};
+use crate::components::WeaponInfo;
+use westiny_common::network::InventoryState;
+
+/// Client side copy of the player's inventory
+#[derive(Debug, Clone, Default, Component)]
+#[storage(VecStorage)]
+pub struct Inventory {
+    pub slots: Vec<Option<WeaponInfo>>,
+    pub selected: usize,
+}
+
+impl Inventory {
+    pub fn active(&self) -> Option<&WeaponInfo> {
+        self.slots.get(self.selected).and_then(Option::as_ref)
+    }
+
+    pub fn active_mut(&mut self) -> Option<&mut WeaponInfo> {
+        self.slots.get_mut(self.selected).and_then(Option::as_mut)
+    }
+}
+
+impl From<&InventoryState> for Inventory {
+    fn from(state: &InventoryState) -> Self {
+        Inventory {
+            slots: state.slots.iter()
+                .map(|slot| slot.as_ref().map(|weapon| WeaponInfo {
+                    magazine_size: weapon.magazine_size,
+                    bullets_in_magazine: weapon.ammo_in_magazine,
+                    name: weapon.name.clone(),
+                }))
+                .collect(),
+            selected: state.selected,
+        }
+    }
+}
@@ -1,3 +1,5 @@
 pub use weapon_info::WeaponInfo;
+pub use inventory::Inventory;
 
-mod weapon_info;
\ No newline at end of file
+mod weapon_info;
+mod inventory;
\ No newline at end of file
@@ -0,0 +1,34 @@
+use amethyst::core::Transform;
+use amethyst::prelude::*;
+use amethyst::ecs::Entity;
+use amethyst::renderer::{palette::Srgba, resources::Tint};
+
+use westiny_common::components::{EntityType, NetworkId};
+use westiny_common::entities::spawn_barrel;
+use westiny_common::resources::SpriteId;
+use crate::resources::SpriteResource;
+
+/// Creates a barrel placed back by the server
+pub fn create_barrel<B: Builder>(
+    builder: B,
+    sprite_resource: &SpriteResource,
+    network_id: NetworkId,
+    transform: Transform
+    ) -> Entity
+{
+    let builder = spawn_barrel(transform, network_id, builder)
+        .with(sprite_resource.sprite_render_for(SpriteId::Barrel));
+
+    match barrel_tint(&network_id) {
+        Some(tint) => builder.with(tint).build(),
+        None => builder.build(),
+    }
+}
+
+/// Explosive barrels are painted red
+pub fn barrel_tint(network_id: &NetworkId) -> Option<Tint> {
+    match network_id.entity_type {
+        EntityType::ExplosiveBarrel => Some(Tint(Srgba::new(1.0, 0.4, 0.4, 1.0))),
+        _ => None,
+    }
+}
@@ -0,0 +1,28 @@
+use amethyst::core::Transform;
+use amethyst::core::math::Vector3;
+use amethyst::prelude::*;
+use amethyst::ecs::Entity;
+
+use westiny_common::components::NetworkId;
+use westiny_common::resources::SpriteId;
+use crate::resources::SpriteResource;
+
+const FLAG_HEIGHT: f32 = 0.4;
+const FLAG_SCALE: f32 = 0.6;
+
+/// The flag is colored by its team when its status arrives
+pub fn create_flag<B: Builder>(
+    builder: B,
+    sprite_resource: &SpriteResource,
+    network_id: NetworkId,
+    mut transform: Transform
+    ) -> Entity
+{
+    transform.set_translation_z(FLAG_HEIGHT);
+    transform.set_scale(Vector3::new(FLAG_SCALE, FLAG_SCALE, 1.0));
+    builder
+        .with(network_id)
+        .with(sprite_resource.sprite_render_for(SpriteId::Flag))
+        .with(transform)
+        .build()
+}
@@ -0,0 +1,29 @@
+use amethyst::core::Transform;
+use amethyst::core::math::Vector3;
+use amethyst::prelude::*;
+use amethyst::ecs::Entity;
+use amethyst::renderer::{palette::Srgba, resources::Tint};
+
+use westiny_common::components::NetworkId;
+use westiny_common::resources::SpriteId;
+use crate::resources::SpriteResource;
+
+const GRENADE_HEIGHT: f32 = 0.3;
+const GRENADE_SCALE: f32 = 0.3;
+
+pub fn create_grenade<B: Builder>(
+    builder: B,
+    sprite_resource: &SpriteResource,
+    network_id: NetworkId,
+    mut transform: Transform
+    ) -> Entity
+{
+    transform.set_translation_z(GRENADE_HEIGHT);
+    transform.set_scale(Vector3::new(GRENADE_SCALE, GRENADE_SCALE, 1.0));
+    builder
+        .with(network_id)
+        .with(sprite_resource.sprite_render_for(SpriteId::Barrel))
+        .with(Tint(Srgba::new(0.3, 0.45, 0.2, 1.0)))
+        .with(transform)
+        .build()
+}
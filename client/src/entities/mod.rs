@@ -1,5 +1,13 @@
+pub use barrel::{create_barrel, barrel_tint};
+pub use flag::create_flag;
 pub use player::{create_player, create_character};
+pub use grenade::create_grenade;
+pub use pickup::{create_item_pickup, create_weapon_pickup};
 pub use tilemap::initialize_tilemap;
 
+mod barrel;
+mod flag;
+mod grenade;
 mod player;
+mod pickup;
 mod tilemap;
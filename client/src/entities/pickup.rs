@@ -0,0 +1,50 @@
+use amethyst::core::Transform;
+use amethyst::prelude::*;
+use amethyst::ecs::Entity;
+use amethyst::core::math::Vector3;
+use amethyst::renderer::{palette::Srgba, resources::Tint};
+
+use westiny_common::components::{EntityType, NetworkId};
+use westiny_common::resources::SpriteId;
+use crate::resources::SpriteResource;
+
+const PICKUP_HEIGHT: f32 = 0.2;
+const ITEM_SCALE: f32 = 0.5;
+
+pub fn create_weapon_pickup<B: Builder>(
+    builder: B,
+    sprite_resource: &SpriteResource,
+    network_id: NetworkId,
+    mut transform: Transform
+    ) -> Entity
+{
+    transform.set_translation_z(PICKUP_HEIGHT);
+    builder
+        .with(network_id)
+        .with(sprite_resource.sprite_render_for(SpriteId::HandWithPistol))
+        .with(transform)
+        .build()
+}
+
+/// Medkits and armor are shown as small colored boxes
+pub fn create_item_pickup<B: Builder>(
+    builder: B,
+    sprite_resource: &SpriteResource,
+    network_id: NetworkId,
+    mut transform: Transform
+    ) -> Entity
+{
+    let tint = match network_id.entity_type {
+        EntityType::Medkit => Tint(Srgba::new(0.4, 1.0, 0.4, 1.0)),
+        EntityType::EffectPickup => Tint(Srgba::new(1.0, 0.9, 0.3, 1.0)),
+        _ => Tint(Srgba::new(0.5, 0.6, 1.0, 1.0)),
+    };
+    transform.set_translation_z(PICKUP_HEIGHT);
+    transform.set_scale(Vector3::new(ITEM_SCALE, ITEM_SCALE, 1.0));
+    builder
+        .with(network_id)
+        .with(sprite_resource.sprite_render_for(SpriteId::Barrel))
+        .with(tint)
+        .with(transform)
+        .build()
+}
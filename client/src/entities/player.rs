@@ -4,10 +4,10 @@ use amethyst::prelude::*;
 use amethyst::ecs::Entity;
 use log::info;
 
-use westiny_common::components::{Input, Health, Player, NetworkId, BoundingCircle};
+use westiny_common::components::{Input, Armor, Health, MaxHealth, Player, NetworkId, BoundingCircle, Stamina};
 use crate::resources::SpriteResource;
 use westiny_common::resources::SpriteId;
-use crate::components::WeaponInfo;
+use crate::components::Inventory;
 use westiny_common::metric_dimension::length::Meter;
 
 pub const CHARACTER_HEIGHT : f32 = 1.8;
@@ -59,14 +59,14 @@ where F: Fn () -> B
 
     let builder = factory()
         .with(Player)
+        // Updated by the HealthUpdate sent at spawn
         .with(Health(100))
+        .with(MaxHealth(100))
+        .with(Armor::default())
+        .with(Stamina::default())
         .with(Input::default())
-        // TODO WeaponInfo should be received within SpawnEvent
-        .with(WeaponInfo {
-            magazine_size: 6,
-            bullets_in_magazine: 6,
-            name: "Revolver".to_string()
-        });
+        // Filled by the InventoryUpdate sent at spawn
+        .with(Inventory::default());
     let entity = create_character(builder, factory, sprite_resource, network_id, transform);
     info!("Player created.");
     entity
@@ -2,7 +2,7 @@ use amethyst::audio::AudioBundle;
 use amethyst::utils::application_root_dir;
 use amethyst::{GameDataBuilder, CoreApplication};
 use amethyst::core::TransformBundle;
-use amethyst::renderer::{RenderingBundle, RenderToWindow, RenderFlat2D, types::DefaultBackend};
+use amethyst::renderer::{RenderingBundle, RenderToWindow, RenderFlat2D, RenderDebugLines, types::DefaultBackend};
 use amethyst::ui::{RenderUi, UiBundle};
 use amethyst::tiles::{RenderTiles2D, MortonEncoder};
 use amethyst::network::simulation::laminar::{LaminarSocket, LaminarNetworkBundle};
@@ -72,16 +72,22 @@ fn main() -> amethyst::Result<()> {
             .with_plugin(RenderFlat2D::default())
             .with_plugin(RenderTiles2D::<GroundTile, MortonEncoder>::default())
             .with_plugin(RenderUi::default())
+            .with_plugin(RenderDebugLines::default())
             )?
         .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
         .with_bundle(AudioBundle::default())?
         ;
 
-    let mut game =
-        CoreApplication::<_, WestinyEvent, WestinyEventReader>::build(
+    let mut game = match states::replay::replay_file() {
+        Some(replay_file) => CoreApplication::<_, WestinyEvent, WestinyEventReader>::build(
             &resources_dir,
-            states::connection::ConnectState::new(&common_resources_dir),
-        )?.build(game_data)?;
+            states::replay::ReplayState::new(&common_resources_dir, replay_file),
+        )?.build(game_data)?,
+        None => CoreApplication::<_, WestinyEvent, WestinyEventReader>::build(
+            &resources_dir,
+            states::menu::MenuState::new(&common_resources_dir),
+        )?.build(game_data)?,
+    };
 
     log::info!("Starting client");
     game.run();
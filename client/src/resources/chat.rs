@@ -0,0 +1,130 @@
+use amethyst::assets::Loader;
+use amethyst::ecs::prelude::*;
+use amethyst::ui::{Anchor, LineMode, TtfFormat, UiText, UiTransform};
+
+use super::MessagePanel;
+use westiny_common::network::{ChatMessage, MAX_CHAT_MESSAGE_LENGTH};
+
+const CHAT_LINES: usize = 8;
+
+/// The message being typed. The character stands still meanwhile.
+#[derive(Default)]
+pub struct ChatInput {
+    pub active: bool,
+    pub team_only: bool,
+    pub text: String,
+}
+
+impl ChatInput {
+    pub fn open(&mut self, team_only: bool) {
+        self.active = true;
+        self.team_only = team_only;
+        self.text.clear();
+    }
+
+    pub fn push(&mut self, character: char) {
+        if !character.is_control() && self.text.chars().count() < MAX_CHAT_MESSAGE_LENGTH {
+            self.text.push(character);
+        }
+    }
+
+    pub fn cancel(&mut self) {
+        self.active = false;
+        self.text.clear();
+    }
+
+    /// Closes the input, an empty message is not worth sending
+    pub fn take_message(&mut self) -> Option<String> {
+        self.active = false;
+        let text = std::mem::take(&mut self.text);
+        let text = text.trim();
+        if text.is_empty() {
+            None
+        } else {
+            Some(text.to_string())
+        }
+    }
+}
+
+/// Received chat messages above the notification bar, and the message being typed below them
+pub struct ChatPanel {
+    pub messages: MessagePanel,
+    pub input: Entity,
+}
+
+impl ChatPanel {
+    pub fn initialize(world: &mut World)
+    {
+        let font = {
+            let loader = world.read_resource::<Loader>();
+            loader.load("fonts/square.ttf", TtfFormat, (), &world.read_resource())
+        };
+
+        let messages = MessagePanel::create(world, &font, "ChatMessages", (10., 200.), 400., CHAT_LINES);
+        let input = world.create_entity()
+            .with(UiTransform::new(
+                "ChatInput".to_string(),
+                Anchor::BottomLeft,
+                Anchor::BottomLeft,
+                10., 170., 1.,
+                400., 24.,
+            ))
+            .with(UiText::new(
+                font,
+                "".to_string(),
+                [1., 1., 0.6, 1.],
+                18.,
+                LineMode::Single,
+                Anchor::MiddleLeft,
+            ))
+            .build();
+
+        world.insert(ChatPanel { messages, input });
+        world.insert(ChatInput::default());
+    }
+}
+
+pub fn format_chat_message(message: &ChatMessage) -> String {
+    let team = if message.team_only { "[Team] " } else { "" };
+    format!("{}{}: {}", team, message.sender, message.text)
+}
+
+pub fn format_chat_input(input: &ChatInput) -> String {
+    if !input.active {
+        return String::new();
+    }
+    let audience = if input.team_only { "Team" } else { "All" };
+    format!("{}: {}_", audience, input.text)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use westiny_common::PlayerName;
+
+    #[test]
+    fn typed_message_is_taken_trimmed() {
+        let mut input = ChatInput::default();
+        input.open(true);
+        for character in " hi\u{8} all ".chars() {
+            input.push(character);
+        }
+        assert_eq!(format_chat_input(&input), "Team:  hi all _");
+        assert_eq!(input.take_message(), Some("hi all".to_string()));
+        assert!(!input.active);
+        assert_eq!(format_chat_input(&input), "");
+
+        input.open(false);
+        input.push(' ');
+        assert_eq!(input.take_message(), None);
+    }
+
+    #[test]
+    fn team_messages_are_marked() {
+        let mut message = ChatMessage { sender: PlayerName("Joe".to_string()), text: "go go go".to_string(), team_only: false };
+        assert_eq!(format_chat_message(&message), "Joe: go go go");
+
+        message.team_only = true;
+        assert_eq!(format_chat_message(&message), "[Team] Joe: go go go");
+    }
+}
@@ -1,6 +1,8 @@
 use amethyst::tiles::Tile;
 use amethyst::ecs::World;
 use amethyst::core::math::Point3;
+use amethyst::renderer::palette::Srgba;
+use std::collections::HashSet;
 
 #[derive(Default, Clone)]
 pub struct GroundTile;
@@ -8,5 +10,34 @@ impl Tile for GroundTile {
     fn sprite(&self, point: Point3<u32>, _: &World) -> Option<usize> {
         Some(((point.x + point.y) % 2) as usize)
     }
+
+    fn tint(&self, point: Point3<u32>, world: &World) -> Srgba {
+        let hidden = world.try_fetch::<FogOfWar>()
+            .map_or(false, |fog| fog.is_hidden(point.x, point.y));
+        if hidden {
+            Srgba::new(0.35, 0.35, 0.45, 1.0)
+        } else {
+            Srgba::new(1.0, 1.0, 1.0, 1.0)
+        }
+    }
 }
 
+/// The ground tiles out of the player's line of sight, they are drawn darkened
+#[derive(Default)]
+pub struct FogOfWar {
+    hidden: HashSet<(u32, u32)>,
+}
+
+impl FogOfWar {
+    pub fn is_hidden(&self, x: u32, y: u32) -> bool {
+        self.hidden.contains(&(x, y))
+    }
+
+    pub fn set_hidden<I: IntoIterator<Item = (u32, u32)>>(&mut self, tiles: I) {
+        self.hidden = tiles.into_iter().collect();
+    }
+
+    pub fn clear(&mut self) {
+        self.hidden.clear();
+    }
+}
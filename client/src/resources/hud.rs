@@ -3,10 +3,22 @@ use amethyst::ecs::prelude::Entity;
 use amethyst::ecs::prelude::*;
 use amethyst::assets::{Loader};
 use amethyst::core::Parent;
+use crate::components::Inventory;
+use westiny_common::network::StatusEffectState;
+use std::time::Duration;
 
 pub struct Hud {
     pub health: Entity,
     pub ammo: Entity,
+    pub stamina: Entity,
+    pub armor: Entity,
+    pub inventory: Entity,
+    pub match_status: Entity,
+    pub scoreboard: Entity,
+    pub flag_status: Entity,
+    pub zone_status: Entity,
+    pub spectator: Entity,
+    pub status_effects: Entity,
 }
 
 pub fn initialize_hud(world: &mut World)
@@ -45,13 +57,173 @@ pub fn initialize_hud(world: &mut World)
     let ammo = world.create_entity()
         .with(ammo_transform)
         .with(Parent { entity: health })
+        .with(ui_text.clone())
+        .build();
+
+    let stamina_transform = UiTransform::new(
+            "stamina".to_string(),
+            Anchor::TopRight,
+            Anchor::TopMiddle,
+            -100., -100., 1.,
+            150., 50.,
+        );
+    let stamina = world.create_entity()
+        .with(stamina_transform)
+        .with(Parent { entity: health })
+        .with(ui_text.clone())
+        .build();
+
+    let armor_transform = UiTransform::new(
+            "armor".to_string(),
+            Anchor::TopRight,
+            Anchor::TopMiddle,
+            -100., -150., 1.,
+            150., 50.,
+        );
+    let armor = world.create_entity()
+        .with(armor_transform)
+        .with(Parent { entity: health })
         .with(ui_text)
         .build();
 
+    let status_effects_transform = UiTransform::new(
+            "status_effects".to_string(),
+            Anchor::TopRight,
+            Anchor::TopRight,
+            -10., -110., 1.,
+            400., 30.,
+        );
+    let status_effects = world.create_entity()
+        .with(status_effects_transform)
+        .with(UiText::new(
+            font.clone(),
+            String::new(),
+            [1., 0.8, 0.2, 1.],
+            20.,
+            LineMode::Single,
+            Anchor::MiddleRight))
+        .build();
+
+    let inventory_transform = UiTransform::new(
+            "inventory".to_string(),
+            Anchor::BottomMiddle,
+            Anchor::BottomMiddle,
+            0., 10., 1.,
+            600., 30.,
+        );
+    let inventory = world.create_entity()
+        .with(inventory_transform)
+        .with(UiText::new(
+            font.clone(),
+            String::new(),
+            [1., 1., 1., 1.],
+            20.,
+            LineMode::Single,
+            Anchor::Middle))
+        .build();
+
+    let spectator_transform = UiTransform::new(
+            "spectator".to_string(),
+            Anchor::BottomMiddle,
+            Anchor::BottomMiddle,
+            0., 50., 1.,
+            800., 30.,
+        );
+    let spectator = world.create_entity()
+        .with(spectator_transform)
+        .with(UiText::new(
+            font.clone(),
+            String::new(),
+            [1., 1., 1., 1.],
+            20.,
+            LineMode::Single,
+            Anchor::Middle))
+        .build();
+
+    let match_status_transform = UiTransform::new(
+            "match_status".to_string(),
+            Anchor::TopMiddle,
+            Anchor::TopMiddle,
+            0., -10., 1.,
+            800., 30.,
+        );
+    let match_status = world.create_entity()
+        .with(match_status_transform)
+        .with(UiText::new(
+            font.clone(),
+            String::new(),
+            [1., 1., 1., 1.],
+            24.,
+            LineMode::Single,
+            Anchor::Middle))
+        .build();
+
+    let flag_status_transform = UiTransform::new(
+            "flag_status".to_string(),
+            Anchor::TopMiddle,
+            Anchor::TopMiddle,
+            0., -40., 1.,
+            800., 30.,
+        );
+    let flag_status = world.create_entity()
+        .with(flag_status_transform)
+        .with(UiText::new(
+            font.clone(),
+            String::new(),
+            [1., 1., 1., 1.],
+            18.,
+            LineMode::Single,
+            Anchor::Middle))
+        .build();
+
+    let zone_status_transform = UiTransform::new(
+            "zone_status".to_string(),
+            Anchor::TopMiddle,
+            Anchor::TopMiddle,
+            0., -70., 1.,
+            800., 30.,
+        );
+    let zone_status = world.create_entity()
+        .with(zone_status_transform)
+        .with(UiText::new(
+            font.clone(),
+            String::new(),
+            [1., 1., 1., 1.],
+            18.,
+            LineMode::Single,
+            Anchor::Middle))
+        .build();
+
+    let scoreboard_transform = UiTransform::new(
+            "scoreboard".to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            0., 0., 2.,
+            600., 400.,
+        );
+    let scoreboard = world.create_entity()
+        .with(scoreboard_transform)
+        .with(UiText::new(
+            font,
+            String::new(),
+            [1., 1., 1., 1.],
+            20.,
+            LineMode::Wrap,
+            Anchor::TopLeft))
+        .build();
 
     world.insert(Hud{
         health,
-        ammo
+        ammo,
+        stamina,
+        armor,
+        inventory,
+        match_status,
+        scoreboard,
+        flag_status,
+        zone_status,
+        spectator,
+        status_effects,
     });
 
 }
@@ -61,6 +233,60 @@ pub fn format_health(health: u16) -> String
     format!("HP {}", health)
 }
 
+/// Empty without armor
+pub fn format_armor(armor: u16) -> String {
+    if armor > 0 {
+        format!("AR {}", armor)
+    } else {
+        String::new()
+    }
+}
+
+/// The health text turns red when the player is about to die
+pub fn health_color(health: u16, max_health: u16) -> [f32; 4] {
+    if (health as u32) * 4 <= max_health as u32 {
+        [1., 0.3, 0.3, 1.]
+    } else {
+        [1., 1., 1., 1.]
+    }
+}
+
+pub fn format_stamina(stamina: f32) -> String {
+    format!("ST {}", stamina.round())
+}
+
 pub fn format_ammo(ammo_in_magazine: u32, magazine_size: u32) -> String {
     format!("{} / {}", ammo_in_magazine, magazine_size)
 }
+
+/// Lists every slot with its number, the selected one is put in brackets
+pub fn format_inventory(inventory: &Inventory) -> String {
+    inventory.slots.iter()
+        .enumerate()
+        .map(|(index, slot)| {
+            let name = slot.as_ref().map(|weapon| weapon.name.as_str()).unwrap_or("-");
+            if index == inventory.selected {
+                format!("[{} {}]", index + 1, name)
+            } else {
+                format!("{} {}", index + 1, name)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
+
+/// Lists the effects with their stacks and remaining seconds, `elapsed` is the time since the server sent them
+pub fn format_status_effects(effects: &[StatusEffectState], elapsed: Duration) -> String {
+    effects.iter()
+        .filter(|effect| effect.remaining.0 > elapsed.as_secs_f32())
+        .map(|effect| {
+            let remaining = (effect.remaining.0 - elapsed.as_secs_f32()).ceil();
+            if effect.stacks > 1 {
+                format!("{:?} x{} {}s", effect.kind, effect.stacks, remaining)
+            } else {
+                format!("{:?} {}s", effect.kind, remaining)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("  ")
+}
@@ -1,42 +1,73 @@
-use amethyst::ui::{Anchor, LineMode, TtfFormat, UiText, UiTransform};
+use amethyst::ui::{Anchor, FontHandle, LineMode, UiText, UiTransform};
 use amethyst::ecs::prelude::Entity;
 use amethyst::ecs::prelude::*;
-use amethyst::assets::{Loader};
+use std::collections::VecDeque;
 
-pub struct  {
-    pub health: Entity,
+/// A block of text showing the latest lines, older ones scroll out at the top
+pub struct MessagePanel {
+    pub text: Entity,
+    lines: VecDeque<String>,
+    max_lines: usize,
 }
 
-pub fn initialize_hud(world: &mut World)
-{
-    let font = {
-        let loader = world.read_resource::<Loader>();
-        loader.load("fonts/square.ttf", TtfFormat, (), &world.read_resource())
-    };
-    let panel_transform = UiTransform::new(
-        "HUD".to_string(),
-        Anchor::TopRight,
-        Anchor::TopMiddle,
-        -100., -50., 1., // x,y,z
-        150., 50., // width, height
-        );
-
-    let panel = world.create_entity()
-            .with(hud_transform)
+impl MessagePanel {
+    /// The panel grows upwards from its bottom left corner
+    pub fn create(world: &mut World, font: &FontHandle, id: &str, position: (f32, f32), width: f32, max_lines: usize) -> Self
+    {
+        let font_size = 18.;
+        let panel_transform = UiTransform::new(
+            id.to_string(),
+            Anchor::BottomLeft,
+            Anchor::BottomLeft,
+            position.0, position.1, 1.,
+            width, font_size * 1.2 * max_lines as f32,
+            );
+
+        let text = world.create_entity()
+            .with(panel_transform)
             .with(UiText::new(
                     font.clone(),
-                    format_health(0), // text
+                    "".to_string(),
                     [1., 1., 1., 0.8], // color
-                    10., // font size
+                    font_size,
                     LineMode::Wrap,
-                    Anchor::Middle)
-            ).build();
+                    Anchor::BottomLeft)
+                ).build();
+
+        MessagePanel { text, lines: VecDeque::new(), max_lines }
+    }
 
-    world.insert(Hud{ health: health });
+    pub fn push(&mut self, line: String, ui_texts: &mut WriteStorage<UiText>)
+    {
+        self.add_line(line);
+        if let Some(text) = ui_texts.get_mut(self.text) {
+            text.text = self.content();
+        }
+    }
 
+    fn add_line(&mut self, line: String) {
+        self.lines.push_back(line);
+        while self.lines.len() > self.max_lines {
+            self.lines.pop_front();
+        }
+    }
+
+    fn content(&self) -> String {
+        self.lines.iter().cloned().collect::<Vec<_>>().join("\n")
+    }
 }
 
-pub fn format_health(health: u16) -> String
-{
-    format!("HP {}", health)
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn oldest_lines_scroll_out() {
+        let mut world = World::new();
+        let mut panel = MessagePanel { text: world.create_entity().build(), lines: VecDeque::new(), max_lines: 2 };
+        for line in &["first", "second", "third"] {
+            panel.add_line(line.to_string());
+        }
+        assert_eq!(panel.content(), "second\nthird");
+    }
 }
@@ -1,16 +1,44 @@
 pub use audio::{initialize_audio, Sounds};
-pub use hud::{format_health, format_ammo, Hud, initialize_hud};
+pub use chat::{format_chat_input, format_chat_message, ChatInput, ChatPanel};
+pub use hud::{format_health, format_armor, health_color, format_ammo, format_inventory, format_stamina, format_status_effects, Hud, initialize_hud};
 pub use network_stream_id::StreamId;
+pub use message_panel::MessagePanel;
 pub use notification_bar::{NotificationBar};
 pub use sprite_resource::{initialize_sprite_resource, SpriteResource};
-pub use groundtile::GroundTile;
+pub use groundtile::{FogOfWar, GroundTile};
+pub use profile::Profile;
+pub use recent_servers::RecentServers;
+pub use replay::ReplayPlayback;
 use westiny_common::components::NetworkId;
+use westiny_common::network::StatusEffectState;
+use std::time::Duration;
 
 mod audio;
+mod chat;
 mod hud;
+mod message_panel;
 mod notification_bar;
 mod sprite_resource;
 mod network_stream_id;
 mod groundtile;
+mod profile;
+mod recent_servers;
+mod replay;
 
-pub struct PlayerNetworkId(pub NetworkId);
+/// Not set for observers, they have no character
+pub struct PlayerNetworkId(pub Option<NetworkId>);
+
+/// Status effects of the player as last told by the server
+#[derive(Default)]
+pub struct ActiveStatusEffects {
+    pub effects: Vec<StatusEffectState>,
+    pub received_at: Duration,
+}
+
+/// Entered in the menu, sent in the connection request to password protected servers
+#[derive(Default)]
+pub struct ServerPassword(pub Option<String>);
+
+/// Set when the player has no character in the round and watches the others
+#[derive(Default)]
+pub struct Spectating(pub bool);
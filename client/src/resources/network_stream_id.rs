@@ -2,6 +2,7 @@
 #[repr(u8)]
 pub enum StreamId {
     InputState,
+    Chat,
 }
 
 impl Into<Option<u8>> for StreamId {
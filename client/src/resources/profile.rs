@@ -0,0 +1,59 @@
+use amethyst::config::Config;
+use serde::{Serialize, Deserialize};
+use std::path::PathBuf;
+
+use westiny_common::utilities::read_ron;
+
+const PROFILE_FILE: &str = ".westiny_profile.ron";
+const PLAYER_NAME_MAGIC: &str = "Narancsos_Feco";
+
+/// Settings of the local player, stored in the home directory of the user.
+/// The name is edited in the main menu, the color only in the file.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Profile {
+    pub name: String,
+    /// Tints the own character in games without teams
+    pub color: (f32, f32, f32),
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile {
+            name: std::env::var("USER").unwrap_or(PLAYER_NAME_MAGIC.to_string()),
+            color: (1.0, 1.0, 1.0),
+        }
+    }
+}
+
+impl Profile {
+    pub fn load() -> Self {
+        read_ron(&Self::file_path()).unwrap_or_else(|err| {
+            log::debug!("Profile could not be loaded, using the default: {}", err);
+            Profile::default()
+        })
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        self.write(Self::file_path())?;
+        Ok(())
+    }
+
+    fn file_path() -> PathBuf {
+        std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(PROFILE_FILE)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn missing_settings_are_defaulted() {
+        let profile = Profile::load_bytes(b"(name: \"Clint\")").expect("profile could not be parsed");
+        assert_eq!(profile, Profile { name: "Clint".to_string(), ..Profile::default() });
+    }
+}
@@ -0,0 +1,93 @@
+use std::fmt::{Display, Formatter};
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+const MAX_RECENT_SERVERS: usize = 5;
+const RECENT_SERVERS_FILE: &str = ".westiny_recent_servers";
+
+/// The servers connected to lately, the most recent first.
+/// They are stored in the home directory of the user, one address per line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct RecentServers {
+    addresses: Vec<SocketAddr>,
+}
+
+impl RecentServers {
+    pub fn load() -> Self {
+        std::fs::read_to_string(Self::file_path())
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> anyhow::Result<()> {
+        std::fs::write(Self::file_path(), self.to_string())?;
+        Ok(())
+    }
+
+    /// Lines which are not addresses are skipped
+    fn parse(content: &str) -> Self {
+        RecentServers {
+            addresses: content.lines()
+                .filter_map(|line| SocketAddr::from_str(line.trim()).ok())
+                .take(MAX_RECENT_SERVERS)
+                .collect(),
+        }
+    }
+
+    pub fn add(&mut self, address: SocketAddr) {
+        self.addresses.retain(|recent| *recent != address);
+        self.addresses.insert(0, address);
+        self.addresses.truncate(MAX_RECENT_SERVERS);
+    }
+
+    pub fn addresses(&self) -> &[SocketAddr] {
+        &self.addresses
+    }
+
+    fn file_path() -> PathBuf {
+        std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(RECENT_SERVERS_FILE)
+    }
+}
+
+impl Display for RecentServers {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for address in self.addresses.iter() {
+            writeln!(f, "{}", address)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn address(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    #[test]
+    fn latest_server_comes_first_without_duplicates() {
+        let mut recent = RecentServers::default();
+        for port in 1..=7 {
+            recent.add(address(port));
+        }
+        recent.add(address(5));
+
+        assert_eq!(recent.addresses(), &[address(5), address(7), address(6), address(4), address(3)]);
+    }
+
+    #[test]
+    fn stored_list_is_read_back() {
+        let mut recent = RecentServers::default();
+        recent.add(address(2));
+        recent.add(address(1));
+
+        let content = format!("{}not an address\n", recent);
+        assert_eq!(RecentServers::parse(&content), recent);
+    }
+}
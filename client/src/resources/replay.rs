@@ -0,0 +1,318 @@
+use std::mem::discriminant;
+use std::time::Duration;
+use westiny_common::components::NetworkId;
+use westiny_common::network::{EntityState, MapChange, NetworkEntityDelete, PacketType};
+use westiny_common::replay::{is_lasting_state, ReplayFrame};
+
+/// Seeking jumps this much in the replay
+const SEEK_STEP: Duration = Duration::from_secs(10);
+const MIN_SPEED: f32 = 0.25;
+const MAX_SPEED: f32 = 8.0;
+
+/// The position and the controls of the replay being watched
+pub struct ReplayPlayback {
+    frames: Vec<ReplayFrame>,
+    position: Duration,
+    /// Index of the first frame not played yet
+    next_frame: usize,
+    speed: f32,
+    paused: bool,
+    /// Set after seeking, the lasting state at the new position is played first
+    seeked: bool,
+    /// Places and deletes the map objects to match the new position after seeking
+    map_objects: Vec<PacketType>,
+    /// The map the recording starts on
+    first_map: MapChange,
+    /// The map shown, seeking loads another one if the server has changed it in between
+    map: MapChange,
+}
+
+impl ReplayPlayback {
+    pub fn new(frames: Vec<ReplayFrame>, first_map: MapChange) -> Self {
+        ReplayPlayback {
+            frames,
+            position: Duration::default(),
+            next_frame: 0,
+            speed: 1.0,
+            paused: false,
+            seeked: false,
+            map_objects: Vec::new(),
+            map: first_map.clone(),
+            first_map,
+        }
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.frames.last().map_or(Duration::default(), |frame| frame.time)
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_frame >= self.frames.len()
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn faster(&mut self) {
+        self.speed = (self.speed * 2.0).min(MAX_SPEED);
+    }
+
+    pub fn slower(&mut self) {
+        self.speed = (self.speed / 2.0).max(MIN_SPEED);
+    }
+
+    pub fn seek(&mut self, position: Duration) {
+        self.position = position.min(self.duration());
+        let position = self.position;
+        self.next_frame = self.frames.iter()
+            .position(|frame| frame.time > position)
+            .unwrap_or(self.frames.len());
+
+        // Only the objects of the map played at the new position are placed
+        let played = &self.frames[..self.next_frame];
+        let map_start = played.iter().rposition(|frame| map_change(frame).is_some()).unwrap_or(0);
+        let map = played.get(map_start).and_then(map_change).unwrap_or(&self.first_map).clone();
+        let mut packets = Vec::new();
+        if map != self.map {
+            packets.push(PacketType::MapChange(map.clone()));
+            self.map = map;
+        }
+        packets.extend(map_objects(&self.frames[map_start..self.next_frame]));
+        self.map_objects = packets;
+        self.seeked = true;
+    }
+
+    pub fn seek_forward(&mut self) {
+        self.seek(self.position + SEEK_STEP);
+    }
+
+    pub fn seek_backward(&mut self) {
+        self.seek(self.position.checked_sub(SEEK_STEP).unwrap_or_default());
+    }
+
+    /// Moves the playback by the elapsed time, returns the packets to play in order
+    pub fn advance(&mut self, elapsed: Duration) -> Vec<&PacketType> {
+        let mut packets = Vec::new();
+        if self.seeked {
+            self.seeked = false;
+            packets.extend(self.map_objects.iter());
+            packets.extend(lasting_state(&self.frames[..self.next_frame]));
+        }
+        if self.paused {
+            return packets;
+        }
+
+        self.position = (self.position + elapsed.mul_f32(self.speed)).min(self.duration());
+        let position = self.position;
+        while let Some(frame) = self.frames.get(self.next_frame).filter(|frame| frame.time <= position) {
+            if let Some(change) = map_change(frame) {
+                self.map = change.clone();
+            }
+            packets.extend(frame.packets.iter());
+            self.next_frame += 1;
+        }
+        packets
+    }
+
+    /// Shown on the notification bar when the playback is controlled
+    pub fn status(&self) -> String {
+        let mut status = format!("Replay {} / {}, speed {}x", format_time(self.position), format_time(self.duration()), self.speed);
+        if self.paused {
+            status.push_str(", paused");
+        }
+        status
+    }
+}
+
+/// The latest packet of every lasting state kind
+fn lasting_state(frames: &[ReplayFrame]) -> Vec<&PacketType> {
+    let mut latest: Vec<&PacketType> = Vec::new();
+    for packet in frames.iter().rev().flat_map(|frame| frame.packets.iter().rev()) {
+        if is_lasting_state(packet) && !latest.iter().any(|known| discriminant(*known) == discriminant(packet)) {
+            latest.push(packet);
+        }
+    }
+    latest
+}
+
+/// The map loaded in the frame, if any
+fn map_change(frame: &ReplayFrame) -> Option<&MapChange> {
+    frame.packets.iter().find_map(|packet| match packet {
+        PacketType::MapChange(change) => Some(change),
+        _ => None,
+    })
+}
+
+/// Static map objects are recorded when they appear and deleted when destroyed,
+/// so the objects standing at the end of the frames are placed and the destroyed ones are deleted
+fn map_objects(frames: &[ReplayFrame]) -> Vec<PacketType> {
+    let mut standing: Vec<EntityState> = Vec::new();
+    let mut destroyed: Vec<NetworkId> = Vec::new();
+    for packet in frames.iter().flat_map(|frame| frame.packets.iter()) {
+        match packet {
+            PacketType::EntityStateUpdate(states) => {
+                for state in states.iter().filter(|state| state.network_id.entity_type.is_static()) {
+                    destroyed.retain(|network_id| *network_id != state.network_id);
+                    standing.retain(|known| known.network_id != state.network_id);
+                    standing.push(state.clone());
+                }
+            }
+            PacketType::EntityDelete(delete) if delete.network_id.entity_type.is_static() => {
+                standing.retain(|known| known.network_id != delete.network_id);
+                if !destroyed.contains(&delete.network_id) {
+                    destroyed.push(delete.network_id);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut packets: Vec<PacketType> = destroyed.into_iter()
+        .map(|network_id| PacketType::EntityDelete(NetworkEntityDelete { network_id }))
+        .collect();
+    if !standing.is_empty() {
+        packets.push(PacketType::EntityStateUpdate(standing));
+    }
+    packets
+}
+
+fn format_time(time: Duration) -> String {
+    let secs = time.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::core::math::Point2;
+    use westiny_common::components::EntityType;
+    use westiny_common::metric_dimension::length::Meter;
+    use westiny_common::network::{MatchPhase, MatchUpdate, PlayerNotification};
+    use westiny_common::resources::Seed;
+
+    fn notification(message: &str) -> PacketType {
+        PacketType::Notification(PlayerNotification { message: message.to_string() })
+    }
+
+    fn frame(secs: u64, packets: Vec<PacketType>) -> ReplayFrame {
+        ReplayFrame { time: Duration::from_secs(secs), packets }
+    }
+
+    fn messages(packets: &[&PacketType]) -> Vec<String> {
+        packets.iter()
+            .map(|packet| match packet {
+                PacketType::Notification(notification) => notification.message.clone(),
+                PacketType::EntityStateUpdate(states) => format!("{} entities", states.len()),
+                packet => format!("{:?}", packet),
+            })
+            .collect()
+    }
+
+    fn map(name: &str) -> MapChange {
+        MapChange { map: name.to_string(), seed: Seed(0) }
+    }
+
+    fn playback() -> ReplayPlayback {
+        ReplayPlayback::new((1..=30)
+            .map(|secs| frame(secs, vec![PacketType::EntityStateUpdate(vec![]), notification(&secs.to_string())]))
+            .collect(), map("rust2"))
+    }
+
+    #[test]
+    fn frames_are_played_as_time_passes() {
+        let mut playback = playback();
+        assert!(playback.advance(Duration::from_millis(500)).is_empty());
+        assert_eq!(messages(&playback.advance(Duration::from_secs(2))), vec!["0 entities", "1", "0 entities", "2"]);
+
+        playback.faster();
+        assert_eq!(messages(&playback.advance(Duration::from_secs(1))), vec!["0 entities", "3", "0 entities", "4"]);
+
+        playback.toggle_pause();
+        assert!(playback.advance(Duration::from_secs(5)).is_empty());
+        assert_eq!(playback.status(), "Replay 0:04 / 0:30, speed 2x, paused");
+    }
+
+    #[test]
+    fn seeking_plays_only_the_lasting_state() {
+        let mut playback = playback();
+        playback.frames[4].packets.push(PacketType::MatchUpdate(MatchUpdate { phase: MatchPhase::Live, time_left: None }));
+        playback.toggle_pause();
+
+        playback.seek(Duration::from_secs(12));
+        let packets = playback.advance(Duration::from_secs(1));
+        assert_eq!(packets.len(), 2);
+        assert!(matches!(packets[0], PacketType::EntityStateUpdate(_)));
+        assert!(matches!(packets[1], PacketType::MatchUpdate(_)));
+
+        playback.toggle_pause();
+        assert_eq!(messages(&playback.advance(Duration::from_secs(1))), vec!["0 entities", "13"]);
+
+        playback.seek_backward();
+        assert_eq!(playback.status(), "Replay 0:03 / 0:30, speed 1x");
+        assert_eq!(messages(&playback.advance(Duration::from_secs(1)))[1..], ["0 entities", "4"]);
+    }
+
+    #[test]
+    fn seeking_places_and_deletes_the_map_objects() {
+        let barrel = NetworkId::new(EntityType::Barrel, 3);
+        let barrel_state = EntityState { network_id: barrel, position: Point2::new(Meter(2.0), Meter(-4.0)), rotation: 0.0 };
+        let mut playback = playback();
+        playback.frames[0].packets[0] = PacketType::EntityStateUpdate(vec![barrel_state.clone()]);
+        playback.frames[9].packets.push(PacketType::EntityDelete(NetworkEntityDelete { network_id: barrel }));
+        playback.toggle_pause();
+
+        // Seeking across the destruction deletes the barrel
+        playback.seek(Duration::from_secs(15));
+        let packets = playback.advance(Duration::from_secs(1));
+        assert!(matches!(packets[0], PacketType::EntityDelete(delete) if delete.network_id == barrel), "{:?}", packets);
+        assert!(packets[1..].iter().all(|packet| !matches!(packet, PacketType::EntityStateUpdate(states) if !states.is_empty())));
+
+        // Seeking back before it places the barrel back
+        playback.seek(Duration::from_secs(5));
+        let packets = playback.advance(Duration::from_secs(1));
+        assert!(matches!(packets[0], PacketType::EntityStateUpdate(states)
+            if states.len() == 1 && states[0].network_id == barrel && states[0].position == barrel_state.position));
+        assert!(packets.iter().all(|packet| !matches!(packet, PacketType::EntityDelete(_))));
+    }
+
+    #[test]
+    fn seeking_loads_the_map_played_at_the_new_position() {
+        let barrel = NetworkId::new(EntityType::Barrel, 3);
+        let mut playback = playback();
+        playback.frames[9].packets.push(PacketType::EntityDelete(NetworkEntityDelete { network_id: barrel }));
+        playback.frames[19].packets.insert(0, PacketType::MapChange(map("canyon")));
+        playback.toggle_pause();
+
+        // The barrel destroyed on the previous map is not deleted on the new one
+        playback.seek(Duration::from_secs(25));
+        let packets = playback.advance(Duration::from_secs(1));
+        assert!(matches!(packets[0], PacketType::MapChange(change) if change.map == "canyon"), "{:?}", packets);
+        assert!(packets.iter().all(|packet| !matches!(packet, PacketType::EntityDelete(_))));
+
+        // Seeking on the same map loads nothing
+        playback.seek(Duration::from_secs(22));
+        assert!(playback.advance(Duration::from_secs(1)).iter().all(|packet| !matches!(packet, PacketType::MapChange(_))));
+
+        playback.seek(Duration::from_secs(5));
+        let packets = playback.advance(Duration::from_secs(1));
+        assert!(matches!(packets[0], PacketType::MapChange(change) if change.map == "rust2"), "{:?}", packets);
+
+        // Playing the change keeps track of the map too
+        playback.toggle_pause();
+        playback.seek(Duration::from_secs(19));
+        playback.advance(Duration::from_secs(2));
+        playback.seek(Duration::from_secs(23));
+        assert!(playback.advance(Duration::from_secs(0)).iter().all(|packet| !matches!(packet, PacketType::MapChange(_))));
+    }
+
+    #[test]
+    fn playback_stops_at_the_end() {
+        let mut playback = playback();
+        playback.seek_forward();
+        playback.seek(Duration::from_secs(100));
+        assert!(playback.is_finished());
+        playback.advance(Duration::from_secs(1));
+        assert_eq!(playback.status(), "Replay 0:30 / 0:30, speed 1x");
+    }
+}
@@ -1,18 +1,22 @@
 use amethyst::prelude::*;
 use amethyst::shred::{Dispatcher, DispatcherBuilder};
 use amethyst::core::ecs::WorldExt;
-use amethyst::core::ArcThreadPool;
+use amethyst::core::{ArcThreadPool, Time};
+use amethyst::input::{is_close_requested, is_key_down, VirtualKeyCode};
 use westiny_common::{
     resources::ServerAddress,
     events::{AppEvent, WestinyEvent},
 };
 use crate::systems;
-use std::net::SocketAddr;
-use std::str::FromStr;
+use std::time::Duration;
+
+/// Connecting is given up if the server does not answer in this time
+const CONNECTION_TIMEOUT: Duration = Duration::from_secs(10);
 
 pub struct ConnectState {
     dispatcher: Option<Dispatcher<'static, 'static>>,
     resource_dir: std::path::PathBuf,
+    started_at: Duration,
 }
 
 impl ConnectState {
@@ -20,15 +24,20 @@ impl ConnectState {
         ConnectState {
             dispatcher: Default::default(),
             resource_dir: resource_dir.to_path_buf(),
+            started_at: Duration::default(),
         }
     }
+
+    fn back_to_menu(&self, error: String) -> Trans<GameData<'static, 'static>, WestinyEvent> {
+        Trans::Switch(Box::new(super::menu::MenuState::with_error(&self.resource_dir, error)))
+    }
 }
 
 impl State<GameData<'static, 'static>, WestinyEvent> for ConnectState {
     fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
         let mut world = data.world;
-
-        world.insert(get_server_address());
+        self.started_at = world.read_resource::<Time>().absolute_time();
+        log::info!("Connecting to {}", world.read_resource::<ServerAddress>().address);
 
         let mut dispatcher_builder = DispatcherBuilder::new();
 
@@ -45,8 +54,8 @@ impl State<GameData<'static, 'static>, WestinyEvent> for ConnectState {
     }
 
     fn handle_event(&mut self, data: StateData<'_, GameData<'_, '_>>, event: WestinyEvent) -> Trans<GameData<'static, 'static>, WestinyEvent> {
-        if let WestinyEvent::App(app_event) = event {
-            match app_event {
+        match event {
+            WestinyEvent::App(app_event) => match app_event {
                 AppEvent::Connection(result) => {
                     match result {
                         Ok(init_data) => {
@@ -55,18 +64,29 @@ impl State<GameData<'static, 'static>, WestinyEvent> for ConnectState {
                             Trans::Switch(Box::new(super::game_states::PlayState::new(&self.resource_dir, )))
                         }
                         Err(refuse_cause) => {
-                            log::error!("Connection refused. Cause: {}", refuse_cause);
-                            Trans::Quit
+                            self.back_to_menu(format!("Connection refused: {}", refuse_cause))
                         }
                     }
                 }
-                AppEvent::Disconnect => {
+                AppEvent::Disconnect(_) => {
                     log::error!("Invalid Disconnect event received in ConnectState");
+                    Trans::None
+                }
+                AppEvent::MapChange(_) => {
+                    // Not in the game yet, the initial data names the map being played
+                    Trans::None
+                }
+            },
+            WestinyEvent::EngineEvent(StateEvent::Window(event)) => {
+                if is_close_requested(&event) {
                     Trans::Quit
+                } else if is_key_down(&event, VirtualKeyCode::Escape) {
+                    self.back_to_menu("Connection cancelled".to_string())
+                } else {
+                    Trans::None
                 }
             }
-        } else {
-            Trans::None
+            WestinyEvent::EngineEvent(_) => Trans::None,
         }
     }
 
@@ -75,32 +95,12 @@ impl State<GameData<'static, 'static>, WestinyEvent> for ConnectState {
         if let Some(dispatcher) = self.dispatcher.as_mut() {
             dispatcher.dispatch(&data.world);
         }
-        Trans::None
-    }
-}
-
-fn get_server_address() -> ServerAddress {
-    let address_result = std::env::var("WESTINY_SERVER_ADDRESS")
-        .map_err(|err| {
-            anyhow::Error::from(err)
-        })
-        .and_then(|env| SocketAddr::from_str(&env)
-            .map_err(|err|anyhow::Error::from(err)))
-        .map(|addr| ServerAddress { address:addr });
 
-    match address_result {
-        Ok(addr) => {
-            log::info!("Server address: {}", addr.address);
-            addr
-        }
-        Err(err) => {
-            let addr = ServerAddress::default();
-            log::warn!("Server address has not been configured. Error: {}. Using default address: {}", err, addr.address);
-            addr
+        let elapsed = data.world.read_resource::<Time>().absolute_time() - self.started_at;
+        if elapsed >= CONNECTION_TIMEOUT {
+            let address = data.world.read_resource::<ServerAddress>().address;
+            return self.back_to_menu(format!("Server {} did not respond", address));
         }
+        Trans::None
     }
 }
-
-
-
-
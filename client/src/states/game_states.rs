@@ -2,16 +2,18 @@ use amethyst::{
     core::{
         transform::Transform,
         math::{Point2, Vector3},
-        ecs::{Dispatcher, DispatcherBuilder},
+        ecs::{Dispatcher, DispatcherBuilder, Entity, Join},
         ArcThreadPool,
     },
     input::{is_close_requested, is_key_down, VirtualKeyCode},
     prelude::*,
     renderer::Camera,
+    shrev::EventChannel,
     window::ScreenDimensions,
+    winit::Event,
 };
 use std::path::PathBuf;
-use amethyst::renderer::SpriteRender;
+use amethyst::renderer::{SpriteRender, resources::Tint};
 
 use crate::systems::{
     AudioPlayerSystem,
@@ -28,21 +30,35 @@ use crate::systems::{
     LifespanSystem,
     PlayerUpdateSystemDesc,
     CollisionBundle,
+    ExplosionEffectSystemDesc,
+    TeamColorSystemDesc,
+    MatchStatusSystemDesc,
+    ScoreboardSystemDesc,
+    FlagStatusSystemDesc,
+    FogOfWarSystem,
+    SafeZoneSystemDesc,
+    SpectatorSystemDesc,
+    ChatSystemDesc,
+    ReplayPlaybackSystem,
 };
 use crate::resources::{
     initialize_audio,
     initialize_hud,
     NotificationBar,
+    ChatInput,
+    ChatPanel,
     initialize_sprite_resource,
     SpriteResource,
-    PlayerNetworkId
+    PlayerNetworkId,
+    ReplayPlayback,
+    Spectating,
 };
-use crate::entities::initialize_tilemap;
+use crate::entities::{initialize_tilemap, barrel_tint};
 
 use westiny_common::{
-    components::BoundingCircle,
+    components::{BoundingCircle, EntityType, NetworkId},
     events::{AppEvent, WestinyEvent},
-    network::ClientInitialData,
+    network::{ClientInitialData, MapChange, PlayerNotification},
     resources::{AudioQueue, Seed, map::build_map}
 };
 use amethyst::core::SystemBundle;
@@ -51,6 +67,8 @@ use amethyst::core::SystemBundle;
 pub struct PlayState {
     dispatcher: Option<Dispatcher<'static, 'static>>,
     resource_dir: PathBuf,
+    /// Watching a replay instead of playing, the packets come from the ReplayPlayback resource
+    replay: bool,
 }
 
 impl PlayState {
@@ -58,23 +76,57 @@ impl PlayState {
         PlayState {
             dispatcher: Default::default(),
             resource_dir: resource_dir.to_path_buf(),
+            replay: false,
         }
     }
 
-    fn place_objects(&self, world: &mut World, seed: Seed) {
+    pub fn replay(resource_dir: &std::path::Path) -> Self {
+        PlayState {
+            replay: true,
+            ..PlayState::new(resource_dir)
+        }
+    }
+
+    fn place_objects(&self, world: &mut World, map: &str, seed: Seed, destroyed_objects: &[NetworkId]) {
         let entities = build_map(world,
+                  map,
                   seed,
                   &self.resource_dir.join("map"))
             .expect("Map could not be created");
 
         let sprite_resource = world.fetch_mut::<SpriteResource>();
         let mut sprite_storage = world.write_storage::<SpriteRender>();
+        let mut tint_storage = world.write_storage::<Tint>();
+        let network_ids = world.read_storage::<NetworkId>();
+        let entity_storage = world.entities();
 
         entities.iter().for_each(|(entity, sprite_id)| {
+            if let Some(network_id) = network_ids.get(*entity) {
+                // Objects destroyed before joining the game
+                if destroyed_objects.contains(network_id) {
+                    entity_storage.delete(*entity).expect("Unable to delete destroyed map object");
+                    return;
+                }
+
+                if let Some(tint) = barrel_tint(network_id) {
+                    tint_storage.insert(*entity, tint).expect("Unable to add tint to entity during map build");
+                }
+            }
+
             let sprite_render = sprite_resource.sprite_render_for(*sprite_id);
             sprite_storage.insert(*entity, sprite_render).expect("Unable to add sprite to entity during map build");
         })
     }
+
+    /// Only the players are kept, the server sends the state of its other entities again
+    fn change_map(&self, world: &mut World, change: &MapChange) {
+        let map_entities: Vec<Entity> = (&world.entities(), &world.read_storage::<NetworkId>()).join()
+            .filter(|(_, network_id)| network_id.entity_type != EntityType::Player)
+            .map(|(entity, _)| entity)
+            .collect();
+        world.delete_entities(&map_entities).expect("Unable to delete the objects of the previous map");
+        self.place_objects(world, &change.map, change.seed, &[]);
+    }
 }
 
 impl State<GameData<'static, 'static>, WestinyEvent> for PlayState {
@@ -84,6 +136,10 @@ impl State<GameData<'static, 'static>, WestinyEvent> for PlayState {
         let sprite_resource = initialize_sprite_resource(&mut world);
 
         let mut dispatcher_builder = DispatcherBuilder::new();
+        if self.replay {
+            dispatcher_builder.add(ReplayPlaybackSystem, "replay_playback", &[]);
+        }
+        let message_receiver_deps: &[&str] = if self.replay { &["replay_playback"] } else { &[] };
 
         let network_message_receiver_sys = NetworkMessageReceiverSystemDesc::default().build(&mut world);
         let network_entity_update_sys = NetworkEntityStateUpdateSystemDesc::default().build(&mut world);
@@ -91,13 +147,21 @@ impl State<GameData<'static, 'static>, WestinyEvent> for PlayState {
         let player_update_system = PlayerUpdateSystemDesc::default().build(&mut world);
         let notification_bar_sys = NotificationBarSystemDesc::default().build(&mut world);
         let shooter_system = ShooterSystemDesc::default().build(&mut world);
+        let explosion_effect_system = ExplosionEffectSystemDesc::default().build(&mut world);
+        let team_color_system = TeamColorSystemDesc::default().build(&mut world);
+        let match_status_system = MatchStatusSystemDesc::default().build(&mut world);
+        let scoreboard_system = ScoreboardSystemDesc::default().build(&mut world);
+        let flag_status_system = FlagStatusSystemDesc::default().build(&mut world);
+        let safe_zone_system = SafeZoneSystemDesc::default().build(&mut world);
+        let spectator_system = SpectatorSystemDesc::default().build(&mut world);
+        let chat_system = ChatSystemDesc::default().build(&mut world);
 
         dispatcher_builder = dispatcher_builder
-            .with(network_message_receiver_sys, "network_message_receiver", &[])
+            .with(network_message_receiver_sys, "network_message_receiver", message_receiver_deps)
             .with(network_entity_update_sys, "network_entity_update", &[])
-            .with(CameraMovementSystem, "camera_movement_system", &["network_entity_update"])
+            .with(spectator_system, "spectator", &["network_message_receiver"])
+            .with(CameraMovementSystem::default(), "camera_movement_system", &["network_entity_update", "spectator"])
             .with(CursorPosUpdateSystem, "cursor_pos_update_system", &["camera_movement_system"])
-            .with(InputStateSystem, "input_state_system", &["cursor_pos_update_system"])
             .with(PhysicsSystem, "physics", &[])
             .with(player_update_system, "player_update", &["network_message_receiver"])
             .with(shooter_system, "shooter", &["network_message_receiver"])
@@ -105,8 +169,21 @@ impl State<GameData<'static, 'static>, WestinyEvent> for PlayState {
             .with(AudioPlayerSystem, "audio_player_system", &["cursor_pos_update_system"])
             .with(HudUpdateSystem, "hud_update_system", &["player_update"])
             .with(notification_bar_sys, "notification_bar", &["network_message_receiver"])
+            .with(chat_system, "chat", &["network_message_receiver"])
+            .with(explosion_effect_system, "explosion_effect", &["network_message_receiver"])
+            .with(team_color_system, "team_color", &["network_entity_update"])
+            .with(match_status_system, "match_status", &["network_message_receiver"])
+            .with(scoreboard_system, "scoreboard", &["network_message_receiver"])
+            .with(flag_status_system, "flag_status", &["network_message_receiver", "network_entity_update"])
+            .with(safe_zone_system, "safe_zone", &["network_message_receiver", "network_entity_update"])
+            .with(FogOfWarSystem::default(), "fog_of_war", &["network_entity_update"])
             .with_pool((*world.read_resource::<ArcThreadPool>()).clone());
 
+        // Nothing is sent to the server while watching a replay
+        if !self.replay {
+            dispatcher_builder.add(InputStateSystem, "input_state_system", &["cursor_pos_update_system"]);
+        }
+
         CollisionBundle.build(world, &mut dispatcher_builder).expect("Unable to build CollisionBundle");
         dispatcher_builder.add(entity_delete_system, "entitiy_delete", &["network_entity_update", "projectile_collision_handler"]);
 
@@ -123,14 +200,17 @@ impl State<GameData<'static, 'static>, WestinyEvent> for PlayState {
 
         let init_data = (*world.read_resource::<ClientInitialData>()).clone();
         world.insert(PlayerNetworkId(init_data.player_network_id));
+        world.insert(Spectating(init_data.player_network_id.is_none()));
 
         initialize_tilemap(world, &sprite_resource, Point2::new(0.0, 0.0));
         initialize_audio(world);
 
         world.register::<BoundingCircle>();
-        self.place_objects(&mut world, init_data.seed);
+        world.register::<Tint>();
+        self.place_objects(&mut world, &init_data.map, init_data.seed, &init_data.destroyed_objects);
         initialize_hud(&mut world);
         NotificationBar::initialize(&mut world);
+        ChatPanel::initialize(&mut world);
     }
 
     fn on_stop(&mut self, data: StateData<GameData<'_, '_>>) {
@@ -141,21 +221,29 @@ impl State<GameData<'static, 'static>, WestinyEvent> for PlayState {
 
     fn handle_event(
         &mut self,
-        _data: StateData<'_, GameData<'_, '_>>,
+        data: StateData<'_, GameData<'_, '_>>,
         event: WestinyEvent
     ) -> Trans<GameData<'static, 'static>, WestinyEvent> {
         match event {
             WestinyEvent::EngineEvent(engine_event) => {
                 if let StateEvent::Window(event) = engine_event {
-                    if is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape) {
+                    // Escape only closes the chat while typing
+                    let is_chatting = data.world.try_fetch::<ChatInput>().map_or(false, |chat| chat.active);
+                    if is_close_requested(&event) || (is_key_down(&event, VirtualKeyCode::Escape) && !is_chatting) {
                         return Trans::Quit;
                     }
+                    if self.replay && !is_chatting {
+                        control_replay(data.world, &event);
+                    }
                 }
             }
             WestinyEvent::App(app_event) => {
-                if let AppEvent::Disconnect = &app_event
-                {
-                    return Trans::Switch(Box::new(super::connection::ConnectState::new(&self.resource_dir)));
+                match app_event {
+                    AppEvent::Disconnect(reason) => {
+                        return Trans::Switch(Box::new(super::menu::MenuState::with_error(&self.resource_dir, reason)));
+                    }
+                    AppEvent::MapChange(change) => self.change_map(data.world, &change),
+                    AppEvent::Connection(_) => {}
                 }
             }
         }
@@ -172,6 +260,25 @@ impl State<GameData<'static, 'static>, WestinyEvent> for PlayState {
     }
 }
 
+/// P pauses, the left and right arrows seek, the up and down arrows change the speed
+fn control_replay(world: &mut World, event: &Event) {
+    let mut playback = world.write_resource::<ReplayPlayback>();
+    if is_key_down(event, VirtualKeyCode::P) {
+        playback.toggle_pause();
+    } else if is_key_down(event, VirtualKeyCode::Left) {
+        playback.seek_backward();
+    } else if is_key_down(event, VirtualKeyCode::Right) {
+        playback.seek_forward();
+    } else if is_key_down(event, VirtualKeyCode::Up) {
+        playback.faster();
+    } else if is_key_down(event, VirtualKeyCode::Down) {
+        playback.slower();
+    } else {
+        return;
+    }
+    world.write_resource::<EventChannel<PlayerNotification>>().single_write(PlayerNotification { message: playback.status() });
+}
+
 const CAMERA_ALTITUDE: f32 = 3.0;
 const CAMERA_DEPTH_VISION: f32 = CAMERA_ALTITUDE + 1.0;
 
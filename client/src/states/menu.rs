@@ -0,0 +1,390 @@
+use amethyst::{
+    assets::Loader,
+    ecs::{Entity, EntityBuilder},
+    input::{is_close_requested, is_key_down, VirtualKeyCode},
+    prelude::*,
+    ui::{Anchor, FontHandle, Interactable, LineMode, Selectable, TextEditing, TtfFormat, UiEvent, UiEventType, UiText, UiTransform},
+};
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use westiny_common::discovery::{DiscoveredServer, DiscoveryClient, ServerInfo};
+use westiny_common::events::WestinyEvent;
+use westiny_common::resources::ServerAddress;
+use westiny_common::player_name::{PlayerName, MAX_PLAYER_NAME_LENGTH};
+use crate::resources::{Profile, RecentServers, ServerPassword};
+
+const MAX_ADDRESS_LENGTH: usize = 64;
+const MAX_PASSWORD_LENGTH: usize = 64;
+const WHITE: [f32; 4] = [1., 1., 1., 1.];
+const GREY: [f32; 4] = [0.7, 0.7, 0.7, 1.];
+const TEXT_WIDTH: f32 = 600.;
+const COLUMN_WIDTH: f32 = 380.;
+const SERVER_LIST_TOP: f32 = -165.;
+const SERVER_LIST_SPACING: f32 = 30.;
+
+struct MenuUi {
+    font: FontHandle,
+    name_field: Entity,
+    address_field: Entity,
+    password_field: Entity,
+    connect_button: Entity,
+    search_button: Entity,
+    /// Recent and LAN servers, clicking them fills the address field
+    server_buttons: Vec<(Entity, SocketAddr)>,
+    lan_server_count: usize,
+    error: Entity,
+    /// Every entity of the menu, they are deleted when the menu is left
+    entities: Vec<Entity>,
+}
+
+/// Lets the player enter a name, choose the server and enter its password before connecting.
+/// Connection errors are shown here, so the player can retry.
+pub struct MenuState {
+    resource_dir: PathBuf,
+    error: Option<String>,
+    recent_servers: RecentServers,
+    /// Present while searching for servers on the LAN
+    discovery: Option<DiscoveryClient>,
+    ui: Option<MenuUi>,
+}
+
+impl MenuState {
+    pub fn new(resource_dir: &Path) -> Self {
+        MenuState {
+            resource_dir: resource_dir.to_path_buf(),
+            error: None,
+            recent_servers: RecentServers::load(),
+            discovery: None,
+            ui: None,
+        }
+    }
+
+    pub fn with_error(resource_dir: &Path, error: String) -> Self {
+        MenuState {
+            error: Some(error),
+            ..MenuState::new(resource_dir)
+        }
+    }
+
+    fn create_ui(&self, world: &mut World) -> MenuUi {
+        let font = {
+            let loader = world.read_resource::<Loader>();
+            loader.load("fonts/square.ttf", TtfFormat, (), &world.read_resource())
+        };
+        if !world.has_value::<Profile>() {
+            world.insert(Profile::load());
+        }
+        let player_name = world.read_resource::<Profile>().name.clone();
+        let address = default_server_address(
+            world.try_fetch::<ServerAddress>().map(|server| server.address),
+            &self.recent_servers,
+        );
+        let password = default_server_password(world.try_fetch::<ServerPassword>().and_then(|password| password.0.clone()));
+
+        let mut entities = Vec::new();
+        let mut text = |world: &mut World, content: &str, x: f32, y: f32, size: f32, color: [f32; 4]| {
+            let entity = create_text(world, &font, content, x, y, TEXT_WIDTH, size, color).build();
+            entities.push(entity);
+            entity
+        };
+
+        text(world, "Westiny", 0., 180., 48., WHITE);
+        text(world, "Name:", -220., 110., 24., GREY);
+        text(world, "Server:", -220., 70., 24., GREY);
+        text(world, "Password:", -220., 30., 24., GREY);
+        text(world, "Recent servers", -200., -130., 20., GREY);
+        text(world, "LAN servers", 200., -130., 20., GREY);
+
+        let name_field = create_text(world, &font, &player_name, 60., 110., TEXT_WIDTH, 24., WHITE)
+            .with(TextEditing::new(MAX_PLAYER_NAME_LENGTH, [0., 0., 0., 1.], [1., 1., 1., 1.], false))
+            .with(Selectable::<()>::new(0))
+            .with(Interactable)
+            .build();
+        let address_field = create_text(world, &font, &address.to_string(), 60., 70., TEXT_WIDTH, 24., WHITE)
+            .with(TextEditing::new(MAX_ADDRESS_LENGTH, [0., 0., 0., 1.], [1., 1., 1., 1.], false))
+            .with(Selectable::<()>::new(1))
+            .with(Interactable)
+            .build();
+        let password_field = create_text(world, &font, &password, 60., 30., TEXT_WIDTH, 24., WHITE)
+            .with(TextEditing::new(MAX_PASSWORD_LENGTH, [0., 0., 0., 1.], [1., 1., 1., 1.], false))
+            .with(Selectable::<()>::new(2))
+            .with(Interactable)
+            .build();
+        if let Some(text) = world.write_storage::<UiText>().get_mut(password_field) {
+            text.password = true;
+        }
+        let connect_button = create_text(world, &font, "[ Connect ]", -120., -30., COLUMN_WIDTH, 28., WHITE)
+            .with(Interactable)
+            .build();
+        let search_button = create_text(world, &font, "[ Search LAN ]", 120., -30., COLUMN_WIDTH, 28., WHITE)
+            .with(Interactable)
+            .build();
+        let error = text(world, self.error.as_deref().unwrap_or(""), 0., -80., 20., [1., 0.3, 0.3, 1.]);
+
+        let server_buttons: Vec<_> = self.recent_servers.addresses().iter()
+            .enumerate()
+            .map(|(index, address)| {
+                let y = SERVER_LIST_TOP - SERVER_LIST_SPACING * index as f32;
+                let button = create_text(world, &font, &address.to_string(), -200., y, COLUMN_WIDTH, 20., WHITE)
+                    .with(Interactable)
+                    .build();
+                (button, *address)
+            })
+            .collect();
+
+        entities.extend(&[name_field, address_field, password_field, connect_button, search_button]);
+        entities.extend(server_buttons.iter().map(|(button, _)| *button));
+        MenuUi {
+            font,
+            name_field,
+            address_field,
+            password_field,
+            connect_button,
+            search_button,
+            server_buttons,
+            lan_server_count: 0,
+            error,
+            entities,
+        }
+    }
+
+    fn show_error(&self, world: &mut World, error: String) {
+        if let Some(ui) = &self.ui {
+            if let Some(text) = world.write_storage::<UiText>().get_mut(ui.error) {
+                text.text = error;
+            }
+        }
+    }
+
+    /// Broadcasts a query, the answers are collected while the menu is shown
+    fn search_lan(&mut self, world: &mut World) {
+        let discovery = match self.discovery.take() {
+            Some(discovery) => Ok(discovery),
+            None => DiscoveryClient::bind(),
+        };
+        match discovery.and_then(|discovery| discovery.broadcast().map(|_| discovery)) {
+            Ok(discovery) => self.discovery = Some(discovery),
+            Err(err) => self.show_error(world, format!("LAN search failed: {}", err)),
+        }
+    }
+
+    fn add_lan_servers(&mut self, world: &mut World, servers: Vec<DiscoveredServer>) {
+        let ui = match &mut self.ui {
+            Some(ui) => ui,
+            None => return,
+        };
+
+        for server in servers {
+            let lan_server_addresses = ui.server_buttons[ui.server_buttons.len() - ui.lan_server_count..].iter();
+            if lan_server_addresses.map(|(_, address)| address).any(|address| *address == server.address) {
+                continue;
+            }
+
+            let y = SERVER_LIST_TOP - SERVER_LIST_SPACING * ui.lan_server_count as f32;
+            let color = if server.info.is_compatible() { WHITE } else { GREY };
+            let button = create_text(world, &ui.font, &format_server(&server.info), 200., y, COLUMN_WIDTH, 20., color)
+                .with(Interactable)
+                .build();
+            ui.server_buttons.push((button, server.address));
+            ui.entities.push(button);
+            ui.lan_server_count += 1;
+        }
+    }
+
+    fn connect(&mut self, world: &mut World) -> Trans<GameData<'static, 'static>, WestinyEvent> {
+        let ui = match &self.ui {
+            Some(ui) => ui,
+            None => return Trans::None,
+        };
+
+        let (player_name, address, password) = {
+            let texts = world.read_storage::<UiText>();
+            let text_of = |entity| texts.get(entity).map(|text| text.text.clone()).unwrap_or_default();
+            (text_of(ui.name_field), text_of(ui.address_field), text_of(ui.password_field))
+        };
+
+        match validate_input(&player_name, &address) {
+            Ok((player_name, address)) => {
+                self.recent_servers.add(address);
+                if let Err(err) = self.recent_servers.save() {
+                    log::warn!("Recent servers could not be saved: {}", err);
+                }
+                {
+                    let mut profile = world.write_resource::<Profile>();
+                    profile.name = player_name;
+                    if let Err(err) = profile.save() {
+                        log::warn!("Profile could not be saved: {}", err);
+                    }
+                }
+                world.insert(ServerAddress { address });
+                world.insert(ServerPassword(Some(password).filter(|password| !password.is_empty())));
+                Trans::Switch(Box::new(super::connection::ConnectState::new(&self.resource_dir)))
+            }
+            Err(err) => {
+                self.show_error(world, err);
+                Trans::None
+            }
+        }
+    }
+
+    fn select_server(&self, world: &mut World, clicked: Entity) {
+        if let Some(ui) = &self.ui {
+            let address = ui.server_buttons.iter()
+                .find(|(button, _)| *button == clicked)
+                .map(|(_, address)| address);
+            if let (Some(address), Some(text)) = (address, world.write_storage::<UiText>().get_mut(ui.address_field)) {
+                text.text = address.to_string();
+            }
+        }
+    }
+}
+
+impl State<GameData<'static, 'static>, WestinyEvent> for MenuState {
+    fn on_start(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(error) = &self.error {
+            log::error!("{}", error);
+        }
+        self.ui = Some(self.create_ui(data.world));
+    }
+
+    fn on_stop(&mut self, data: StateData<'_, GameData<'_, '_>>) {
+        if let Some(ui) = self.ui.take() {
+            if let Err(err) = data.world.delete_entities(&ui.entities) {
+                log::error!("Menu could not be removed: {}", err);
+            }
+        }
+    }
+
+    fn handle_event(&mut self, data: StateData<'_, GameData<'_, '_>>, event: WestinyEvent) -> Trans<GameData<'static, 'static>, WestinyEvent> {
+        match event {
+            WestinyEvent::EngineEvent(StateEvent::Window(event)) => {
+                if is_close_requested(&event) || is_key_down(&event, VirtualKeyCode::Escape) {
+                    Trans::Quit
+                } else if is_key_down(&event, VirtualKeyCode::Return) {
+                    self.connect(data.world)
+                } else {
+                    Trans::None
+                }
+            }
+            WestinyEvent::EngineEvent(StateEvent::Ui(UiEvent { event_type: UiEventType::Click, target })) => {
+                if self.ui.as_ref().map_or(false, |ui| ui.connect_button == target) {
+                    self.connect(data.world)
+                } else if self.ui.as_ref().map_or(false, |ui| ui.search_button == target) {
+                    self.search_lan(data.world);
+                    Trans::None
+                } else {
+                    self.select_server(data.world, target);
+                    Trans::None
+                }
+            }
+            _ => Trans::None,
+        }
+    }
+
+    fn update(&mut self, data: StateData<GameData<'_, '_>>) -> Trans<GameData<'static, 'static>, WestinyEvent> {
+        data.data.update(&data.world);
+
+        let answers = self.discovery.as_ref().map(|discovery| discovery.receive());
+        match answers {
+            Some(Ok(servers)) => self.add_lan_servers(data.world, servers),
+            Some(Err(err)) => {
+                log::warn!("LAN search failed: {}", err);
+                self.discovery = None;
+            }
+            None => {}
+        }
+        Trans::None
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn create_text<'a>(world: &'a mut World, font: &FontHandle, content: &str, x: f32, y: f32, width: f32, size: f32, color: [f32; 4]) -> EntityBuilder<'a> {
+    world.create_entity()
+        .with(UiTransform::new(
+            content.to_string(),
+            Anchor::Middle,
+            Anchor::Middle,
+            x, y, 1.,
+            width, size * 1.5,
+        ))
+        .with(UiText::new(
+            font.clone(),
+            content.to_string(),
+            color,
+            size,
+            LineMode::Single,
+            Anchor::Middle,
+        ))
+}
+
+/// The address of the running session is kept. Otherwise the one set in the
+/// WESTINY_SERVER_ADDRESS environment variable, or the last used server is offered.
+fn default_server_address(current: Option<SocketAddr>, recent_servers: &RecentServers) -> SocketAddr {
+    let from_env = std::env::var("WESTINY_SERVER_ADDRESS").ok()
+        .and_then(|env| match SocketAddr::from_str(&env) {
+            Ok(address) => Some(address),
+            Err(err) => {
+                log::warn!("Invalid WESTINY_SERVER_ADDRESS: {}", err);
+                None
+            }
+        });
+    current
+        .or(from_env)
+        .or_else(|| recent_servers.addresses().first().copied())
+        .unwrap_or(ServerAddress::default().address)
+}
+
+/// The password entered for the running session is kept, otherwise the one set in the
+/// WESTINY_SERVER_PASSWORD environment variable is offered
+fn default_server_password(current: Option<String>) -> String {
+    current
+        .or_else(|| std::env::var("WESTINY_SERVER_PASSWORD").ok())
+        .unwrap_or_default()
+}
+
+fn format_server(info: &ServerInfo) -> String {
+    let label = format!("{} - {} {}/{}", info.name, info.map, info.player_count, info.max_players);
+    if info.is_compatible() {
+        label
+    } else {
+        format!("{} (incompatible)", label)
+    }
+}
+
+fn validate_input(player_name: &str, address: &str) -> Result<(String, SocketAddr), String> {
+    let player_name = PlayerName::validate(player_name).map_err(|err| err.to_string())?;
+    let address = SocketAddr::from_str(address.trim())
+        .map_err(|_| format!("Invalid server address: {}, expected ip:port", address.trim()))?;
+    Ok((player_name.0, address))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn input_is_validated() {
+        assert_eq!(validate_input(" Joe ", "127.0.0.1:5745"), Ok(("Joe".to_string(), SocketAddr::from(([127, 0, 0, 1], 5745)))));
+        assert!(validate_input("  ", "127.0.0.1:5745").is_err());
+        assert!(validate_input("Joe\u{1b}", "127.0.0.1:5745").is_err());
+        assert!(validate_input("Joe", "localhost").is_err());
+    }
+
+    #[test]
+    fn server_label_shows_incompatible_versions() {
+        let mut info = ServerInfo {
+            name: "Saloon".to_string(),
+            map: "rust2".to_string(),
+            player_count: 3,
+            max_players: 16,
+            protocol_version: westiny_common::discovery::PROTOCOL_VERSION,
+            game_port: 5745,
+        };
+        assert_eq!(format_server(&info), "Saloon - rust2 3/16");
+
+        info.protocol_version += 1;
+        assert_eq!(format_server(&info), "Saloon - rust2 3/16 (incompatible)");
+    }
+}
@@ -1,2 +1,4 @@
 pub mod connection;
 pub mod game_states;
+pub mod menu;
+pub mod replay;
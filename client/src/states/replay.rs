@@ -0,0 +1,57 @@
+use amethyst::prelude::*;
+use std::path::{Path, PathBuf};
+use westiny_common::events::WestinyEvent;
+use westiny_common::network::{ClientInitialData, MapChange};
+use westiny_common::replay::Replay;
+use westiny_common::resources::map::available_maps;
+
+use crate::resources::ReplayPlayback;
+
+/// Set WESTINY_REPLAY to the path of a replay file to watch it instead of playing
+pub fn replay_file() -> Option<PathBuf> {
+    std::env::var_os("WESTINY_REPLAY").map(PathBuf::from)
+}
+
+/// Loads the replay file and starts the playback, errors are shown in the menu
+pub struct ReplayState {
+    resource_dir: PathBuf,
+    file: PathBuf,
+}
+
+impl ReplayState {
+    pub fn new(resource_dir: &Path, file: PathBuf) -> Self {
+        ReplayState {
+            resource_dir: resource_dir.to_path_buf(),
+            file,
+        }
+    }
+
+    fn back_to_menu(&self, error: String) -> Trans<GameData<'static, 'static>, WestinyEvent> {
+        Trans::Switch(Box::new(super::menu::MenuState::with_error(&self.resource_dir, error)))
+    }
+}
+
+impl State<GameData<'static, 'static>, WestinyEvent> for ReplayState {
+    fn update(&mut self, data: StateData<GameData<'_, '_>>) -> Trans<GameData<'static, 'static>, WestinyEvent> {
+        let replay = match Replay::load(&self.file) {
+            Ok(replay) => replay,
+            Err(err) => return self.back_to_menu(format!("Replay {} could not be loaded: {}", self.file.display(), err)),
+        };
+        let maps = available_maps(&self.resource_dir.join("map")).unwrap_or_default();
+        if !maps.contains(&replay.header.map) {
+            return self.back_to_menu(format!("The replay was recorded on an unknown map: {}", replay.header.map));
+        }
+        log::info!("Playing {}, {} frames", self.file.display(), replay.frames.len());
+
+        // The replay is watched as an observer
+        let first_map = MapChange { map: replay.header.map.clone(), seed: replay.header.seed };
+        data.world.insert(ClientInitialData {
+            player_network_id: None,
+            map: replay.header.map,
+            seed: replay.header.seed,
+            destroyed_objects: replay.header.destroyed_objects,
+        });
+        data.world.insert(ReplayPlayback::new(replay.frames, first_map));
+        Trans::Switch(Box::new(super::game_states::PlayState::replay(&self.resource_dir)))
+    }
+}
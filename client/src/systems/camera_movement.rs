@@ -1,22 +1,33 @@
 use amethyst::input::InputHandler;
-use amethyst::derive::SystemDesc;
-use amethyst::ecs::{Read, System, SystemData, ReadStorage, WriteStorage};
-use amethyst::core::Transform;
-use amethyst::core::math::Vector3;
+use amethyst::ecs::{Read, ReadExpect, System, ReadStorage, WriteStorage};
+use amethyst::core::{Time, Transform};
+use amethyst::core::math::{Vector2, Vector3};
+use amethyst::ui::UiText;
 
 use amethyst::ecs::prelude::Join;
 use amethyst::renderer::Camera;
 
-use westiny_common::components::Player;
-use crate::bindings::{MovementBindingTypes, AxisBinding};
+use westiny_common::components::{EntityType, NetworkId, Player};
+use westiny_common::metric_dimension::length::Meter;
+use crate::bindings::{MovementBindingTypes, ActionBinding, AxisBinding};
+use crate::resources::{Hud, Spectating};
 
 
-#[derive(SystemDesc)]
-pub struct CameraMovementSystem;
+/// Follows the player's character. Without a character it follows the chosen living player
+/// or flies freely.
+#[derive(Default)]
+pub struct CameraMovementSystem {
+    /// The followed player while there is no own character
+    target: Option<NetworkId>,
+    free_camera: bool,
+    next_target_held: bool,
+    free_camera_held: bool,
+}
 
 const MIN_ZOOM: f32 = 1.0; // 1x (1:1 screenpixel:spritepixel)
 const MAX_ZOOM: f32 = 8.0; // N:1 (N screen pixel will show 1 spritepixel)
 const STEP: f32 = 1.0;
+const FREE_CAMERA_SPEED: Meter = Meter(12.0); // per second
 
 impl<'s> System<'s> for CameraMovementSystem {
     type SystemData = (
@@ -24,14 +35,56 @@ impl<'s> System<'s> for CameraMovementSystem {
         ReadStorage<'s, Camera>,
         Read<'s, InputHandler<MovementBindingTypes>>,
         ReadStorage<'s, Player>,
+        ReadStorage<'s, NetworkId>,
+        Read<'s, Spectating>,
+        Read<'s, Time>,
+        ReadExpect<'s, Hud>,
+        WriteStorage<'s, UiText>,
     );
 
-    fn run(&mut self, (mut transforms, cameras, input, players): Self::SystemData) {
+    fn run(&mut self, (mut transforms, cameras, input, players, network_ids, spectating, time, hud, mut ui_texts): Self::SystemData) {
+        let has_character = (&players, &transforms).join().next().is_some();
+        let next_target_pressed = just_pressed(&mut self.next_target_held, &input, &ActionBinding::NextTarget);
+        let free_camera_pressed = just_pressed(&mut self.free_camera_held, &input, &ActionBinding::FreeCamera);
+
+        let player_pos = if has_character {
+            self.target = None;
+            self.free_camera = false;
+            (&players, &transforms).join().next()
+                .map(|(_, transform)| transform.translation().xy())
+        } else {
+            if free_camera_pressed {
+                self.free_camera = !self.free_camera;
+            }
+
+            let mut living_players: Vec<_> = (&network_ids, &transforms).join()
+                .map(|(network_id, _)| *network_id)
+                .filter(|network_id| network_id.entity_type == EntityType::Player)
+                .collect();
+            living_players.sort_by_key(|network_id| network_id.id);
+
+            if next_target_pressed || !self.target.map_or(false, |target| living_players.contains(&target)) {
+                self.target = next_target(self.target, &living_players);
+                self.free_camera = self.free_camera && !next_target_pressed;
+            }
+
+            if self.free_camera {
+                None
+            } else {
+                (&network_ids, &transforms).join()
+                    .find(|(network_id, _)| Some(**network_id) == self.target)
+                    .map(|(_, transform)| transform.translation().xy())
+            }
+        };
+
+        if let Some(text) = ui_texts.get_mut(hud.spectator) {
+            text.text = if has_character {
+                String::new()
+            } else {
+                spectator_hint(spectating.0, self.free_camera)
+            };
+        }
 
-        let player_pos = (&players, &transforms).join().next()
-            .map(|(_, transform)| transform)
-            .map(Transform::translation)
-            .map(&Vector3::xy);
         if let Some((_camera, transform)) = (&cameras, &mut transforms).join().next() {
             // Emulated axis value is from [-1, 1].
             // -1 when "neg" is pressed
@@ -56,7 +109,60 @@ impl<'s> System<'s> for CameraMovementSystem {
             if let Some(player_coord) = player_pos {
                 transform.set_translation_x(player_coord.x);
                 transform.set_translation_y(player_coord.y);
+            } else if self.free_camera {
+                let direction = free_camera_direction(&input);
+                let step = FREE_CAMERA_SPEED.into_pixel() * time.delta_seconds();
+                transform.prepend_translation(Vector3::new(direction.x * step, direction.y * step, 0.0));
             }
         }
     }
 }
+
+/// True only in the frame the action gets pressed
+fn just_pressed(held: &mut bool, input: &InputHandler<MovementBindingTypes>, action: &ActionBinding) -> bool {
+    let is_down = input.action_is_down(action).unwrap_or(false);
+    let pressed = is_down && !*held;
+    *held = is_down;
+    pressed
+}
+
+/// The player following the current one, or the first if the current one is gone
+fn next_target(current: Option<NetworkId>, living_players: &[NetworkId]) -> Option<NetworkId> {
+    let next_index = current
+        .and_then(|target| living_players.iter().position(|network_id| *network_id == target))
+        .map_or(0, |index| index + 1);
+    living_players.get(next_index).or_else(|| living_players.first()).copied()
+}
+
+fn free_camera_direction(input: &InputHandler<MovementBindingTypes>) -> Vector2<f32> {
+    let is_down = |action| if input.action_is_down(&action).unwrap_or(false) { 1.0 } else { 0.0 };
+    Vector2::new(
+        is_down(ActionBinding::StrafeRight) - is_down(ActionBinding::StrafeLeft),
+        is_down(ActionBinding::Forward) - is_down(ActionBinding::Backward),
+    )
+}
+
+fn spectator_hint(spectating: bool, free_camera: bool) -> String {
+    let status = if spectating { "Spectating" } else { "Waiting for respawn" };
+    let camera = if free_camera { "free camera, WASD to move" } else { "following a player" };
+    format!("{} - {} (Space: next player, F: free camera)", status, camera)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn player(id: u32) -> NetworkId {
+        NetworkId::new(EntityType::Player, id)
+    }
+
+    #[test]
+    fn next_target_cycles_through_living_players() {
+        let living_players = vec![player(1), player(4), player(7)];
+        assert_eq!(next_target(None, &living_players), Some(player(1)));
+        assert_eq!(next_target(Some(player(4)), &living_players), Some(player(7)));
+        assert_eq!(next_target(Some(player(7)), &living_players), Some(player(1)));
+        assert_eq!(next_target(Some(player(5)), &living_players), Some(player(1)));
+        assert_eq!(next_target(Some(player(1)), &[]), None);
+    }
+}
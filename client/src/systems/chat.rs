@@ -0,0 +1,89 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, Write, WriteExpect, WriteStorage},
+    input::{InputEvent, VirtualKeyCode},
+    network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement},
+    shrev::{ReaderId, EventChannel},
+    ui::UiText,
+};
+use derive_new::new;
+
+use crate::bindings::MovementBindingTypes;
+use crate::resources::{format_chat_input, format_chat_message, ChatInput, ChatPanel, StreamId};
+use westiny_common::network::{ChatMessage, PacketType};
+use westiny_common::resources::ServerAddress;
+use westiny_common::serialize;
+
+/// Enter opens the chat for everyone, T for the team only. Enter sends the message, Escape drops it.
+/// Received messages are shown on the chat panel.
+#[derive(SystemDesc, new)]
+#[system_desc(name(ChatSystemDesc))]
+pub struct ChatSystem {
+    #[system_desc(event_channel_reader)]
+    input_reader: ReaderId<InputEvent<MovementBindingTypes>>,
+
+    #[system_desc(event_channel_reader)]
+    chat_reader: ReaderId<ChatMessage>,
+}
+
+impl<'s> System<'s> for ChatSystem {
+    type SystemData = (
+        Read<'s, EventChannel<InputEvent<MovementBindingTypes>>>,
+        Read<'s, EventChannel<ChatMessage>>,
+        Write<'s, ChatInput>,
+        WriteExpect<'s, ChatPanel>,
+        WriteStorage<'s, UiText>,
+        Read<'s, ServerAddress>,
+        Write<'s, TransportResource>,
+    );
+
+    fn run(&mut self, (input_events, chat_channel, mut chat_input, mut panel, mut ui_texts, server, mut net): Self::SystemData) {
+        for message in chat_channel.read(&mut self.chat_reader) {
+            log::info!("Chat: {}", format_chat_message(message));
+            panel.messages.push(format_chat_message(message), &mut ui_texts);
+        }
+
+        let mut input_changed = false;
+        for event in input_events.read(&mut self.input_reader) {
+            match event {
+                InputEvent::KeyPressed { key_code, .. } => match (chat_input.active, key_code) {
+                    (false, VirtualKeyCode::Return) => chat_input.open(false),
+                    (true, VirtualKeyCode::Return) => {
+                        if let Some(text) = chat_input.take_message() {
+                            send_to_server(&mut net, &server, text, chat_input.team_only);
+                        }
+                    }
+                    (true, VirtualKeyCode::Escape) => chat_input.cancel(),
+                    (true, VirtualKeyCode::Back) => {
+                        chat_input.text.pop();
+                    }
+                    _ => continue,
+                },
+                InputEvent::KeyTyped(character) => {
+                    if chat_input.active {
+                        chat_input.push(*character);
+                    } else if character.eq_ignore_ascii_case(&'t') {
+                        chat_input.open(true);
+                    } else {
+                        continue;
+                    }
+                }
+                _ => continue,
+            }
+            input_changed = true;
+        }
+
+        if input_changed {
+            if let Some(text) = ui_texts.get_mut(panel.input) {
+                text.text = format_chat_input(&chat_input);
+            }
+        }
+    }
+}
+
+fn send_to_server(net: &mut TransportResource, server: &ServerAddress, text: String, team_only: bool) {
+    let message = serialize(&PacketType::ChatRequest { text, team_only })
+        .expect("ChatRequest could not be serialized");
+
+    net.send_with_requirements(server.address, &message, DeliveryRequirement::ReliableOrdered(StreamId::Chat.into()), UrgencyRequirement::OnTick);
+}
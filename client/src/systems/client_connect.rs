@@ -13,11 +13,13 @@ use std::time::Duration;
 use westiny_common::{network, deserialize, serialize};
 use westiny_common::resources::ServerAddress;
 
+use crate::resources::{Profile, ServerPassword};
+
 const RUN_EVERY_N_SEC: u64 = 1;
-const PLAYER_NAME_MAGIC: &str = "Narancsos_Feco";
 
-fn get_player_name() -> String {
-    std::env::var("USER").unwrap_or(PLAYER_NAME_MAGIC.to_string())
+/// Set WESTINY_OBSERVER to join without playing
+fn is_observer() -> bool {
+    std::env::var("WESTINY_OBSERVER").is_ok()
 }
 
 #[derive(SystemDesc)]
@@ -42,18 +44,25 @@ impl ClientConnectSystem {
 impl<'s> System<'s> for ClientConnectSystem {
     type SystemData = (
         Read<'s, ServerAddress>,
+        Read<'s, Profile>,
+        Read<'s, ServerPassword>,
         Read<'s, Time>,
         Write<'s, TransportResource>,
         Read<'s, EventChannel<NetworkSimulationEvent>>,
         Write<'s, EventChannel<AppEvent>>
     );
 
-    fn run(&mut self, (server, time, mut net, net_event_ch, mut app_event): Self::SystemData) {
+    fn run(&mut self, (server, profile, password, time, mut net, net_event_ch, mut app_event): Self::SystemData) {
         let time_since_start = time.absolute_time();
 
         if (time_since_start-self.last_run) >= Duration::from_secs(RUN_EVERY_N_SEC) {
             self.last_run = time_since_start;
-                let msg = serialize(&network::PacketType::ConnectionRequest { player_name: get_player_name() })
+                let request = network::PacketType::ConnectionRequest {
+                    player_name: profile.name.clone(),
+                    observer: is_observer(),
+                    password: password.0.clone(),
+                };
+                let msg = serialize(&request)
                     .expect("ConnectionRequest could not be serialized");
 
                 log::debug!("Sending message. Time: {}", time_since_start.as_secs_f32());
@@ -137,8 +146,10 @@ mod test {
     fn ok_init_data() -> network::Result<network::ClientInitialData> {
             Ok(
                 network::ClientInitialData {
-                    player_network_id: NetworkId::new(EntityType::Player, 0),
+                    player_network_id: Some(NetworkId::new(EntityType::Player, 0)),
+                    map: "rust2".to_string(),
                     seed: Seed(100),
+                    destroyed_objects: vec![NetworkId::new(EntityType::ExplosiveBarrel, 2)],
                 }
             )
     }
@@ -0,0 +1,57 @@
+use amethyst::{
+    core::{Transform, Time, math::Vector3},
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, ReadExpect, WriteExpect, Entities, LazyUpdate},
+    prelude::Builder,
+    renderer::{palette::Srgba, resources::Tint},
+    shrev::{ReaderId, EventChannel},
+};
+use derive_new::new;
+
+use westiny_common::components::Lifespan;
+use westiny_common::metric_dimension::Second;
+use westiny_common::network::ExplosionNotification;
+use westiny_common::resources::{AudioQueue, SoundId, SpriteId};
+use crate::resources::SpriteResource;
+
+const EXPLOSION_HEIGHT: f32 = 1.5;
+const EXPLOSION_DURATION: Second = Second(0.3);
+/// Size of the sprite used for the effect [pixel]
+const SPRITE_SIZE: f32 = 32.0;
+
+/// Shows the explosions reported by the server
+#[derive(SystemDesc, new)]
+#[system_desc(name(ExplosionEffectSystemDesc))]
+pub struct ExplosionEffectSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<ExplosionNotification>,
+}
+
+impl<'s> System<'s> for ExplosionEffectSystem {
+    type SystemData = (
+        Read<'s, EventChannel<ExplosionNotification>>,
+        Entities<'s>,
+        ReadExpect<'s, LazyUpdate>,
+        ReadExpect<'s, SpriteResource>,
+        WriteExpect<'s, AudioQueue>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (explosions, entities, lazy, sprite_resource, mut audio, time): Self::SystemData) {
+        for explosion in explosions.read(&mut self.reader) {
+            let mut transform = Transform::default();
+            transform.set_translation_xyz(explosion.position.x.into_pixel(), explosion.position.y.into_pixel(), EXPLOSION_HEIGHT);
+            let scale = 2.0 * explosion.radius.into_pixel() / SPRITE_SIZE;
+            transform.set_scale(Vector3::new(scale, scale, 1.0));
+
+            lazy.create_entity(&entities)
+                .with(transform)
+                .with(sprite_resource.sprite_render_for(SpriteId::Barrel))
+                .with(Tint(Srgba::new(1.0, 0.6, 0.1, 1.0)))
+                .with(Lifespan::new(EXPLOSION_DURATION, time.absolute_time()))
+                .build();
+
+            audio.play(SoundId::SingleShot, 1.0);
+        }
+    }
+}
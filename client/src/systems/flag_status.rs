@@ -0,0 +1,97 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, ReadExpect, ReadStorage, WriteStorage, Entities, Join},
+    renderer::resources::Tint,
+    shrev::{ReaderId, EventChannel},
+    ui::UiText,
+};
+use derive_new::new;
+
+use crate::resources::Hud;
+use super::team_color::team_tint;
+use westiny_common::components::NetworkId;
+use westiny_common::network::{FlagState, FlagStatus};
+
+/// Shows where the flags are in capture the flag mode and colors the flags by their team
+#[derive(SystemDesc, new)]
+#[system_desc(name(FlagStatusSystemDesc))]
+pub struct FlagStatusSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<Vec<FlagStatus>>,
+
+    #[system_desc(skip)]
+    #[new(default)]
+    flags: Vec<FlagStatus>,
+}
+
+impl<'s> System<'s> for FlagStatusSystem {
+    type SystemData = (
+        Read<'s, EventChannel<Vec<FlagStatus>>>,
+        Entities<'s>,
+        ReadStorage<'s, NetworkId>,
+        WriteStorage<'s, Tint>,
+        ReadExpect<'s, Hud>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (flag_status_channel, entities, network_ids, mut tints, hud, mut ui_texts): Self::SystemData) {
+        if let Some(flags) = flag_status_channel.read(&mut self.reader).last() {
+            self.flags = flags.clone();
+            if let Some(text) = ui_texts.get_mut(hud.flag_status) {
+                text.text = format_flag_status(&self.flags);
+            }
+        }
+
+        // Flags are recreated when they come into view again, so the tint is checked continuously
+        for (entity, network_id) in (&entities, &network_ids).join() {
+            if tints.contains(entity) {
+                continue;
+            }
+            if let Some(flag) = self.flags.iter().find(|flag| flag.network_id == *network_id) {
+                if let Err(err) = tints.insert(entity, team_tint(flag.team)) {
+                    log::error!("Could not color the flag {:?}: {}", network_id, err);
+                }
+            }
+        }
+    }
+}
+
+fn format_flag_status(flags: &[FlagStatus]) -> String {
+    flags.iter()
+        .map(|flag| {
+            let state = match &flag.state {
+                FlagState::AtBase => "at base".to_string(),
+                FlagState::Carried { carrier } => format!("carried by {}", carrier),
+                FlagState::Dropped => "dropped".to_string(),
+            };
+            format!("{:?} flag {} ({} captures)", flag.team, state, flag.captures)
+        })
+        .collect::<Vec<_>>()
+        .join("  |  ")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use westiny_common::PlayerName;
+    use westiny_common::components::{EntityType, Team};
+
+    #[test]
+    fn flag_status_tells_the_carrier() {
+        let flags = vec![
+            FlagStatus {
+                network_id: NetworkId::new(EntityType::Flag, 0),
+                team: Team::Red,
+                state: FlagState::Carried { carrier: PlayerName("Bob".to_string()) },
+                captures: 2,
+            },
+            FlagStatus {
+                network_id: NetworkId::new(EntityType::Flag, 1),
+                team: Team::Blue,
+                state: FlagState::AtBase,
+                captures: 0,
+            },
+        ];
+        assert_eq!(format_flag_status(&flags), "Red flag carried by Bob (2 captures)  |  Blue flag at base (0 captures)");
+    }
+}
@@ -0,0 +1,75 @@
+use amethyst::core::Transform;
+use amethyst::core::math::{Point2, Point3};
+use amethyst::ecs::{System, ReadStorage, Write, Join};
+use amethyst::tiles::{Map, MortonEncoder, TileMap};
+
+use crate::resources::{FogOfWar, GroundTile};
+use westiny_common::components::{NetworkId, Player};
+use westiny_common::metric_dimension::length::Meter;
+use westiny_common::visibility::OcclusionMap;
+
+/// Covers the ground out of the player's line of sight with fog.
+/// Without a character the whole map is visible.
+#[derive(Default)]
+pub struct FogOfWarSystem {
+    /// Position of every ground tile by its tile coordinates
+    tile_positions: Vec<((u32, u32), Point2<Meter>)>,
+    /// The cell of the player and the number of obstacles when the fog was calculated last time
+    calculated_for: Option<((i32, i32), usize)>,
+}
+
+impl<'s> System<'s> for FogOfWarSystem {
+    type SystemData = (
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, NetworkId>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, TileMap<GroundTile, MortonEncoder>>,
+        Write<'s, FogOfWar>,
+    );
+
+    fn run(&mut self, (players, network_ids, transforms, tile_maps, mut fog): Self::SystemData) {
+        if self.tile_positions.is_empty() {
+            for (tile_map, transform) in (&tile_maps, &transforms).join() {
+                let dimensions = tile_map.dimensions();
+                for y in 0..dimensions.y {
+                    for x in 0..dimensions.x {
+                        let position = tile_map.to_world(&Point3::new(x, y, 0), Some(transform));
+                        self.tile_positions.push(((x, y), Point2::new(Meter::from_pixel(position.x), Meter::from_pixel(position.y))));
+                    }
+                }
+            }
+        }
+
+        let viewer_cell = match (&players, &transforms).join().next() {
+            Some((_, transform)) => OcclusionMap::cell_of(&Point2::new(
+                Meter::from_pixel(transform.translation().x),
+                Meter::from_pixel(transform.translation().y),
+            )),
+            None => {
+                if self.calculated_for.take().is_some() {
+                    fog.clear();
+                }
+                return;
+            }
+        };
+
+        let occlusion_map = OcclusionMap::new((&network_ids, &transforms).join()
+            .filter(|(network_id, _)| network_id.entity_type.is_static())
+            .map(|(_, transform)| Point2::new(
+                Meter::from_pixel(transform.translation().x),
+                Meter::from_pixel(transform.translation().y),
+            )));
+
+        // Recalculating the whole map is only worth it when something has changed
+        let state = (viewer_cell, occlusion_map.len());
+        if self.calculated_for == Some(state) {
+            return;
+        }
+        self.calculated_for = Some(state);
+
+        let viewer = Point2::new(Meter(viewer_cell.0 as f32), Meter(viewer_cell.1 as f32));
+        fog.set_hidden(self.tile_positions.iter()
+            .filter(|(_, position)| !occlusion_map.is_visible(&viewer, position))
+            .map(|(tile, _)| *tile));
+    }
+}
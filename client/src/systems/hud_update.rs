@@ -1,11 +1,12 @@
 use amethyst::
 {
-    ecs::{System, ReadExpect, WriteStorage, ReadStorage, Join},
+    core::Time,
+    ecs::{System, Read, ReadExpect, WriteStorage, ReadStorage, Join},
     ui::UiText,
 };
-use crate::resources::{Hud, format_health, format_ammo};
-use westiny_common::components::{Player, Health};
-use crate::components::WeaponInfo;
+use crate::resources::{ActiveStatusEffects, Hud, format_health, format_armor, health_color, format_ammo, format_inventory, format_stamina, format_status_effects};
+use westiny_common::components::{Armor, Player, Health, MaxHealth, Stamina};
+use crate::components::Inventory;
 
 pub struct HudUpdateSystem;
 
@@ -15,19 +16,45 @@ impl<'s> System<'s> for HudUpdateSystem {
         WriteStorage<'s, UiText>,
         ReadStorage<'s, Player>,
         ReadStorage<'s, Health>,
-        ReadStorage<'s, WeaponInfo>,
+        ReadStorage<'s, MaxHealth>,
+        ReadStorage<'s, Armor>,
+        ReadStorage<'s, Inventory>,
+        ReadStorage<'s, Stamina>,
+        Read<'s, ActiveStatusEffects>,
+        Read<'s, Time>,
         );
 
-    fn run(&mut self, (hud, mut ui_texts, players, healths, weapons): Self::SystemData)
+    fn run(&mut self, (hud, mut ui_texts, players, healths, max_healths, armors, inventories, staminas, status_effects, time): Self::SystemData)
     {
-        for (_player, health, weapon_info) in (&players, &healths, &weapons).join()
+        for (_player, health, max_health, armor, inventory, stamina) in
+                (&players, &healths, (&max_healths).maybe(), (&armors).maybe(), &inventories, (&staminas).maybe()).join()
         {
             if let Some(text) = ui_texts.get_mut(hud.health) {
                 text.text = format_health(health.0);
+                text.color = health_color(health.0, max_health.map_or(health.0, |max_health| max_health.0));
+            }
+
+            if let Some(text) = ui_texts.get_mut(hud.armor) {
+                text.text = format_armor(armor.map_or(0, |armor| armor.0));
             }
 
             if let Some(text) = ui_texts.get_mut(hud.ammo) {
-                text.text = format_ammo(weapon_info.bullets_in_magazine, weapon_info.magazine_size);
+                text.text = inventory.active()
+                    .map(|weapon_info| format_ammo(weapon_info.bullets_in_magazine, weapon_info.magazine_size))
+                    .unwrap_or_default();
+            }
+
+            if let Some(text) = ui_texts.get_mut(hud.stamina) {
+                text.text = stamina.map(|stamina| format_stamina(stamina.value)).unwrap_or_default();
+            }
+
+            if let Some(text) = ui_texts.get_mut(hud.inventory) {
+                text.text = format_inventory(inventory);
+            }
+
+            if let Some(text) = ui_texts.get_mut(hud.status_effects) {
+                let elapsed = time.absolute_time().checked_sub(status_effects.received_at).unwrap_or_default();
+                text.text = format_status_effects(&status_effects.effects, elapsed);
             }
         }
     }
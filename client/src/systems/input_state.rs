@@ -5,13 +5,13 @@ use amethyst::network::simulation::{TransportResource, DeliveryRequirement, Urge
 
 
 use crate::bindings::{MovementBindingTypes, ActionBinding};
-use crate::resources::StreamId;
+use crate::resources::{ChatInput, StreamId};
 
 use westiny_common::components::{InputFlags, Input};
 use westiny_common::resources::{ServerAddress, CursorPosition};
 use westiny_common::{network, serialize};
 
-const INPUT_FLAG_MAPPING : [(InputFlags, ActionBinding); 13] = [
+const INPUT_FLAG_MAPPING : [(InputFlags, ActionBinding); 14] = [
     (InputFlags::FORWARD,  ActionBinding::Forward),
     (InputFlags::BACKWARD, ActionBinding::Backward),
     (InputFlags::LEFT,     ActionBinding::StrafeLeft),
@@ -25,6 +25,7 @@ const INPUT_FLAG_MAPPING : [(InputFlags, ActionBinding); 13] = [
     (InputFlags::SELECT3,  ActionBinding::Select3),
     (InputFlags::SELECT4,  ActionBinding::Select4),
     (InputFlags::SELECT5,  ActionBinding::Select5),
+    (InputFlags::MELEE,    ActionBinding::Melee),
 ];
 
 fn update_input_keys(input: &mut Input, handler: &InputHandler<MovementBindingTypes>) {
@@ -48,13 +49,19 @@ impl<'s> System<'s> for InputStateSystem {
        WriteStorage<'s, Input>,
        Read<'s, ServerAddress>,
        Write<'s, TransportResource>,
+       Read<'s, ChatInput>,
         );
 
-    fn run(&mut self, (input_handler, cursor, mut inputs, server, mut net): Self::SystemData) {
+    fn run(&mut self, (input_handler, cursor, mut inputs, server, mut net, chat_input): Self::SystemData) {
         // NOTE: There is only one Input component exists on the client
         for mut input in (&mut inputs).join()
         {
-            update_input_keys(&mut input, &input_handler);
+            // The keys pressed while typing are not meant for the character
+            if chat_input.active {
+                input.flags = InputFlags::empty();
+            } else {
+                update_input_keys(&mut input, &input_handler);
+            }
             update_input_cursor(&mut input, &cursor);
 
             send_to_server(&mut net, &server, &input);
@@ -0,0 +1,135 @@
+use amethyst::{
+    core::Time,
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, ReadExpect, WriteStorage},
+    shrev::{ReaderId, EventChannel},
+    ui::UiText,
+};
+use derive_new::new;
+use std::collections::HashMap;
+
+use crate::resources::Hud;
+use westiny_common::network::{MatchPhase, MatchUpdate, PlayerScore};
+
+/// Shows the phase of the match and counts down until the next transition
+#[derive(SystemDesc, new)]
+#[system_desc(name(MatchStatusSystemDesc))]
+pub struct MatchStatusSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<MatchUpdate>,
+
+    /// The last update with the time it was received at
+    #[system_desc(skip)]
+    #[new(default)]
+    last_update: Option<(MatchUpdate, f64)>,
+}
+
+impl<'s> System<'s> for MatchStatusSystem {
+    type SystemData = (
+        Read<'s, EventChannel<MatchUpdate>>,
+        ReadExpect<'s, Hud>,
+        WriteStorage<'s, UiText>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (match_update_channel, hud, mut ui_texts, time): Self::SystemData) {
+        let now = time.absolute_time_seconds();
+        if let Some(update) = match_update_channel.read(&mut self.reader).last() {
+            self.last_update = Some((update.clone(), now));
+        }
+
+        if let (Some((update, received_at)), Some(text)) = (&self.last_update, ui_texts.get_mut(hud.match_status)) {
+            let seconds_left = update.time_left
+                .map(|time_left| (time_left.0 as f64 - (now - received_at)).max(0.0).ceil() as u32);
+            text.text = format_match_status(&update.phase, seconds_left);
+        }
+    }
+}
+
+fn format_match_status(phase: &MatchPhase, seconds_left: Option<u32>) -> String {
+    let countdown = seconds_left
+        .map(|seconds| format!("{}:{:02}", seconds / 60, seconds % 60))
+        .unwrap_or_default();
+    match phase {
+        MatchPhase::Warmup { players_needed } => format!("Warmup - waiting for {} more player(s)", players_needed),
+        MatchPhase::Live => countdown,
+        MatchPhase::Intermission { winner: Some(winner), .. } => format!("{} wins - next round in {}", winner, countdown),
+        MatchPhase::Intermission { results, winner: None } => format!("{} - next round in {}", format_results(results), countdown),
+    }
+}
+
+/// Names the winner, team totals are shown in team modes
+fn format_results(results: &[PlayerScore]) -> String {
+    let mut team_kills = HashMap::new();
+    for score in results {
+        if let Some(team) = score.team {
+            *team_kills.entry(team).or_insert(0) += score.kills;
+        }
+    }
+
+    if team_kills.is_empty() {
+        results.iter()
+            .max_by_key(|score| score.kills)
+            .map(|winner| format!("{} wins with {} kills", winner.player_name, winner.kills))
+            .unwrap_or_else(|| "Round over".to_string())
+    } else {
+        let mut totals: Vec<_> = team_kills.into_iter().collect();
+        totals.sort_by(|(_, a), (_, b)| b.cmp(a));
+        totals.iter()
+            .map(|(team, kills)| format!("{:?} {}", team, kills))
+            .collect::<Vec<_>>()
+            .join(" : ")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use westiny_common::PlayerName;
+    use westiny_common::components::Team;
+
+    fn score(name: &str, team: Option<Team>, kills: u32) -> PlayerScore {
+        PlayerScore { player_name: PlayerName(name.to_string()), team, kills, deaths: 0, assists: 0, captures: 0 }
+    }
+
+    #[test]
+    fn live_round_shows_countdown() {
+        assert_eq!(format_match_status(&MatchPhase::Live, Some(65)), "1:05");
+    }
+
+    #[test]
+    fn warmup_shows_missing_players() {
+        assert_eq!(
+            format_match_status(&MatchPhase::Warmup { players_needed: 2 }, None),
+            "Warmup - waiting for 2 more player(s)"
+        );
+    }
+
+    #[test]
+    fn intermission_shows_winner() {
+        let results = vec![score("Alice", None, 3), score("Bob", None, 7)];
+        assert_eq!(
+            format_match_status(&MatchPhase::Intermission { results, winner: None }, Some(10)),
+            "Bob wins with 7 kills - next round in 0:10"
+        );
+    }
+
+    #[test]
+    fn intermission_shows_team_totals() {
+        let results = vec![
+            score("Alice", Some(Team::Red), 3),
+            score("Bob", Some(Team::Blue), 5),
+            score("Carol", Some(Team::Red), 4),
+        ];
+        assert_eq!(format_results(&results), "Red 7 : Blue 5");
+    }
+
+    #[test]
+    fn intermission_shows_last_man_standing() {
+        let winner = Some(PlayerName("Carol".to_string()));
+        assert_eq!(
+            format_match_status(&MatchPhase::Intermission { results: vec![], winner }, Some(5)),
+            "Carol wins - next round in 0:05"
+        );
+    }
+}
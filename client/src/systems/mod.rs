@@ -1,17 +1,28 @@
 pub use audio_player::AudioPlayerSystem;
 pub use camera_movement::CameraMovementSystem;
+pub use chat::ChatSystemDesc;
 pub use cursor_pos_update::CursorPosUpdateSystem;
+pub use explosion_effect::ExplosionEffectSystemDesc;
+pub use flag_status::FlagStatusSystemDesc;
+pub use fog_of_war::FogOfWarSystem;
 pub use hud_update::HudUpdateSystem;
+pub use match_status::MatchStatusSystemDesc;
 pub use input_state::InputStateSystem;
 pub use notification_bar::NotificationBarSystemDesc;
 pub use network_entity_delete::NetworkEntityDeleteSystemDesc;
 pub use network_entity_update::NetworkEntityStateUpdateSystemDesc;
 pub use network_messenger::NetworkMessageReceiverSystemDesc;
+pub use replay_playback::ReplayPlaybackSystem;
+pub use safe_zone::SafeZoneSystemDesc;
+pub use scoreboard::ScoreboardSystemDesc;
 pub use shooter::ShooterSystemDesc;
+pub use spectator::SpectatorSystemDesc;
+pub use team_color::TeamColorSystemDesc;
 pub use westiny_common::systems::*;
 pub use player_update::PlayerUpdateSystemDesc;
 
 mod audio_player;
+mod chat;
 mod hud_update;
 mod network_messenger;
 mod network_entity_update;
@@ -23,3 +34,12 @@ mod cursor_pos_update;
 pub mod client_connect;
 mod shooter;
 mod player_update;
+mod explosion_effect;
+mod team_color;
+mod match_status;
+mod scoreboard;
+mod flag_status;
+mod safe_zone;
+mod spectator;
+mod fog_of_war;
+mod replay_playback;
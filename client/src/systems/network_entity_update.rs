@@ -10,12 +10,12 @@ use westiny_common::network::{EntityState, PlayerDeath};
 use westiny_common::components::{NetworkId, EntityType, Lifespan};
 use amethyst::core::ecs::{ReadStorage, WriteStorage, Join, Entities, LazyUpdate};
 use westiny_common::resources::SpriteId;
-use amethyst::core::{Transform, Time};
+use amethyst::core::{HiddenPropagate, Transform, Time};
 use std::collections::HashMap;
 use std::time::Duration;
 use amethyst::shred::ReadExpect;
 
-use crate::entities::{create_player, create_character};
+use crate::entities::{create_player, create_character, create_item_pickup, create_weapon_pickup, create_barrel, create_grenade, create_flag};
 use crate::resources;
 use westiny_common::metric_dimension::Second;
 use westiny_common::metric_dimension::length::Meter;
@@ -38,6 +38,7 @@ impl<'s> System<'s> for NetworkEntityStateUpdateSystem {
         Read<'s, EventChannel<PlayerDeath>>,
         ReadStorage<'s, NetworkId>,
         WriteStorage<'s, Transform>,
+        WriteStorage<'s, HiddenPropagate>,
         Entities<'s>,
         ReadExpect<'s, resources::SpriteResource>,
         ReadExpect<'s, resources::PlayerNetworkId>,
@@ -51,28 +52,38 @@ impl<'s> System<'s> for NetworkEntityStateUpdateSystem {
                death_event_channel,
                network_ids,
                mut transforms,
+               mut hiddens,
                entities,
                sprite_resource,
                player_net_id,
                lazy,
                time,
            ): Self::SystemData) {
-        let mut entity_states: HashMap<_, _> = entity_state_event_channel.read(&mut self.entity_state_reader)
+        let updates: Vec<_> = entity_state_event_channel.read(&mut self.entity_state_reader).collect();
+        let mut entity_states: HashMap<_, _> = updates.iter()
             .flat_map(|vec| vec.iter())
             .map(|entity_state| (entity_state.network_id, entity_state))
             .collect();
 
-        for (net_id, transform) in (&network_ids, &mut transforms).join() {
+        for (entity, net_id, transform) in (&entities, &network_ids, &mut transforms).join() {
             if let Some(state) = entity_states.get(net_id) {
                 update_transform(transform, &state);
                 entity_states.remove(&net_id);
+                hiddens.remove(entity);
+            } else if !updates.is_empty() && !net_id.entity_type.is_static() {
+                // The server only sends what the player can see
+                if let Err(err) = hiddens.insert(entity, HiddenPropagate::new()) {
+                    log::error!("Could not hide entity {:?} out of sight: {}", net_id, err);
+                }
             }
         }
 
         // if it is this player
-        if let Some(&new_state) = entity_states.get(&player_net_id.0) {
-            create_player(||{ lazy.create_entity(&entities) }, &sprite_resource, player_net_id.0, as_transform(&new_state.position));
-            entity_states.remove(&player_net_id.0);
+        if let Some(player_net_id) = player_net_id.0 {
+            if let Some(&new_state) = entity_states.get(&player_net_id) {
+                create_player(||{ lazy.create_entity(&entities) }, &sprite_resource, player_net_id, as_transform(&new_state.position));
+                entity_states.remove(&player_net_id);
+            }
         }
 
         for (net_id, entity_state) in entity_states {
@@ -80,9 +91,13 @@ impl<'s> System<'s> for NetworkEntityStateUpdateSystem {
             update_transform(&mut transform, &entity_state);
 
 
-            // Yeah it looks silly but there will be more network entities
             match net_id.entity_type {
-                EntityType::Player => create_character(lazy.create_entity(&entities), ||{ lazy.create_entity(&entities)}, &sprite_resource, net_id, transform)
+                EntityType::Player => create_character(lazy.create_entity(&entities), ||{ lazy.create_entity(&entities)}, &sprite_resource, net_id, transform),
+                EntityType::WeaponPickup => create_weapon_pickup(lazy.create_entity(&entities), &sprite_resource, net_id, transform),
+                EntityType::Medkit | EntityType::ArmorPickup | EntityType::EffectPickup => create_item_pickup(lazy.create_entity(&entities), &sprite_resource, net_id, transform),
+                EntityType::Barrel | EntityType::ExplosiveBarrel => create_barrel(lazy.create_entity(&entities), &sprite_resource, net_id, transform),
+                EntityType::Grenade => create_grenade(lazy.create_entity(&entities), &sprite_resource, net_id, transform),
+                EntityType::Flag => create_flag(lazy.create_entity(&entities), &sprite_resource, net_id, transform),
             };
         }
 
@@ -14,7 +14,7 @@ use westiny_common::{
     deserialize,
     events::AppEvent,
 };
-use westiny_common::network::PlayerDeath;
+use westiny_common::network::{ChatMessage, PlayerDeath, ExplosionNotification, TeamMember, MatchUpdate, PlayerScore, FlagStatus, SafeZoneUpdate, SpectatorUpdate};
 
 #[derive(SystemDesc, new)]
 #[system_desc(name(NetworkMessageReceiverSystemDesc))]
@@ -33,6 +33,14 @@ impl<'s> System<'s> for NetworkMessageReceiverSystem {
         Write<'s, EventChannel<PlayerNotification>>,
         Write<'s, EventChannel<ShotEvent>>,
         Write<'s, EventChannel<PlayerDeath>>,
+        Write<'s, EventChannel<ExplosionNotification>>,
+        Write<'s, EventChannel<Vec<TeamMember>>>,
+        Write<'s, EventChannel<MatchUpdate>>,
+        Write<'s, EventChannel<Vec<PlayerScore>>>,
+        Write<'s, EventChannel<Vec<FlagStatus>>>,
+        Write<'s, EventChannel<SafeZoneUpdate>>,
+        Write<'s, EventChannel<SpectatorUpdate>>,
+        Write<'s, EventChannel<ChatMessage>>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -43,7 +51,15 @@ impl<'s> System<'s> for NetworkMessageReceiverSystem {
         mut entity_delete_channel,
         mut message_channel,
         mut shot_event_channel,
-        mut death_event_channel) = data;
+        mut death_event_channel,
+        mut explosion_channel,
+        mut team_roster_channel,
+        mut match_update_channel,
+        mut scoreboard_channel,
+        mut flag_status_channel,
+        mut safe_zone_channel,
+        mut spectator_channel,
+        mut chat_channel) = data;
         for event in net_event_ch.read(&mut self.reader) {
             match event {
                 NetworkSimulationEvent::Connect(addr) => log::debug!(
@@ -57,7 +73,7 @@ impl<'s> System<'s> for NetworkMessageReceiverSystem {
                     );
 
                     message_channel.single_write(PlayerNotification { message: "Server is unavailable!".to_string() });
-                    app_event.single_write(AppEvent::Disconnect);
+                    app_event.single_write(AppEvent::Disconnect("Disconnected from the server".to_string()));
                 },
                 NetworkSimulationEvent::Message(addr, payload) => {
                     match self.process_payload(&addr,
@@ -67,7 +83,16 @@ impl<'s> System<'s> for NetworkMessageReceiverSystem {
                                                &mut entity_delete_channel,
                                                &mut message_channel,
                                                &mut shot_event_channel,
-                                               &mut death_event_channel) {
+                                               &mut death_event_channel,
+                                               &mut explosion_channel,
+                                               &mut team_roster_channel,
+                                               &mut match_update_channel,
+                                               &mut scoreboard_channel,
+                                               &mut flag_status_channel,
+                                               &mut safe_zone_channel,
+                                               &mut spectator_channel,
+                                               &mut chat_channel,
+                                               &mut app_event) {
                         Ok(_) => log::debug!("Message from {} processed successfully.", addr),
                         Err(e) => {
                             log::error!("Could not process message! {:?}, payload: {:02x?}", e, payload)
@@ -91,6 +116,15 @@ impl NetworkMessageReceiverSystem {
         message_channel: &mut EventChannel<PlayerNotification>,
         shot_event_channel: &mut EventChannel<ShotEvent>,
         death_event_channel: &mut EventChannel<PlayerDeath>,
+        explosion_channel: &mut EventChannel<ExplosionNotification>,
+        team_roster_channel: &mut EventChannel<Vec<TeamMember>>,
+        match_update_channel: &mut EventChannel<MatchUpdate>,
+        scoreboard_channel: &mut EventChannel<Vec<PlayerScore>>,
+        flag_status_channel: &mut EventChannel<Vec<FlagStatus>>,
+        safe_zone_channel: &mut EventChannel<SafeZoneUpdate>,
+        spectator_channel: &mut EventChannel<SpectatorUpdate>,
+        chat_channel: &mut EventChannel<ChatMessage>,
+        app_event: &mut EventChannel<AppEvent>,
     ) -> Result<()> {
 
         log::debug!("Message: {:02x?}", payload);
@@ -118,11 +152,53 @@ impl NetworkMessageReceiverSystem {
                 Ok(())
             }
             PacketType::PlayerDeath(death) => {
-                let notification = PlayerNotification { message: format!("{} died.", death.player_name) };
+                let notification = PlayerNotification { message: kill_feed_message(&death) };
                 message_channel.single_write(notification);
                 death_event_channel.single_write(death);
                 Ok(())
             }
+            PacketType::Explosion(explosion) => {
+                explosion_channel.single_write(explosion);
+                Ok(())
+            }
+            PacketType::TeamRoster(roster) => {
+                team_roster_channel.single_write(roster);
+                Ok(())
+            }
+            PacketType::MatchUpdate(update) => {
+                match_update_channel.single_write(update);
+                Ok(())
+            }
+            PacketType::MapChange(change) => {
+                log::info!("The server has changed the map to {}", change.map);
+                // The map is rebuilt by the play state, it needs the whole world
+                app_event.single_write(AppEvent::MapChange(change));
+                Ok(())
+            }
+            PacketType::Scoreboard(scores) => {
+                scoreboard_channel.single_write(scores);
+                Ok(())
+            }
+            PacketType::FlagStatus(flags) => {
+                flag_status_channel.single_write(flags);
+                Ok(())
+            }
+            PacketType::SafeZone(zone) => {
+                safe_zone_channel.single_write(zone);
+                Ok(())
+            }
+            PacketType::SpectatorUpdate(update) => {
+                spectator_channel.single_write(update);
+                Ok(())
+            }
+            PacketType::Chat(message) => {
+                chat_channel.single_write(message);
+                Ok(())
+            }
+            PacketType::Kicked { reason } => {
+                app_event.single_write(AppEvent::Disconnect(format!("Kicked from the server: {}", reason)));
+                Ok(())
+            }
             _ => Err(anyhow::anyhow!(
                 "Unexpected message from {}, payload={:02x?}",
                 addr,
@@ -131,3 +207,35 @@ impl NetworkMessageReceiverSystem {
         }
     }
 }
+
+fn kill_feed_message(death: &PlayerDeath) -> String {
+    match (&death.killer, &death.weapon) {
+        (Some(killer), Some(weapon)) => format!("{} killed {} with {}.", killer, death.player_name, weapon),
+        (Some(killer), None) => format!("{} killed {}.", killer, death.player_name),
+        (None, _) => format!("{} died.", death.player_name),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::core::math::Point2;
+    use westiny_common::PlayerName;
+    use westiny_common::metric_dimension::length::Meter;
+
+    fn death(killer: Option<&str>, weapon: Option<&str>) -> PlayerDeath {
+        PlayerDeath {
+            player_name: PlayerName("Bob".to_string()),
+            position: Point2::new(Meter(0.0), Meter(0.0)),
+            killer: killer.map(|name| PlayerName(name.to_string())),
+            weapon: weapon.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn kill_feed_names_the_killer_and_the_weapon() {
+        assert_eq!(kill_feed_message(&death(Some("Alice"), Some("Shotgun"))), "Alice killed Bob with Shotgun.");
+        assert_eq!(kill_feed_message(&death(Some("Alice"), None)), "Alice killed Bob.");
+        assert_eq!(kill_feed_message(&death(None, Some("Rocket launcher"))), "Bob died.");
+    }
+}
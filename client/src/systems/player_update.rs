@@ -1,14 +1,15 @@
 use amethyst::{
+    core::Time,
     derive::SystemDesc,
     ecs::{System, SystemData, Read, ReadStorage, WriteStorage, ReadExpect, WriteExpect, Write, Join},
     shrev::{ReaderId, EventChannel},
 };
 
 use derive_new::new;
-use westiny_common::components::{Health, NetworkId};
-use westiny_common::network::{PlayerUpdate, PlayerNotification};
-use crate::resources::PlayerNetworkId;
-use crate::components::WeaponInfo;
+use westiny_common::components::{Armor, Health, MaxHealth, NetworkId, Stamina};
+use westiny_common::network::{PlayerUpdate, PlayerNotification, InventoryState};
+use crate::resources::{ActiveStatusEffects, PlayerNetworkId};
+use crate::components::Inventory;
 use westiny_common::resources::{AudioQueue, SoundId};
 
 #[derive(new, SystemDesc)]
@@ -16,6 +17,11 @@ use westiny_common::resources::{AudioQueue, SoundId};
 pub struct PlayerUpdateSystem {
     #[system_desc(event_channel_reader)]
     reader: ReaderId<PlayerUpdate>,
+
+    /// The inventory is sent at spawn, possibly before the player entity is created here
+    #[system_desc(skip)]
+    #[new(default)]
+    pending_inventory: Option<InventoryState>,
 }
 
 impl<'s> System<'s> for PlayerUpdateSystem {
@@ -23,50 +29,86 @@ impl<'s> System<'s> for PlayerUpdateSystem {
         Read<'s, EventChannel<PlayerUpdate>>,
         ReadStorage<'s, NetworkId>,
         WriteStorage<'s, Health>,
-        WriteStorage<'s, WeaponInfo>,
+        WriteStorage<'s, MaxHealth>,
+        WriteStorage<'s, Armor>,
+        WriteStorage<'s, Inventory>,
+        WriteStorage<'s, Stamina>,
         ReadExpect<'s, PlayerNetworkId>,
         WriteExpect<'s, AudioQueue>,
         Write<'s, EventChannel<PlayerNotification>>,
+        Write<'s, ActiveStatusEffects>,
+        Read<'s, Time>,
     );
 
-    fn run(&mut self, (player_updates_channel, net_ids, mut healths, mut weapons, player_net_id, mut audio, mut notification): Self::SystemData) {
+    fn run(&mut self, (player_updates_channel, net_ids, mut healths, mut max_healths, mut armors, mut inventories, mut staminas, player_net_id, mut audio, mut notification, mut status_effects, time): Self::SystemData) {
         let updates = player_updates_channel.read(&mut self.reader);
-        if updates.len() == 0 { return; }
+        if updates.len() == 0 && self.pending_inventory.is_none() { return; }
 
-        let (health, weapon_info, _) = {
-            if let Some(player) = (&mut healths, &mut weapons, &net_ids).join()
-                .find(|(_, _, &net_id)| net_id == player_net_id.0) {
+        let (health, max_health, armor, inventory, stamina, _) = {
+            if let Some(player) = (&mut healths, &mut max_healths, &mut armors, &mut inventories, &mut staminas, &net_ids).join()
+                .find(|(_, _, _, _, _, &net_id)| Some(net_id) == player_net_id.0) {
                 player
             } else {
-                log::error!("Player update received while player entity does not exist or does not have the required components");
+                for player_update in updates {
+                    if let PlayerUpdate::InventoryUpdate(state) = player_update {
+                        self.pending_inventory = Some(state.clone());
+                    }
+                }
+                log::debug!("Player update received while player entity does not exist or does not have the required components");
                 return;
             }
         };
 
+        if let Some(state) = self.pending_inventory.take() {
+            Self::update_inventory(inventory, &state, &mut notification);
+        }
+
         for player_update in updates {
             match player_update {
-                PlayerUpdate::HealthUpdate(new_health) => {
-                    if new_health.0 < health.0 {
+                PlayerUpdate::HealthUpdate(state) => {
+                    if state.health.0 < health.0 || state.armor.0 < armor.0 {
                         audio.play(SoundId::Ouch, 1.0);
                     }
-                    health.0 = new_health.0;
-                    log::debug!("Health updated to {:?}", new_health);
+                    *health = state.health;
+                    *max_health = state.max_health;
+                    *armor = state.armor;
+                    log::debug!("Health updated to {:?}", state);
                 }
                 PlayerUpdate::AmmoUpdate { ammo_in_magazine} => {
-                    if ammo_in_magazine > &weapon_info.bullets_in_magazine {
-                        audio.play(SoundId::WeaponReady, 1.0);
+                    if let Some(weapon_info) = inventory.active_mut() {
+                        if ammo_in_magazine > &weapon_info.bullets_in_magazine {
+                            audio.play(SoundId::WeaponReady, 1.0);
+                        }
+                        weapon_info.bullets_in_magazine = *ammo_in_magazine;
                     }
-                    weapon_info.bullets_in_magazine = *ammo_in_magazine;
                     log::debug!("Ammo updated to {:?}", ammo_in_magazine);
                 }
-                PlayerUpdate::WeaponSwitch {name, magazine_size, ammo_in_magazine} => {
-                    weapon_info.name = name.clone();
-                    weapon_info.magazine_size = *magazine_size;
-                    weapon_info.bullets_in_magazine = *ammo_in_magazine;
-                    log::debug!("Weapon updated");
-
-                    notification.single_write(PlayerNotification { message: format!("Weapon: {}.", name) })
+                PlayerUpdate::InventoryUpdate(state) => {
+                    Self::update_inventory(inventory, state, &mut notification);
+                }
+                PlayerUpdate::StaminaUpdate(new_stamina) => {
+                    *stamina = *new_stamina;
                 }
+                PlayerUpdate::StatusEffects(effects) => {
+                    status_effects.effects = effects.clone();
+                    status_effects.received_at = time.absolute_time();
+                }
+            }
+        }
+    }
+}
+
+impl PlayerUpdateSystem {
+    fn update_inventory(inventory: &mut Inventory,
+                        state: &InventoryState,
+                        notification: &mut EventChannel<PlayerNotification>) {
+        let previous_weapon = inventory.active().map(|weapon| weapon.name.clone());
+        *inventory = Inventory::from(state);
+        log::debug!("Inventory updated");
+
+        if let Some(weapon) = inventory.active() {
+            if previous_weapon.as_ref() != Some(&weapon.name) {
+                notification.single_write(PlayerNotification { message: format!("Weapon: {}.", weapon.name) })
             }
         }
     }
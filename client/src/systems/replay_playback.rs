@@ -0,0 +1,87 @@
+use amethyst::{
+    core::Time,
+    ecs::{System, Read, Write, WriteExpect},
+    network::simulation::NetworkSimulationEvent,
+    shrev::EventChannel,
+};
+use westiny_common::{network::PlayerNotification, resources::ServerAddress, serialize};
+
+use crate::resources::ReplayPlayback;
+
+/// Plays the replay as if its packets were arriving from the server,
+/// so they are handled by the same systems as in a game
+pub struct ReplayPlaybackSystem;
+
+impl<'s> System<'s> for ReplayPlaybackSystem {
+    type SystemData = (
+        WriteExpect<'s, ReplayPlayback>,
+        Read<'s, Time>,
+        Read<'s, ServerAddress>,
+        Write<'s, EventChannel<NetworkSimulationEvent>>,
+        Write<'s, EventChannel<PlayerNotification>>,
+    );
+
+    fn run(&mut self, (mut playback, time, server, mut net_event_ch, mut message_channel): Self::SystemData) {
+        let was_finished = playback.is_finished();
+
+        for packet in playback.advance(time.delta_real_time()) {
+            match serialize(packet) {
+                Ok(payload) => net_event_ch.single_write(NetworkSimulationEvent::Message(server.address, payload.into())),
+                Err(err) => log::error!("Recorded packet could not be played: {}", err),
+            }
+        }
+
+        if playback.is_finished() && !was_finished {
+            message_channel.single_write(PlayerNotification { message: "End of the replay".to_string() });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::Error;
+    use amethyst::prelude::*;
+    use amethyst::shrev::ReaderId;
+    use amethyst_test::prelude::*;
+    use std::time::Duration;
+    use westiny_common::deserialize;
+    use westiny_common::network::{MapChange, PacketType};
+    use westiny_common::resources::Seed;
+    use westiny_common::replay::ReplayFrame;
+
+    #[test]
+    fn recorded_packets_are_played_as_network_messages() -> Result<(), Error> {
+        amethyst::start_logger(Default::default());
+
+        let frames = vec![ReplayFrame {
+            time: Duration::default(),
+            packets: vec![PacketType::Notification(PlayerNotification { message: "Clint joined".to_string() })],
+        }];
+
+        AmethystApplication::blank()
+            .with_resource(ReplayPlayback::new(frames, MapChange { map: "rust2".to_string(), seed: Seed(0) }))
+            .with_setup(|world: &mut World| {
+                let mut net_event_ch = EventChannel::<NetworkSimulationEvent>::new();
+                let reader = net_event_ch.register_reader();
+                world.insert(net_event_ch);
+                world.insert(reader);
+            })
+            .with_system(ReplayPlaybackSystem, "replay_playback", &[])
+            .with_assertion(|world: &mut World| {
+                let mut reader = world.write_resource::<ReaderId<NetworkSimulationEvent>>();
+                let net_event_ch = world.read_resource::<EventChannel<NetworkSimulationEvent>>();
+                let payloads: Vec<_> = net_event_ch.read(&mut reader)
+                    .map(|event| match event {
+                        NetworkSimulationEvent::Message(_, payload) => deserialize(payload).expect("Played packet is invalid"),
+                        event => panic!("Unexpected event {:?}", event),
+                    })
+                    .collect();
+
+                assert_eq!(payloads.len(), 1);
+                assert!(matches!(&payloads[0], PacketType::Notification(notification) if notification.message == "Clint joined"));
+                assert!(world.read_resource::<ReplayPlayback>().is_finished());
+            })
+            .run()
+    }
+}
@@ -0,0 +1,172 @@
+use amethyst::{
+    core::{Transform, math::{Point2, Point3, Vector2}},
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, ReadExpect, ReadStorage, Write, WriteStorage, Join},
+    renderer::{camera::Camera, debug_drawing::DebugLines, palette::Srgba},
+    shrev::{ReaderId, EventChannel},
+    ui::UiText,
+    window::ScreenDimensions,
+};
+use derive_new::new;
+
+use crate::resources::Hud;
+use westiny_common::components::Player;
+use westiny_common::metric_dimension::to_meter_vec;
+use westiny_common::metric_dimension::length::{Meter, magnitude};
+use westiny_common::network::SafeZoneUpdate;
+
+const BOUNDARY_POINTS: u32 = 64;
+const BOUNDARY_Z: f32 = 0.5;
+
+/// The map is 64 x 64 meters centered on the origin
+const MAP_HALF_SIZE: Meter = Meter(32.0);
+/// Size and distance from the top right corner of the screen, in screen pixels
+const MINIMAP_SIZE: f32 = 160.0;
+const MINIMAP_MARGIN: f32 = 20.0;
+const MINIMAP_PLAYER_RADIUS: f32 = 3.0;
+const MINIMAP_Z: f32 = 0.8;
+
+/// Draws the boundary of the safe zone and shows the zone and the player on a minimap
+#[derive(SystemDesc, new)]
+#[system_desc(name(SafeZoneSystemDesc))]
+pub struct SafeZoneSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<SafeZoneUpdate>,
+
+    #[system_desc(skip)]
+    #[new(default)]
+    zone: Option<SafeZoneUpdate>,
+}
+
+impl<'s> System<'s> for SafeZoneSystem {
+    type SystemData = (
+        Read<'s, EventChannel<SafeZoneUpdate>>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Camera>,
+        ReadStorage<'s, Transform>,
+        ReadExpect<'s, ScreenDimensions>,
+        Write<'s, DebugLines>,
+        ReadExpect<'s, Hud>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (safe_zone_channel, players, cameras, transforms, screen_dimensions, mut debug_lines, hud, mut ui_texts): Self::SystemData) {
+        if let Some(zone) = safe_zone_channel.read(&mut self.reader).last() {
+            self.zone = Some(zone.clone());
+        }
+
+        let zone = match &self.zone {
+            Some(zone) => zone,
+            None => return,
+        };
+
+        // Debug lines are cleared after every frame
+        debug_lines.draw_circle(
+            Point3::new(zone.center.x.into_pixel(), zone.center.y.into_pixel(), BOUNDARY_Z),
+            zone.radius.into_pixel(),
+            BOUNDARY_POINTS,
+            boundary_color(),
+        );
+
+        let player_pos = (&players, &transforms).join().next()
+            .map(|(_, transform)| to_meter_vec(transform.translation().xy()));
+
+        if let Some((_, camera_transform)) = (&cameras, &transforms).join().next() {
+            let minimap = Minimap::new(
+                Point2::from(camera_transform.translation().xy()),
+                camera_transform.scale().x,
+                Vector2::new(screen_dimensions.width(), screen_dimensions.height()),
+            );
+            minimap.draw(&mut debug_lines, zone, player_pos.map(Point2::from));
+        }
+
+        if let Some(text) = ui_texts.get_mut(hud.zone_status) {
+            text.text = player_pos.map(|pos| format_zone_status(zone, pos)).unwrap_or_default();
+        }
+    }
+}
+
+fn boundary_color() -> Srgba {
+    Srgba::new(1.0, 0.2, 0.2, 1.0)
+}
+
+fn format_zone_status(zone: &SafeZoneUpdate, player_pos: Vector2<Meter>) -> String {
+    let distance_to_edge = magnitude(zone.center.coords - player_pos) - zone.radius;
+    if distance_to_edge <= Meter(0.0) {
+        "Inside the safe zone".to_string()
+    } else {
+        format!("Outside the safe zone: {:.0}m", distance_to_edge.0)
+    }
+}
+
+/// The whole map shrunk into the top right corner of the screen.
+/// It is drawn with debug lines in world coordinates, so it follows the camera and its zoom.
+struct Minimap {
+    /// World position of the minimap center
+    center: Point2<f32>,
+    /// World pixels per map meter
+    scale: f32,
+    /// World pixels per screen pixel
+    camera_scale: f32,
+}
+
+impl Minimap {
+    fn new(camera_pos: Point2<f32>, camera_scale: f32, screen_size: Vector2<f32>) -> Self {
+        let corner_offset = screen_size / 2.0 - Vector2::repeat(MINIMAP_MARGIN + MINIMAP_SIZE / 2.0);
+        Minimap {
+            center: camera_pos + corner_offset * camera_scale,
+            scale: MINIMAP_SIZE * camera_scale / (MAP_HALF_SIZE.0 * 2.0),
+            camera_scale,
+        }
+    }
+
+    fn point(&self, pos: Point2<Meter>) -> Point3<f32> {
+        Point3::new(self.center.x + pos.x.0 * self.scale, self.center.y + pos.y.0 * self.scale, MINIMAP_Z)
+    }
+
+    fn draw(&self, debug_lines: &mut DebugLines, zone: &SafeZoneUpdate, player_pos: Option<Point2<Meter>>) {
+        let (low, high) = (-MAP_HALF_SIZE, MAP_HALF_SIZE);
+        let corners = [(low, low), (high, low), (high, high), (low, high)]
+            .iter()
+            .map(|(x, y)| self.point(Point2::new(*x, *y)))
+            .collect::<Vec<_>>();
+        for (index, corner) in corners.iter().enumerate() {
+            debug_lines.draw_line(*corner, corners[(index + 1) % corners.len()], Srgba::new(1.0, 1.0, 1.0, 0.8));
+        }
+
+        debug_lines.draw_circle(self.point(zone.center), zone.radius.0 * self.scale, BOUNDARY_POINTS, boundary_color());
+
+        if let Some(player_pos) = player_pos {
+            debug_lines.draw_circle(
+                self.point(player_pos),
+                MINIMAP_PLAYER_RADIUS * self.camera_scale,
+                8,
+                Srgba::new(0.2, 1.0, 0.2, 1.0),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn zone_status_tells_the_distance() {
+        let zone = SafeZoneUpdate { center: Point2::new(Meter(0.0), Meter(0.0)), radius: Meter(5.0) };
+        assert_eq!(format_zone_status(&zone, Vector2::new(Meter(1.0), Meter(2.0))), "Inside the safe zone");
+        assert_eq!(format_zone_status(&zone, Vector2::new(Meter(0.0), Meter(-12.0))), "Outside the safe zone: 7m");
+    }
+
+    #[test]
+    fn minimap_is_in_the_top_right_corner_of_the_screen() {
+        let minimap = Minimap::new(Point2::new(0.0, 0.0), 1.0, Vector2::new(800.0, 600.0));
+        assert_eq!(minimap.point(Point2::new(Meter(0.0), Meter(0.0))), Point3::new(300.0, 200.0, MINIMAP_Z));
+        assert_eq!(minimap.point(Point2::new(MAP_HALF_SIZE, MAP_HALF_SIZE)), Point3::new(380.0, 280.0, MINIMAP_Z));
+
+        // Zooming in shrinks the visible world, so the minimap shrinks with it
+        let zoomed = Minimap::new(Point2::new(100.0, 100.0), 0.25, Vector2::new(800.0, 600.0));
+        assert_eq!(zoomed.point(Point2::new(Meter(0.0), Meter(0.0))), Point3::new(175.0, 150.0, MINIMAP_Z));
+        assert_eq!(zoomed.point(Point2::new(-MAP_HALF_SIZE, Meter(0.0))), Point3::new(155.0, 150.0, MINIMAP_Z));
+    }
+}
@@ -0,0 +1,80 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, ReadExpect, WriteStorage},
+    input::InputHandler,
+    shrev::{ReaderId, EventChannel},
+    ui::UiText,
+};
+use derive_new::new;
+
+use crate::bindings::{ActionBinding, MovementBindingTypes};
+use crate::resources::Hud;
+use westiny_common::network::PlayerScore;
+
+/// Shows the scores of the players while the scoreboard key is held down
+#[derive(SystemDesc, new)]
+#[system_desc(name(ScoreboardSystemDesc))]
+pub struct ScoreboardSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<Vec<PlayerScore>>,
+
+    #[system_desc(skip)]
+    #[new(default)]
+    scores: Vec<PlayerScore>,
+}
+
+impl<'s> System<'s> for ScoreboardSystem {
+    type SystemData = (
+        Read<'s, EventChannel<Vec<PlayerScore>>>,
+        Read<'s, InputHandler<MovementBindingTypes>>,
+        ReadExpect<'s, Hud>,
+        WriteStorage<'s, UiText>,
+    );
+
+    fn run(&mut self, (scoreboard_channel, input_handler, hud, mut ui_texts): Self::SystemData) {
+        if let Some(scores) = scoreboard_channel.read(&mut self.reader).last() {
+            self.scores = scores.clone();
+        }
+
+        if let Some(text) = ui_texts.get_mut(hud.scoreboard) {
+            text.text = if input_handler.action_is_down(&ActionBinding::Scoreboard).unwrap_or(false) {
+                format_scoreboard(&self.scores)
+            } else {
+                String::new()
+            };
+        }
+    }
+}
+
+fn format_scoreboard(scores: &[PlayerScore]) -> String {
+    let mut lines = vec![format!("{:<20}{:>6}{:>6}{:>6}", "Player", "K", "D", "A")];
+    lines.extend(scores.iter().map(|score| {
+        let name = match score.team {
+            Some(team) => format!("{} ({:?})", score.player_name, team),
+            None => score.player_name.to_string(),
+        };
+        format!("{:<20}{:>6}{:>6}{:>6}", name, score.kills, score.deaths, score.assists)
+    }));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use westiny_common::PlayerName;
+    use westiny_common::components::Team;
+
+    #[test]
+    fn scoreboard_lists_the_players_under_the_header() {
+        let scores = vec![
+            PlayerScore { player_name: PlayerName("Bob".to_string()), team: Some(Team::Blue), kills: 7, deaths: 2, assists: 1, captures: 0 },
+            PlayerScore { player_name: PlayerName("Alice".to_string()), team: None, kills: 3, deaths: 4, assists: 0, captures: 0 },
+        ];
+        let lines: Vec<_> = format_scoreboard(&scores).lines().map(str::to_string).collect();
+        assert_eq!(lines, vec![
+            "Player                   K     D     A",
+            "Bob (Blue)               7     2     1",
+            "Alice                    3     4     0",
+        ]);
+    }
+}
@@ -0,0 +1,30 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, Write},
+    shrev::{ReaderId, EventChannel},
+};
+use derive_new::new;
+
+use crate::resources::Spectating;
+use westiny_common::network::SpectatorUpdate;
+
+/// Tracks whether the player watches the others instead of playing
+#[derive(SystemDesc, new)]
+#[system_desc(name(SpectatorSystemDesc))]
+pub struct SpectatorSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<SpectatorUpdate>,
+}
+
+impl<'s> System<'s> for SpectatorSystem {
+    type SystemData = (
+        Read<'s, EventChannel<SpectatorUpdate>>,
+        Write<'s, Spectating>,
+    );
+
+    fn run(&mut self, (spectator_channel, mut spectating): Self::SystemData) {
+        if let Some(update) = spectator_channel.read(&mut self.reader).last() {
+            spectating.0 = update.spectating;
+        }
+    }
+}
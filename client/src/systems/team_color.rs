@@ -0,0 +1,77 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, ReadStorage, WriteStorage, Entities, Join},
+    renderer::{palette::Srgba, resources::Tint},
+    shrev::{ReaderId, EventChannel},
+};
+use derive_new::new;
+use std::collections::HashMap;
+
+use westiny_common::components::{NetworkId, Player, Team};
+use westiny_common::network::TeamMember;
+
+use crate::resources::Profile;
+
+/// Colors the characters according to their team.
+/// Without a team the own character gets the color of the profile.
+#[derive(SystemDesc, new)]
+#[system_desc(name(TeamColorSystemDesc))]
+pub struct TeamColorSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<Vec<TeamMember>>,
+
+    /// Characters are recreated on respawn, so the teams are remembered
+    #[system_desc(skip)]
+    #[new(default)]
+    roster: HashMap<NetworkId, Team>,
+}
+
+impl<'s> System<'s> for TeamColorSystem {
+    type SystemData = (
+        Read<'s, EventChannel<Vec<TeamMember>>>,
+        Entities<'s>,
+        ReadStorage<'s, NetworkId>,
+        ReadStorage<'s, Player>,
+        WriteStorage<'s, Tint>,
+        Read<'s, Profile>,
+    );
+
+    fn run(&mut self, (roster_channel, entities, network_ids, players, mut tints, profile): Self::SystemData) {
+        let mut roster_changed = false;
+        for roster in roster_channel.read(&mut self.reader) {
+            self.roster = roster.iter()
+                .map(|member| (member.network_id, member.team))
+                .collect();
+            roster_changed = true;
+        }
+
+        for (entity, network_id, player) in (&entities, &network_ids, (&players).maybe()).join() {
+            // The profile color of the own character is replaced when it turns out to have a team
+            let is_recolored = roster_changed && player.is_some();
+            if tints.contains(entity) && !is_recolored {
+                continue;
+            }
+
+            let tint = match self.roster.get(network_id) {
+                Some(team) => team_tint(*team),
+                None if player.is_some() => profile_tint(&profile),
+                None => continue,
+            };
+            if let Err(err) = tints.insert(entity, tint) {
+                log::error!("Could not color the character of {:?}: {}", network_id, err);
+            }
+        }
+    }
+}
+
+pub(super) fn team_tint(team: Team) -> Tint {
+    match team {
+        Team::Red => Tint(Srgba::new(1.0, 0.55, 0.55, 1.0)),
+        Team::Blue => Tint(Srgba::new(0.55, 0.65, 1.0, 1.0)),
+    }
+}
+
+fn profile_tint(profile: &Profile) -> Tint {
+    let (red, green, blue) = profile.color;
+    Tint(Srgba::new(red, green, blue, 1.0))
+}
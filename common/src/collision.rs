@@ -58,6 +58,59 @@ pub fn check_projectile_collision(a: &Transform, b: Collider) -> Option<Vector2<
     }
 }
 
+/// Checks whether the collider overlaps the circular sector in front of `origin`.
+/// The sector is `2 * half_angle` wide [radian] around `direction` and reaches `reach` far.
+/// Returns the vector pointing from `origin` to the collider.
+pub fn check_arc_collision(origin: &Transform, direction: &Vector2<f32>, reach: Meter, half_angle: f32, target: Collider) -> Option<Vector2<Meter>>
+{
+    let disposition = calculate_disposition(origin, target.transform);
+    let distance = magnitude(disposition.clone() as Vector2<Meter>);
+    if distance < FUZZY_THRESHOLD
+    {
+        return Some(disposition);
+    }
+
+    if distance >= reach + target.bound.radius
+    {
+        return None;
+    }
+
+    // The collider may be partially inside the sector even if its center is not
+    let tolerance = (target.bound.radius / distance).0.min(1.0).asin();
+    if direction.angle(&normalize(disposition)) <= half_angle + tolerance
+    {
+        Some(disposition)
+    }
+    else
+    {
+        None
+    }
+}
+
+/// Checks whether the collider lies across the straight line between `from` and `to`
+pub fn is_segment_blocked(from: &Transform, to: &Transform, obstacle: Collider) -> bool
+{
+    let segment = to_f32_vec(calculate_disposition(from, to));
+    let to_obstacle = to_f32_vec(calculate_disposition(from, obstacle.transform));
+
+    let length_squared = segment.norm_squared();
+    let closest_point = if length_squared > 0.0
+    {
+        segment * (to_obstacle.dot(&segment) / length_squared).max(0.0).min(1.0)
+    }
+    else
+    {
+        segment
+    };
+
+    (to_obstacle - closest_point).norm() < obstacle.bound.radius.0
+}
+
+fn to_f32_vec(vec: Vector2<Meter>) -> Vector2<f32>
+{
+    Vector2::new(vec.x.0, vec.y.0)
+}
+
 fn to_vector2<T>(vec: &Vector3<T>) -> Vector2<T>
     where T: 'static + Copy + PartialEq + Debug
 {
@@ -164,4 +217,69 @@ mod test
             check_projectile_collision(Transform::default().set_translation_xyz(3.6, 3.6, 0.0), collider.clone()),
             None);
     }
+
+    #[test]
+    fn test_arc_collision()
+    {
+        use std::f32::consts::FRAC_PI_4;
+
+        let origin = Transform::default();
+        let facing_down = Vector2::new(0.0, -1.0);
+        let reach = Meter::from_pixel(32.0);
+        let bounds = BoundingCircle{radius: Meter::from_pixel(8.0)};
+        let mut target = Transform::default();
+
+        // straight ahead
+        target.set_translation_xyz(0.0, -20.0, 0.0);
+        assert_eq!(
+            check_arc_collision(&origin, &facing_down, reach, FRAC_PI_4, Collider{transform: &target, bound: &bounds}),
+            Some(Vector2::new(Meter::zero(), Meter::from_pixel(-20.0))),
+            "In front");
+
+        // behind
+        target.set_translation_xyz(0.0, 20.0, 0.0);
+        assert_eq!(
+            check_arc_collision(&origin, &facing_down, reach, FRAC_PI_4, Collider{transform: &target, bound: &bounds}),
+            None,
+            "Behind");
+
+        // center is out of the sector but the body reaches into it
+        target.set_translation_xyz(20.0, -15.0, 0.0);
+        assert!(
+            check_arc_collision(&origin, &facing_down, reach, FRAC_PI_4, Collider{transform: &target, bound: &bounds}).is_some(),
+            "Partially in the sector");
+
+        // beside
+        target.set_translation_xyz(20.0, 0.0, 0.0);
+        assert_eq!(
+            check_arc_collision(&origin, &facing_down, reach, FRAC_PI_4, Collider{transform: &target, bound: &bounds}),
+            None,
+            "Beside");
+
+        // too far
+        target.set_translation_xyz(0.0, -41.0, 0.0);
+        assert_eq!(
+            check_arc_collision(&origin, &facing_down, reach, FRAC_PI_4, Collider{transform: &target, bound: &bounds}),
+            None,
+            "Out of reach");
+    }
+
+    #[test]
+    fn test_segment_blocked()
+    {
+        let from = Transform::default();
+        let mut to = Transform::default();
+        to.set_translation_xyz(64.0, 0.0, 0.0);
+        let bounds = BoundingCircle{radius: Meter::from_pixel(8.0)};
+        let mut obstacle = Transform::default();
+
+        obstacle.set_translation_xyz(32.0, 4.0, 0.0);
+        assert!(is_segment_blocked(&from, &to, Collider{transform: &obstacle, bound: &bounds}), "Obstacle in between");
+
+        obstacle.set_translation_xyz(32.0, 10.0, 0.0);
+        assert!(!is_segment_blocked(&from, &to, Collider{transform: &obstacle, bound: &bounds}), "Obstacle beside");
+
+        obstacle.set_translation_xyz(80.0, 0.0, 0.0);
+        assert!(!is_segment_blocked(&from, &to, Collider{transform: &obstacle, bound: &bounds}), "Obstacle behind the target");
+    }
 }
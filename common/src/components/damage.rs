@@ -1,6 +1,7 @@
 use amethyst::core::ecs::{Component, VecStorage};
 
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(test, derive(PartialEq))]
 pub struct Damage(pub u16);
 
 impl Component for Damage {
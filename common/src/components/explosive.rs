@@ -0,0 +1,10 @@
+use amethyst::core::ecs::{Component, DenseVecStorage};
+use crate::explosion::ExplosionDetails;
+
+/// Projectile exploding on impact
+#[derive(Clone, Debug)]
+pub struct Explosive(pub ExplosionDetails);
+
+impl Component for Explosive {
+    type Storage = DenseVecStorage<Self>;
+}
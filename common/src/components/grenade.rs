@@ -0,0 +1,17 @@
+use amethyst::core::ecs::{Component, DenseVecStorage};
+use std::time::Duration;
+use derive_new::new;
+use crate::components::weapon::GrenadeDetails;
+use crate::explosion::ExplosionDetails;
+
+/// Thrown explosive rolling and bouncing around until its fuse burns down
+#[derive(new, Clone, Debug)]
+pub struct Grenade {
+    pub details: GrenadeDetails,
+    pub explosion: ExplosionDetails,
+    pub detonates_at: Duration,
+}
+
+impl Component for Grenade {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -3,6 +3,8 @@ use serde::{Serialize, Deserialize};
 use std::ops::SubAssign;
 use crate::components::Damage;
 
+pub const MAX_ARMOR: u16 = 100;
+
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct Health(pub u16);
@@ -16,3 +18,72 @@ impl SubAssign<Damage> for Health {
         self.0 -= damage.0;
     }
 }
+
+impl Health {
+    /// Returns whether the health has changed
+    pub fn heal(&mut self, amount: u16, max_health: MaxHealth) -> bool {
+        let healed = self.0.saturating_add(amount).min(max_health.0).max(self.0);
+        let changed = healed != self.0;
+        self.0 = healed;
+        changed
+    }
+}
+
+/// Healing does not raise the health above this
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MaxHealth(pub u16);
+
+impl Component for MaxHealth {
+    type Storage = VecStorage<Self>;
+}
+
+/// Takes a part of the damage until it is worn out
+#[derive(Copy, Clone, Debug, Default, Serialize, Deserialize, PartialEq)]
+pub struct Armor(pub u16);
+
+impl Component for Armor {
+    type Storage = VecStorage<Self>;
+}
+
+impl Armor {
+    /// Absorbs the `absorption` ratio of the damage as far as the armor lasts.
+    /// Returns the rest of the damage which hurts the health.
+    pub fn absorb(&mut self, damage: Damage, absorption: f32) -> Damage {
+        let absorbed = ((damage.0 as f32 * absorption.min(1.0).max(0.0)).round() as u16).min(self.0);
+        self.0 -= absorbed;
+        Damage(damage.0 - absorbed)
+    }
+
+    /// Returns whether the armor has changed
+    pub fn repair(&mut self, amount: u16) -> bool {
+        let repaired = self.0.saturating_add(amount).min(MAX_ARMOR).max(self.0);
+        let changed = repaired != self.0;
+        self.0 = repaired;
+        changed
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn armor_absorbs_its_share_while_it_lasts() {
+        let mut armor = Armor(10);
+        assert_eq!(armor.absorb(Damage(10), 0.6), Damage(4));
+        assert_eq!(armor, Armor(4));
+        assert_eq!(armor.absorb(Damage(20), 0.6), Damage(16));
+        assert_eq!(armor, Armor(0));
+        assert_eq!(armor.absorb(Damage(20), 0.6), Damage(20));
+    }
+
+    #[test]
+    fn healing_stops_at_max_health() {
+        let mut health = Health(90);
+        assert!(health.heal(5, MaxHealth(100)));
+        assert_eq!(health, Health(95));
+        assert!(health.heal(50, MaxHealth(100)));
+        assert_eq!(health, Health(100));
+        assert!(!health.heal(1, MaxHealth(100)));
+    }
+}
@@ -31,6 +31,7 @@ bitflags::bitflags! {
         const SELECT3 =  0b0000_0100_0000_0000;
         const SELECT4 =  0b0000_1000_0000_0000;
         const SELECT5 =  0b0001_0000_0000_0000;
+        const MELEE =    0b0010_0000_0000_0000;
     }
 
 }
@@ -43,9 +44,17 @@ pub struct Input
 }
 
 impl Input {
-    /// returns the first SELECT value if any of them is active. Otherwise returns None
-    pub fn get_selection(&self) -> Option<&InputFlags> {
-        SELECTIONS.iter().find(|&select| self.flags.intersects(*select))
+    /// returns the inventory slot index of the first active SELECT value. Otherwise returns None
+    pub fn selected_slot(&self) -> Option<usize> {
+        SELECTIONS.iter().position(|&select| self.flags.intersects(select))
+    }
+
+    /// Inputs arriving from the network are not trusted, the cursor has to be a finite position
+    /// and only the known flags may be set
+    pub fn is_valid(&self) -> bool {
+        self.cursor.x.0.is_finite()
+            && self.cursor.y.0.is_finite()
+            && InputFlags::from_bits(self.flags.bits()).is_some()
     }
 }
 
@@ -0,0 +1,24 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+use amethyst::core::math::Vector2;
+use std::time::Duration;
+use crate::metric_dimension::{MeterPerSec, Second};
+
+/// Extra velocity applied on top of the voluntary movement for a short time
+#[derive(Debug)]
+pub struct Knockback {
+    pub velocity: Vector2<MeterPerSec>,
+    pub until: Duration,
+}
+
+impl Knockback {
+    pub fn new(velocity: Vector2<MeterPerSec>, duration: Second, timing_start: Duration) -> Self {
+        Knockback {
+            velocity,
+            until: timing_start + duration.into_duration(),
+        }
+    }
+}
+
+impl Component for Knockback {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,28 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+
+/// State of the melee attack of an entity. Static details are in `MeleeDetails` resource.
+pub struct Melee {
+    /// Time::absolute_time_seconds() of the last attack
+    pub last_attack_time: f64,
+    /// One attack per button press
+    pub input_lifted: bool,
+}
+
+impl Default for Melee {
+    fn default() -> Self {
+        Melee {
+            last_attack_time: 0.0,
+            input_lifted: true,
+        }
+    }
+}
+
+impl Melee {
+    pub fn is_allowed_to_attack(&self, current_absolute_time: f64, cooldown_sec: f64) -> bool {
+        self.input_lifted && current_absolute_time > self.last_attack_time + cooldown_sec
+    }
+}
+
+impl Component for Melee {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -2,13 +2,23 @@ pub use bounding_circle::BoundingCircle;
 pub use damage::Damage;
 pub use time_limit::Lifespan;
 pub use eliminate::Eliminated;
-pub use health::Health;
+pub use explosive::Explosive;
+pub use grenade::Grenade;
+pub use health::{Armor, Health, MaxHealth, MAX_ARMOR};
 pub use input::{Input, InputFlags};
+pub use knockback::Knockback;
+pub use melee::Melee;
 pub use network_id::{EntityType, NetworkId};
+pub use owner::Owner;
+pub use pickup::Pickup;
 pub use player::Player;
 pub use projectile::Projectile;
 pub use respawn::Respawn;
+pub use stamina::{Stamina, MAX_STAMINA};
+pub use status_effect::{ActiveEffect, OnHitEffect, StatusEffectDetails, StatusEffectKind, StatusEffects};
+pub use team::Team;
 pub use velocity::Velocity;
+pub use weapon_name::WeaponName;
 
 mod input;
 mod network_id;
@@ -22,3 +32,13 @@ mod projectile;
 mod damage;
 mod eliminate;
 mod respawn;
+mod pickup;
+mod melee;
+mod knockback;
+mod explosive;
+mod owner;
+mod grenade;
+mod team;
+mod weapon_name;
+mod status_effect;
+mod stamina;
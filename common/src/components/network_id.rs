@@ -12,4 +12,23 @@ pub struct NetworkId {
 #[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum EntityType {
     Player,
+    WeaponPickup,
+    Barrel,
+    ExplosiveBarrel,
+    Grenade,
+    Medkit,
+    ArmorPickup,
+    EffectPickup,
+    Flag,
+}
+
+impl EntityType {
+    /// Static entities are placed by the map on both sides with the same network ids, so their
+    /// state is not broadcast continuously.
+    pub fn is_static(&self) -> bool {
+        match self {
+            EntityType::Barrel | EntityType::ExplosiveBarrel => true,
+            _ => false,
+        }
+    }
 }
\ No newline at end of file
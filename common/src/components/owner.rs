@@ -0,0 +1,9 @@
+use amethyst::core::ecs::{Component, DenseVecStorage, Entity};
+
+/// The entity which created this one, e.g. the shooter of a projectile
+#[derive(Copy, Clone, Debug)]
+pub struct Owner(pub Entity);
+
+impl Component for Owner {
+    type Storage = DenseVecStorage<Self>;
+}
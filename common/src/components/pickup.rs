@@ -0,0 +1,18 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage};
+use crate::components::weapon::Weapon;
+use crate::components::StatusEffectDetails;
+
+/// An item lying on the ground that can be collected by players
+pub enum Pickup {
+    Weapon(Weapon),
+    /// Heals this much health
+    Medkit(u16),
+    /// Gives this much armor
+    Armor(u16),
+    /// Applies the effect on the player, e.g. a speed boost
+    StatusEffect(StatusEffectDetails),
+}
+
+impl Component for Pickup {
+    type Storage = DenseVecStorage<Self>;
+}
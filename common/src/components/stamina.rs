@@ -0,0 +1,53 @@
+use amethyst::ecs::prelude::{Component, VecStorage};
+use serde::{Serialize, Deserialize};
+use crate::components::{Input, InputFlags};
+
+pub const MAX_STAMINA: f32 = 100.0;
+
+/// Drained by sprinting, regenerates while the player is not sprinting
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct Stamina {
+    pub value: f32,
+    /// Set when the stamina runs out, the player cannot sprint until recovered
+    pub exhausted: bool,
+}
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina::new(MAX_STAMINA)
+    }
+}
+
+impl Component for Stamina {
+    type Storage = VecStorage<Self>;
+}
+
+impl Stamina {
+    pub fn new(value: f32) -> Self {
+        Stamina { value, exhausted: false }
+    }
+
+    /// Sprinting needs the run key held while moving forward, and some stamina left without being exhausted
+    pub fn is_sprinting(&self, input: &Input) -> bool {
+        input.flags.contains(InputFlags::RUN | InputFlags::FORWARD) && self.value > 0.0 && !self.exhausted
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn input(flags: InputFlags) -> Input {
+        Input { flags, ..Input::default() }
+    }
+
+    #[test]
+    fn sprinting_needs_forward_movement_and_stamina() {
+        assert!(Stamina::new(10.0).is_sprinting(&input(InputFlags::RUN | InputFlags::FORWARD)));
+        assert!(!Stamina::new(10.0).is_sprinting(&input(InputFlags::RUN | InputFlags::BACKWARD)));
+        assert!(!Stamina::new(10.0).is_sprinting(&input(InputFlags::FORWARD)));
+        assert!(!Stamina::new(0.0).is_sprinting(&input(InputFlags::RUN | InputFlags::FORWARD)));
+        assert!(!Stamina { value: 10.0, exhausted: true }.is_sprinting(&input(InputFlags::RUN | InputFlags::FORWARD)));
+    }
+}
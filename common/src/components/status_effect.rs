@@ -0,0 +1,181 @@
+use amethyst::ecs::prelude::{Component, DenseVecStorage, Entity};
+use serde::{Serialize, Deserialize};
+use std::time::Duration;
+use crate::metric_dimension::Second;
+use crate::network::StatusEffectState;
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum StatusEffectKind {
+    Slow,
+    Bleed,
+    Burn,
+    SpeedBoost,
+}
+
+/// A timed effect on a player, e.g. applied by a weapon hit
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct StatusEffectDetails {
+    /// Effects of the same kind stack instead of being applied side by side
+    pub kind: StatusEffectKind,
+    pub duration: Second,
+    /// Movement speed is multiplied by this
+    #[serde(default = "default_multiplier")]
+    pub speed_multiplier: f32,
+    /// Fire rate of the weapons is multiplied by this
+    #[serde(default = "default_multiplier")]
+    pub fire_rate_multiplier: f32,
+    /// Damage dealt every second while the effect lasts
+    #[serde(default)]
+    pub damage_per_sec: u16,
+    /// Applying the effect again adds a stack until this limit, then only refreshes its duration
+    #[serde(default = "default_max_stacks")]
+    pub max_stacks: u32,
+}
+
+fn default_multiplier() -> f32 {
+    1.0
+}
+
+fn default_max_stacks() -> u32 {
+    1
+}
+
+/// Bullets having this apply the effect on the players they hit
+#[derive(Clone, Debug)]
+pub struct OnHitEffect(pub StatusEffectDetails);
+
+impl Component for OnHitEffect {
+    type Storage = DenseVecStorage<Self>;
+}
+
+#[derive(Clone, Debug)]
+pub struct ActiveEffect {
+    pub details: StatusEffectDetails,
+    pub stacks: u32,
+    pub expires_at: Duration,
+    /// The one who applied the effect last, the damage over time is credited to them
+    pub source: Option<Entity>,
+}
+
+/// The status effects currently affecting a player
+#[derive(Clone, Debug, Default)]
+pub struct StatusEffects {
+    effects: Vec<ActiveEffect>,
+}
+
+impl Component for StatusEffects {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl StatusEffects {
+    pub fn apply(&mut self, details: StatusEffectDetails, source: Option<Entity>, now: Duration) {
+        let expires_at = now + details.duration.into_duration();
+        match self.effects.iter_mut().find(|effect| effect.details.kind == details.kind) {
+            Some(effect) => {
+                effect.stacks = (effect.stacks + 1).min(details.max_stacks.max(1));
+                effect.expires_at = effect.expires_at.max(expires_at);
+                effect.details = details;
+                effect.source = source.or(effect.source);
+            }
+            None => self.effects.push(ActiveEffect { details, stacks: 1, expires_at, source }),
+        }
+    }
+
+    /// Returns whether any effect has ended
+    pub fn remove_expired(&mut self, now: Duration) -> bool {
+        let count = self.effects.len();
+        self.effects.retain(|effect| effect.expires_at > now);
+        self.effects.len() != count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.effects.is_empty()
+    }
+
+    pub fn effects(&self) -> &[ActiveEffect] {
+        &self.effects
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        self.effects.iter()
+            .map(|effect| effect.details.speed_multiplier.powi(effect.stacks as i32))
+            .product()
+    }
+
+    pub fn fire_rate_multiplier(&self) -> f32 {
+        self.effects.iter()
+            .map(|effect| effect.details.fire_rate_multiplier.powi(effect.stacks as i32))
+            .product()
+    }
+
+    pub fn state(&self, now: Duration) -> Vec<StatusEffectState> {
+        self.effects.iter()
+            .map(|effect| StatusEffectState {
+                kind: effect.details.kind,
+                stacks: effect.stacks,
+                remaining: Second::from(effect.expires_at.checked_sub(now).unwrap_or_default()),
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn bleed() -> StatusEffectDetails {
+        StatusEffectDetails {
+            kind: StatusEffectKind::Bleed,
+            duration: Second(4.0),
+            speed_multiplier: 1.0,
+            fire_rate_multiplier: 1.0,
+            damage_per_sec: 2,
+            max_stacks: 2,
+        }
+    }
+
+    fn slow() -> StatusEffectDetails {
+        StatusEffectDetails {
+            kind: StatusEffectKind::Slow,
+            duration: Second(2.0),
+            speed_multiplier: 0.5,
+            fire_rate_multiplier: 0.8,
+            damage_per_sec: 0,
+            max_stacks: 1,
+        }
+    }
+
+    #[test]
+    fn same_kind_stacks_until_the_limit_and_refreshes() {
+        let mut effects = StatusEffects::default();
+        effects.apply(bleed(), None, Duration::from_secs(0));
+        effects.apply(bleed(), None, Duration::from_secs(1));
+        effects.apply(bleed(), None, Duration::from_secs(2));
+
+        assert_eq!(effects.effects().len(), 1);
+        assert_eq!(effects.effects()[0].stacks, 2);
+        assert_eq!(effects.effects()[0].expires_at, Duration::from_secs(6));
+    }
+
+    #[test]
+    fn multipliers_of_different_effects_are_combined() {
+        let mut effects = StatusEffects::default();
+        effects.apply(slow(), None, Duration::from_secs(0));
+        effects.apply(bleed(), None, Duration::from_secs(0));
+
+        assert_eq!(effects.speed_multiplier(), 0.5);
+        assert_eq!(effects.fire_rate_multiplier(), 0.8);
+    }
+
+    #[test]
+    fn expired_effects_are_removed() {
+        let mut effects = StatusEffects::default();
+        effects.apply(slow(), None, Duration::from_secs(0));
+        effects.apply(bleed(), None, Duration::from_secs(0));
+
+        assert!(!effects.remove_expired(Duration::from_secs(1)));
+        assert!(effects.remove_expired(Duration::from_secs(3)));
+        assert_eq!(effects.effects().len(), 1);
+        assert_eq!(effects.effects()[0].details.kind, StatusEffectKind::Bleed);
+    }
+}
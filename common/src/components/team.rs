@@ -0,0 +1,43 @@
+use amethyst::core::ecs::{Component, DenseVecStorage};
+use serde::{Serialize, Deserialize};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Team {
+    Red,
+    Blue,
+}
+
+impl Component for Team {
+    type Storage = DenseVecStorage<Self>;
+}
+
+impl Team {
+    pub const ALL: [Team; 2] = [Team::Red, Team::Blue];
+
+    /// Chooses the team with the fewest members. On a tie the first team in `Team::ALL` wins.
+    pub fn balanced<I: IntoIterator<Item=Team>>(members: I) -> Team {
+        let mut counts = [0usize; Team::ALL.len()];
+        for member in members {
+            counts[member as usize] += 1;
+        }
+
+        Team::ALL.iter()
+            .zip(counts.iter())
+            .min_by_key(|(_, &count)| count)
+            .map(|(&team, _)| team)
+            .unwrap_or(Team::Red)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn joining_player_goes_to_smaller_team() {
+        assert_eq!(Team::balanced(vec![]), Team::Red);
+        assert_eq!(Team::balanced(vec![Team::Red]), Team::Blue);
+        assert_eq!(Team::balanced(vec![Team::Red, Team::Blue]), Team::Red);
+        assert_eq!(Team::balanced(vec![Team::Blue, Team::Blue, Team::Red]), Team::Red);
+    }
+}
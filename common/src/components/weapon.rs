@@ -3,52 +3,123 @@ use std::time::Duration;
 pub use weapon_details::*;
 use crate::resources::weapon::{GunResource, GunId};
 use crate::metric_dimension::Second;
+use crate::network::{InventoryState, WeaponSlot};
 
-const NUMBER_OF_SLOTS: usize = 3;
+/// Number of inventory slots. There is one slot for every SELECT input flag.
+pub const NUMBER_OF_SLOTS: usize = 5;
 
-/// This is the first approach of the inventory. For now it stores fix number of guns
+/// The inventory of a player. Weapons can be added (e.g. picked up from the ground) and removed
+/// (e.g. dropped on death) at runtime. Empty slots can not be selected.
 #[derive(Component)]
 #[storage(DenseVecStorage)]
 pub struct Holster {
-    guns: [(Weapon, &'static str); NUMBER_OF_SLOTS],
-    selected: usize
+    slots: Vec<Option<Weapon>>,
+    selected: usize,
+    /// Time::absolute_time() until no weapon may shoot
+    cooldown_until: f64,
 }
 
 impl Holster {
+    /// Creates the starting loadout
     pub fn new(gun_resource: &GunResource) -> Self {
-        let guns = [
-            (Weapon::new(gun_resource.get_gun(GunId::Revolver)), "Revolver"),
-            (Weapon::new(gun_resource.get_gun(GunId::Shotgun)), "Shotgun"),
-            (Weapon::new(gun_resource.get_gun(GunId::Rifle)), "Rifle")
-        ];
-
-        Holster { guns, selected: 0 }
+        Self::new_with_guns(vec![
+            Weapon::new(gun_resource.get_gun(GunId::Revolver)),
+            Weapon::new(gun_resource.get_gun(GunId::Shotgun)),
+            Weapon::new(gun_resource.get_gun(GunId::Rifle)),
+            Weapon::new(gun_resource.get_gun(GunId::RocketLauncher)),
+            Weapon::new(gun_resource.get_gun(GunId::Grenade)),
+        ])
     }
 
-    pub fn new_with_guns(guns: [(Weapon, &'static str); NUMBER_OF_SLOTS]) -> Self {
+    /// Puts the guns into the first slots. Guns over `NUMBER_OF_SLOTS` are discarded.
+    pub fn new_with_guns(guns: Vec<Weapon>) -> Self {
+        let mut slots: Vec<Option<Weapon>> = guns.into_iter()
+            .take(NUMBER_OF_SLOTS)
+            .map(Some)
+            .collect();
+        slots.resize_with(NUMBER_OF_SLOTS, || None);
+
         Holster {
-            guns,
-            selected: 0
+            slots,
+            selected: 0,
+            cooldown_until: 0.0,
         }
     }
 
-    pub fn switch(&mut self, slot: usize) -> Option<&'static str> {
-        if let Some(newly_selected) = self.guns.get(slot) {
+    /// Selects the given slot if it holds a weapon. Returns whether the selection happened.
+    pub fn switch(&mut self, slot: usize) -> bool {
+        if let Some(Some(_)) = self.slots.get(slot) {
             self.selected = slot;
-            Some(newly_selected.1)
-        } else { None }
+            true
+        } else { false }
+    }
+
+    /// The cooldown of the last shot is kept when the weapon is switched,
+    /// so switching after every shot does not make the shooting faster
+    pub fn is_cooled_down(&self, current_absolute_time: f64) -> bool {
+        current_absolute_time >= self.cooldown_until
+    }
+
+    pub fn start_cooldown(&mut self, until: f64) {
+        self.cooldown_until = until;
     }
 
     pub fn active_slot(&self) -> usize {
         self.selected
     }
 
-    pub fn active_gun(&self) -> &Weapon {
-        &self.guns[self.selected].0
+    pub fn active_gun(&self) -> Option<&Weapon> {
+        self.slots[self.selected].as_ref()
+    }
+
+    pub fn active_gun_mut(&mut self) -> Option<&mut Weapon> {
+        self.slots[self.selected].as_mut()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.slots.iter().all(Option::is_some)
+    }
+
+    /// Puts the weapon into the first empty slot and returns the index of that slot.
+    /// When there is no empty slot the weapon is given back.
+    /// If the inventory was empty, the new weapon becomes the active one.
+    pub fn add(&mut self, weapon: Weapon) -> Result<usize, Weapon> {
+        let was_empty = self.active_gun().is_none();
+        match self.slots.iter().position(Option::is_none) {
+            Some(slot) => {
+                self.slots[slot] = Some(weapon);
+                if was_empty {
+                    self.selected = slot;
+                }
+                Ok(slot)
+            }
+            None => Err(weapon)
+        }
+    }
+
+    /// Puts the weapon into the active slot and returns the weapon held there before.
+    pub fn replace_active(&mut self, weapon: Weapon) -> Option<Weapon> {
+        self.slots[self.selected].replace(weapon)
     }
 
-    pub fn active_gun_mut(&mut self) -> &mut Weapon {
-        &mut self.guns[self.selected].0
+    /// Empties the inventory and returns every weapon held
+    pub fn take_all(&mut self) -> Vec<Weapon> {
+        self.selected = 0;
+        self.slots.iter_mut().filter_map(Option::take).collect()
+    }
+
+    /// Snapshot of the inventory that can be sent to the owner client
+    pub fn inventory_state(&self) -> InventoryState {
+        InventoryState {
+            slots: self.slots.iter()
+                .map(|slot| slot.as_ref().map(|weapon| WeaponSlot {
+                    name: weapon.details.name.clone(),
+                    magazine_size: weapon.details.magazine_size,
+                    ammo_in_magazine: weapon.bullets_left_in_magazine,
+                }))
+                .collect(),
+            selected: self.selected,
+        }
     }
 }
 
@@ -76,8 +147,9 @@ impl Weapon {
         }
     }
 
-    pub fn is_allowed_to_shoot(&self, current_absolute_time: f64) -> bool {
-        let shoot_interval = 1.0 / self.details.fire_rate as f64;
+    /// Status effects can make the weapon fire faster or slower by the `fire_rate_multiplier`
+    pub fn is_allowed_to_shoot(&self, current_absolute_time: f64, fire_rate_multiplier: f32) -> bool {
+        let shoot_interval = self.shoot_interval(fire_rate_multiplier);
         let need_input_press = match self.details.shot {
             Shot::Single => true,
             Shot::Burst(_) => true,
@@ -91,6 +163,11 @@ impl Weapon {
             && current_absolute_time > self.last_shot_time + shoot_interval
     }
 
+    /// Minimum time between two shots in seconds
+    pub fn shoot_interval(&self, fire_rate_multiplier: f32) -> f64 {
+        1.0 / (self.details.fire_rate * fire_rate_multiplier) as f64
+    }
+
     pub fn bullet_lifespan_sec(&self) -> Second {
         self.details.bullet_distance_limit / self.details.bullet_speed
     }
@@ -104,6 +181,8 @@ mod weapon_details {
     use serde::Deserialize;
     use crate::metric_dimension::length::Meter;
     use crate::metric_dimension::{MeterPerSec, Second};
+    use crate::explosion::ExplosionDetails;
+    use crate::components::StatusEffectDetails;
 
     #[derive(Debug, PartialEq, Deserialize, Clone)]
     pub enum Shot {
@@ -119,6 +198,8 @@ mod weapon_details {
 
     #[derive(Deserialize, Clone, PartialEq)]
     pub struct WeaponDetails {
+        /// Displayed name of the weapon
+        pub name: String,
         /// Fire rate per seconds [1/s]
         pub fire_rate: f32,
         /// Number of bullets in a single magazine. 0 mean infinite (e.g. laser pistol)
@@ -137,5 +218,115 @@ mod weapon_details {
         pub shot: Shot,
         /// Number of pellets when shot
         pub pellet_number: u32,
+        /// Bullets explode on impact if set.
+        /// Thrown grenades explode when their fuse burns down.
+        #[serde(default)]
+        pub explosion: Option<ExplosionDetails>,
+        /// The weapon throws a bouncing grenade instead of shooting bullets if set
+        #[serde(default)]
+        pub grenade: Option<GrenadeDetails>,
+        /// Applied on the players hit by the bullets
+        #[serde(default)]
+        pub status_effect: Option<StatusEffectDetails>,
+    }
+
+    #[derive(Deserialize, Clone, Debug, PartialEq)]
+    pub struct GrenadeDetails {
+        /// Time between the throw and the detonation
+        pub fuse: Second,
+        /// Deceleration of the rolling grenade [m/s^2]
+        pub friction: f32,
+        /// Ratio of the speed kept when bouncing off an obstacle. 0 stops it, 1 is a perfect bounce
+        pub bounciness: f32,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::metric_dimension::length::Meter;
+    use crate::metric_dimension::MeterPerSec;
+
+    fn make_weapon(name: &str) -> Weapon {
+        Weapon::new(WeaponDetails {
+            name: name.to_string(),
+            fire_rate: 1.0,
+            magazine_size: 6,
+            reload_time: Second(1.0),
+            damage: 5,
+            spread: 0.0,
+            bullet_distance_limit: Meter(5.0),
+            bullet_speed: MeterPerSec(10.0),
+            shot: Shot::Single,
+            pellet_number: 1,
+            explosion: None,
+            grenade: None,
+            status_effect: None,
+        })
+    }
+
+    fn slot_names(holster: &Holster) -> Vec<Option<String>> {
+        holster.inventory_state().slots.into_iter()
+            .map(|slot| slot.map(|weapon| weapon.name))
+            .collect()
+    }
+
+    #[test]
+    fn added_weapon_goes_to_first_empty_slot() {
+        let mut holster = Holster::new_with_guns(vec![make_weapon("first")]);
+        assert_eq!(holster.add(make_weapon("second")).ok(), Some(1));
+        assert_eq!(slot_names(&holster), vec![Some("first".to_string()), Some("second".to_string()), None, None, None]);
+        assert_eq!(holster.active_slot(), 0);
+    }
+
+    #[test]
+    fn full_holster_gives_back_the_weapon() {
+        let guns = (0..NUMBER_OF_SLOTS).map(|i| make_weapon(&i.to_string())).collect();
+        let mut holster = Holster::new_with_guns(guns);
+        assert!(holster.is_full());
+
+        let rejected = holster.add(make_weapon("extra")).expect_err("Weapon added to a full holster");
+        assert_eq!(rejected.details.name, "extra");
+    }
+
+    #[test]
+    fn empty_slot_can_not_be_selected() {
+        let mut holster = Holster::new_with_guns(vec![make_weapon("first")]);
+        assert!(!holster.switch(3));
+        assert!(!holster.switch(NUMBER_OF_SLOTS));
+        assert_eq!(holster.active_slot(), 0);
+    }
+
+    #[test]
+    fn weapon_added_to_empty_holster_becomes_active() {
+        let mut holster = Holster::new_with_guns(vec![make_weapon("first"), make_weapon("second")]);
+        holster.switch(1);
+        let dropped = holster.take_all();
+        assert_eq!(dropped.len(), 2);
+        assert!(holster.active_gun().is_none());
+
+        holster.add(make_weapon("picked")).expect("Could not add weapon");
+        assert_eq!(holster.active_gun().map(|weapon| weapon.details.name.as_str()), Some("picked"));
+    }
+
+    #[test]
+    fn cooldown_is_kept_after_switching() {
+        let mut holster = Holster::new_with_guns(vec![make_weapon("first"), make_weapon("second")]);
+        assert!(holster.is_cooled_down(0.0));
+
+        let shoot_interval = holster.active_gun().unwrap().shoot_interval(1.0);
+        holster.start_cooldown(10.0 + shoot_interval);
+        holster.switch(1);
+        assert!(!holster.is_cooled_down(10.5));
+        assert!(holster.is_cooled_down(11.0));
+    }
+
+    #[test]
+    fn replacing_active_weapon_returns_the_previous_one() {
+        let mut holster = Holster::new_with_guns(vec![make_weapon("first"), make_weapon("second")]);
+        holster.switch(1);
+        let previous = holster.replace_active(make_weapon("new")).expect("Active slot was empty");
+        assert_eq!(previous.details.name, "second");
+        assert_eq!(slot_names(&holster)[1], Some("new".to_string()));
     }
 }
@@ -0,0 +1,9 @@
+use amethyst::core::ecs::{Component, DenseVecStorage};
+
+/// Name of the weapon a projectile or grenade comes from, used for kill attribution
+#[derive(Clone, Debug)]
+pub struct WeaponName(pub String);
+
+impl Component for WeaponName {
+    type Storage = DenseVecStorage<Self>;
+}
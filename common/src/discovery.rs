@@ -0,0 +1,180 @@
+use serde::{Serialize, Deserialize};
+use std::io;
+use std::net::{SocketAddr, UdpSocket};
+
+/// Servers listen to discovery queries on this port besides their game port
+pub const DISCOVERY_PORT: u16 = 5746;
+
+/// Clients and servers of different protocol versions can not play together
+pub const PROTOCOL_VERSION: u32 = 2;
+
+const MAX_MESSAGE_SIZE: usize = 1024;
+
+/// Discovery runs on plain UDP, so clients can broadcast it on the LAN without a connection
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum DiscoveryMessage {
+    Query {
+        protocol_version: u32,
+    },
+    Answer(ServerInfo),
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ServerInfo {
+    pub name: String,
+    pub map: String,
+    pub player_count: usize,
+    pub max_players: usize,
+    pub protocol_version: u32,
+    /// Clients connect to this port of the answering host
+    pub game_port: u16,
+}
+
+impl ServerInfo {
+    pub fn is_compatible(&self) -> bool {
+        self.protocol_version == PROTOCOL_VERSION
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DiscoveredServer {
+    /// The game address of the server
+    pub address: SocketAddr,
+    pub info: ServerInfo,
+}
+
+fn encode(message: &DiscoveryMessage) -> io::Result<Vec<u8>> {
+    rmp_serde::to_vec(message).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+fn decode(buf: &[u8]) -> io::Result<DiscoveryMessage> {
+    rmp_serde::from_read_ref(buf).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Reads every datagram waiting on the non-blocking socket
+fn receive_all(socket: &UdpSocket) -> io::Result<Vec<(SocketAddr, DiscoveryMessage)>> {
+    let mut messages = Vec::new();
+    let mut buf = [0u8; MAX_MESSAGE_SIZE];
+    loop {
+        match socket.recv_from(&mut buf) {
+            Ok((len, sender)) => match decode(&buf[..len]) {
+                Ok(message) => messages.push((sender, message)),
+                Err(err) => log::debug!("Invalid discovery message from {}: {}", sender, err),
+            },
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(messages),
+            Err(err) => return Err(err),
+        }
+    }
+}
+
+/// Answers the discovery queries on the server
+pub struct DiscoveryResponder {
+    socket: UdpSocket,
+}
+
+impl DiscoveryResponder {
+    pub fn bind(address: SocketAddr) -> io::Result<Self> {
+        let socket = UdpSocket::bind(address)?;
+        socket.set_nonblocking(true)?;
+        Ok(DiscoveryResponder { socket })
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Answers the queries arrived since the last call, returns how many were answered
+    pub fn answer_queries(&self, info: &ServerInfo) -> io::Result<usize> {
+        let mut answered = 0;
+        for (sender, message) in receive_all(&self.socket)? {
+            if let DiscoveryMessage::Query { .. } = message {
+                self.socket.send_to(&encode(&DiscoveryMessage::Answer(info.clone()))?, sender)?;
+                answered += 1;
+            }
+        }
+        Ok(answered)
+    }
+}
+
+/// Looks for servers on the client
+pub struct DiscoveryClient {
+    socket: UdpSocket,
+}
+
+impl DiscoveryClient {
+    pub fn bind() -> io::Result<Self> {
+        let socket = UdpSocket::bind(SocketAddr::from(([0, 0, 0, 0], 0)))?;
+        socket.set_broadcast(true)?;
+        socket.set_nonblocking(true)?;
+        Ok(DiscoveryClient { socket })
+    }
+
+    /// Asks every server on the local network
+    pub fn broadcast(&self) -> io::Result<()> {
+        self.query(SocketAddr::from(([255, 255, 255, 255], DISCOVERY_PORT)))
+    }
+
+    pub fn query(&self, target: SocketAddr) -> io::Result<()> {
+        let query = encode(&DiscoveryMessage::Query { protocol_version: PROTOCOL_VERSION })?;
+        self.socket.send_to(&query, target)?;
+        Ok(())
+    }
+
+    /// The servers answered since the last call
+    pub fn receive(&self) -> io::Result<Vec<DiscoveredServer>> {
+        Ok(receive_all(&self.socket)?.into_iter()
+            .filter_map(|(sender, message)| match message {
+                DiscoveryMessage::Answer(info) => Some(DiscoveredServer {
+                    address: SocketAddr::new(sender.ip(), info.game_port),
+                    info,
+                }),
+                DiscoveryMessage::Query { .. } => None,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    fn server_info() -> ServerInfo {
+        ServerInfo {
+            name: "Test server".to_string(),
+            map: "rust2".to_string(),
+            player_count: 3,
+            max_players: 16,
+            protocol_version: PROTOCOL_VERSION,
+            game_port: 5745,
+        }
+    }
+
+    #[test]
+    fn server_is_discovered_over_loopback() {
+        let responder = DiscoveryResponder::bind(SocketAddr::from(([127, 0, 0, 1], 0))).unwrap();
+        let client = DiscoveryClient::bind().unwrap();
+        client.query(responder.local_addr().unwrap()).unwrap();
+
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let mut answered = 0;
+        let mut discovered = Vec::new();
+        while discovered.is_empty() && Instant::now() < deadline {
+            answered += responder.answer_queries(&server_info()).unwrap();
+            discovered.extend(client.receive().unwrap());
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        assert_eq!(answered, 1);
+        assert_eq!(discovered, vec![DiscoveredServer {
+            address: SocketAddr::from(([127, 0, 0, 1], 5745)),
+            info: server_info(),
+        }]);
+    }
+
+    #[test]
+    fn messages_survive_encoding() {
+        let message = DiscoveryMessage::Answer(server_info());
+        assert_eq!(decode(&encode(&message).unwrap()).unwrap(), message);
+    }
+}
@@ -2,20 +2,26 @@ use amethyst::core::ecs::{World, Entity};
 use amethyst::core::math::Point2;
 use amethyst::core::Transform;
 use amethyst::prelude::{WorldExt, Builder};
-use crate::components::BoundingCircle;
+use crate::components::{BoundingCircle, NetworkId};
 use crate::metric_dimension::length::Meter;
 
 const BARREL_HEIGHT: f32 = 1.0;
 const BARREL_DIAMETER: Meter = Meter(1.0);
+pub const BARREL_RADIUS: Meter = Meter(0.5);
 
-pub fn place_barrel(world: &mut World, pos: Point2<i32>) -> Entity {
+pub fn place_barrel(world: &mut World, pos: Point2<i32>, network_id: NetworkId) -> Entity {
 
     let mut transform = Transform::default();
     transform.set_translation_xyz((pos.x as f32) * BARREL_DIAMETER.into_pixel(), (pos.y as f32) * BARREL_DIAMETER.into_pixel(), BARREL_HEIGHT);
 
-    world
-        .create_entity()
+    spawn_barrel(transform, network_id, world.create_entity()).build()
+}
+
+/// Adds the components of a barrel to the builder. The caller is responsible for building the entity.
+pub fn spawn_barrel<B: Builder>(mut transform: Transform, network_id: NetworkId, entity_builder: B) -> B {
+    transform.set_translation_z(BARREL_HEIGHT);
+    entity_builder
         .with(transform)
-        .with(BoundingCircle{radius: Meter(0.5)})
-        .build()
+        .with(BoundingCircle{radius: BARREL_RADIUS})
+        .with(network_id)
 }
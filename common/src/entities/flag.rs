@@ -0,0 +1,19 @@
+use amethyst::{
+    core::transform::Transform,
+    ecs::prelude::Builder,
+};
+
+use crate::components::NetworkId;
+
+const FLAG_HEIGHT: f32 = 0.4;
+
+pub fn spawn_flag<B: Builder>(
+    mut transform: Transform,
+    network_id: NetworkId,
+    entity_builder: B) -> B
+{
+    transform.set_translation_z(FLAG_HEIGHT);
+    entity_builder
+        .with(transform)
+        .with(network_id)
+}
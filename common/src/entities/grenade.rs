@@ -0,0 +1,27 @@
+use amethyst::{
+    core::transform::Transform,
+    core::math::Vector2,
+    ecs::prelude::Builder,
+};
+
+use crate::components::{BoundingCircle, Grenade, NetworkId, Velocity};
+use crate::metric_dimension::MeterPerSec;
+use crate::metric_dimension::length::Meter;
+
+pub const GRENADE_RADIUS: Meter = Meter(0.15);
+
+pub fn spawn_grenade<B: Builder>(
+    transform: Transform,
+    velocity: Vector2<MeterPerSec>,
+    grenade: Grenade,
+    network_id: NetworkId,
+    entity_builder: B)
+{
+    entity_builder
+        .with(transform)
+        .with(Velocity(velocity))
+        .with(BoundingCircle{radius: GRENADE_RADIUS})
+        .with(grenade)
+        .with(network_id)
+        .build();
+}
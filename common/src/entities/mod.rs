@@ -1,5 +1,11 @@
-pub use barrel::place_barrel;
+pub use barrel::{place_barrel, spawn_barrel, BARREL_RADIUS};
 pub use bullet::spawn_bullet;
+pub use flag::spawn_flag;
+pub use grenade::{spawn_grenade, GRENADE_RADIUS};
+pub use pickup::{place_item, spawn_pickup};
 
 mod barrel;
-mod bullet;
\ No newline at end of file
+mod bullet;
+mod flag;
+mod grenade;
+mod pickup;
\ No newline at end of file
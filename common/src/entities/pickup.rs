@@ -0,0 +1,40 @@
+use amethyst::{
+    core::transform::Transform,
+    ecs::prelude::{Builder, Entity},
+};
+
+use crate::components::{Pickup, NetworkId, Lifespan};
+use std::time::Duration;
+use crate::metric_dimension::Second;
+
+/// Pickups lying on the ground disappear after this time
+const PICKUP_LIFESPAN: Second = Second(60.0);
+
+pub fn spawn_pickup<B: Builder>(
+    transform: Transform,
+    pickup: Pickup,
+    network_id: NetworkId,
+    current_time: Duration,
+    entity_builder: B)
+{
+    entity_builder
+        .with(transform)
+        .with(pickup)
+        .with(network_id)
+        .with(Lifespan::new(PICKUP_LIFESPAN, current_time))
+        .build();
+}
+
+/// Places an item which stays on the ground until someone picks it up
+pub fn place_item<B: Builder>(
+    transform: Transform,
+    pickup: Pickup,
+    network_id: NetworkId,
+    entity_builder: B) -> Entity
+{
+    entity_builder
+        .with(transform)
+        .with(pickup)
+        .with(network_id)
+        .build()
+}
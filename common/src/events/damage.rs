@@ -4,4 +4,8 @@ use crate::components::Damage;
 pub struct DamageEvent {
     pub damage: Damage,
     pub target: Entity,
+    /// The entity causing the damage, if known
+    pub attacker: Option<Entity>,
+    /// Name of the weapon the damage is dealt with, if any
+    pub weapon: Option<String>,
 }
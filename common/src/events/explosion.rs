@@ -0,0 +1,13 @@
+use amethyst::core::ecs::Entity;
+use amethyst::core::math::Point2;
+use crate::explosion::ExplosionDetails;
+use crate::metric_dimension::length::Meter;
+
+pub struct ExplosionEvent {
+    pub position: Point2<Meter>,
+    pub details: ExplosionDetails,
+    /// The entity which caused the explosion
+    pub owner: Option<Entity>,
+    /// Name of the weapon which fired the explosive
+    pub weapon: Option<String>,
+}
@@ -1,7 +1,11 @@
 pub use westiny_event::{AppEvent, WestinyEvent, WestinyEventReader};
 pub use entity_delete::EntityDelete;
 pub use damage::DamageEvent;
+pub use explosion::ExplosionEvent;
+pub use status_effect::StatusEffectEvent;
 
 mod westiny_event;
 mod entity_delete;
-mod damage;
\ No newline at end of file
+mod damage;
+mod explosion;
+mod status_effect;
\ No newline at end of file
@@ -0,0 +1,9 @@
+use amethyst::core::ecs::Entity;
+use crate::components::StatusEffectDetails;
+
+pub struct StatusEffectEvent {
+    pub target: Entity,
+    pub effect: StatusEffectDetails,
+    /// The entity which applied the effect, if known
+    pub source: Option<Entity>,
+}
@@ -18,7 +18,9 @@ use crate::network;
 #[derive(Clone, Debug, PartialEq)]
 pub enum AppEvent {
     Connection(network::Result<network::ClientInitialData>),
-    Disconnect,
+    /// The session ended, the reason is shown to the player
+    Disconnect(String),
+    MapChange(network::MapChange),
 }
 
 #[derive(Clone, Debug, EventReader)]
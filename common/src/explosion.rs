@@ -0,0 +1,94 @@
+use serde::Deserialize;
+use crate::metric_dimension::length::Meter;
+use crate::components::StatusEffectDetails;
+
+/// Describes how the damage decreases from the center of the explosion toward its edge
+#[derive(Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum Falloff {
+    /// Full damage in the whole area
+    Constant,
+    Linear,
+    /// Damage drops fast near the center
+    Quadratic,
+}
+
+impl Default for Falloff {
+    fn default() -> Self {
+        Falloff::Linear
+    }
+}
+
+/// Parameters of an area damage
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub struct ExplosionDetails {
+    /// Entities within this distance from the center take damage
+    pub radius: Meter,
+    /// Damage caused in the center of the explosion
+    pub damage: u16,
+    #[serde(default)]
+    pub falloff: Falloff,
+    /// Whether the one causing the explosion is hurt by it
+    #[serde(default = "default_self_damage")]
+    pub self_damage: bool,
+    /// Applied on the players caught in the explosion
+    #[serde(default)]
+    pub status_effect: Option<StatusEffectDetails>,
+}
+
+fn default_self_damage() -> bool {
+    true
+}
+
+impl ExplosionDetails {
+    /// Damage taken at the given distance from the center. Returns `None` when out of range.
+    pub fn damage_at(&self, distance: Meter) -> Option<u16> {
+        if distance.0 > self.radius.0 {
+            return None;
+        }
+
+        let ratio = if self.radius.0 > 0.0 { distance.0.max(0.0) / self.radius.0 } else { 0.0 };
+        let multiplier = match self.falloff {
+            Falloff::Constant => 1.0,
+            Falloff::Linear => 1.0 - ratio,
+            Falloff::Quadratic => (1.0 - ratio).powi(2),
+        };
+
+        let damage = (self.damage as f32 * multiplier).round() as u16;
+        if damage > 0 { Some(damage) } else { None }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn explosion(falloff: Falloff) -> ExplosionDetails {
+        ExplosionDetails {
+            radius: Meter(2.0),
+            damage: 100,
+            falloff,
+            self_damage: true,
+            status_effect: None,
+        }
+    }
+
+    #[test]
+    fn damage_decreases_with_distance() {
+        let constant = explosion(Falloff::Constant);
+        assert_eq!(constant.damage_at(Meter(0.0)), Some(100));
+        assert_eq!(constant.damage_at(Meter(2.0)), Some(100));
+
+        let linear = explosion(Falloff::Linear);
+        assert_eq!(linear.damage_at(Meter(0.0)), Some(100));
+        assert_eq!(linear.damage_at(Meter(1.0)), Some(50));
+        assert_eq!(linear.damage_at(Meter(2.0)), None, "No damage on the edge");
+
+        let quadratic = explosion(Falloff::Quadratic);
+        assert_eq!(quadratic.damage_at(Meter(1.0)), Some(25));
+    }
+
+    #[test]
+    fn no_damage_out_of_range() {
+        assert_eq!(explosion(Falloff::Constant).damage_at(Meter(2.1)), None);
+    }
+}
@@ -9,9 +9,14 @@ pub mod network;
 pub mod serialization;
 pub mod entities;
 pub mod collision;
+pub mod discovery;
+pub mod explosion;
 pub mod events;
 pub mod utilities;
 pub mod metric_dimension;
+pub mod player_name;
+pub mod replay;
+pub mod visibility;
 
 /// The move direction relative to facing
 #[derive(Copy, Clone, Debug, Serialize, Deserialize)]
@@ -23,7 +28,7 @@ pub enum MoveDirection {
 }
 
 pub use serialization::{serialize, deserialize};
-use std::fmt;
+pub use player_name::PlayerName;
 
 
 #[derive(Deserialize)]
@@ -39,12 +44,3 @@ impl Into<LaminarConfig> for NetworkConfig {
     }
 }
 
-#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
-pub struct PlayerName(pub String);
-
-impl fmt::Display for PlayerName {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", self.0)?;
-        Ok(())
-    }
-}
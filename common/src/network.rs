@@ -1,7 +1,7 @@
 use serde::{Serialize, Deserialize};
 use derive_new::new;
 use std::fmt::{Display, Debug, Formatter};
-use crate::components::{Input, NetworkId, Health};
+use crate::components::{Input, NetworkId, Armor, Health, MaxHealth, Stamina, StatusEffectKind, Team};
 use amethyst::core::math::{Point2, Vector2};
 use crate::resources::Seed;
 use crate::PlayerName;
@@ -12,7 +12,11 @@ use crate::metric_dimension::length::Meter;
 #[cfg_attr(test, derive(Clone, PartialEq))]
 pub enum PacketType {
     ConnectionRequest {
-        player_name: String
+        player_name: String,
+        /// Observers watch the game without playing
+        observer: bool,
+        /// Required by password protected servers
+        password: Option<String>,
     },
     ConnectionResponse(Result<ClientInitialData>),
     InputState {
@@ -24,11 +28,44 @@ pub enum PacketType {
     Notification(PlayerNotification),
     ShotEvent(ShotEvent),
     PlayerDeath(PlayerDeath),
+    Explosion(ExplosionNotification),
+    TeamRoster(Vec<TeamMember>),
+    MatchUpdate(MatchUpdate),
+    MapChange(MapChange),
+    Scoreboard(Vec<PlayerScore>),
+    FlagStatus(Vec<FlagStatus>),
+    SafeZone(SafeZoneUpdate),
+    SpectatorUpdate(SpectatorUpdate),
+    /// Sent by the players, the server forwards it as `Chat`
+    ChatRequest {
+        text: String,
+        /// Only the teammates of the sender receive it
+        team_only: bool,
+    },
+    Chat(ChatMessage),
+    /// The server closed the session of the client
+    Kicked {
+        reason: String,
+    },
 }
 
+/// Longer chat messages are cut by the server
+pub const MAX_CHAT_MESSAGE_LENGTH: usize = 120;
+
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
 pub struct ClientInitialData {
-    pub player_network_id: NetworkId,
+    /// Observers have no character
+    pub player_network_id: Option<NetworkId>,
+    pub map: String,
+    pub seed: Seed,
+    /// Map objects already destroyed when the client joins
+    pub destroyed_objects: Vec<NetworkId>,
+}
+
+/// The server has loaded a map. Everything but the players is removed and the map is built again.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct MapChange {
+    pub map: String,
     pub seed: Seed,
 }
 
@@ -52,6 +89,14 @@ pub struct PlayerNotification {
     pub message: String,
 }
 
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ChatMessage {
+    pub sender: PlayerName,
+    pub text: String,
+    pub team_only: bool,
+}
+
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
 pub struct ShotEvent {
@@ -65,25 +110,149 @@ pub struct ShotEvent {
 pub struct PlayerDeath {
     pub player_name: PlayerName,
     pub position: Point2<Meter>,
+    /// Not set if the player was not killed by someone else, e.g. suicide
+    pub killer: Option<PlayerName>,
+    pub weapon: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct TeamMember {
+    pub network_id: NetworkId,
+    pub team: Team,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct MatchUpdate {
+    pub phase: MatchPhase,
+    /// Remaining time of the current phase, if it is limited
+    pub time_left: Option<Second>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub enum MatchPhase {
+    /// Waiting for players, kills are not counted
+    Warmup {
+        players_needed: u32,
+    },
+    Live,
+    /// Break between two rounds showing the results of the last one
+    Intermission {
+        results: Vec<PlayerScore>,
+        /// Set when the winner is not decided by the scores, e.g. the last survivor
+        winner: Option<PlayerName>,
+    },
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct PlayerScore {
+    pub player_name: PlayerName,
+    pub team: Option<Team>,
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+    /// Enemy flags brought home in capture the flag mode
+    pub captures: u32,
+}
+
+/// Players outside the safe zone are hurt in last man standing mode
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SafeZoneUpdate {
+    pub center: Point2<Meter>,
+    pub radius: Meter,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct SpectatorUpdate {
+    /// The player has no character and watches the others
+    pub spectating: bool,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct FlagStatus {
+    pub network_id: NetworkId,
+    pub team: Team,
+    pub state: FlagState,
+    /// Number of times the team captured the enemy flag in this round
+    pub captures: u32,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub enum FlagState {
+    AtBase,
+    Carried {
+        carrier: PlayerName,
+    },
+    /// Lying on the ground until picked up or returned
+    Dropped,
+}
+
+/// Lets the clients play the effects of an explosion
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ExplosionNotification {
+    pub position: Point2<Meter>,
+    pub radius: Meter,
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 #[cfg_attr(test, derive(PartialEq))]
 pub enum PlayerUpdate {
-    HealthUpdate(Health),
+    HealthUpdate(HealthState),
     AmmoUpdate {
         ammo_in_magazine: u32,
     },
-    WeaponSwitch {
-        name: String,
-        magazine_size: u32,
-        ammo_in_magazine: u32,
-    }
+    InventoryUpdate(InventoryState),
+    StatusEffects(Vec<StatusEffectState>),
+    StaminaUpdate(Stamina),
+}
+
+#[derive(Copy, Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct HealthState {
+    pub health: Health,
+    pub max_health: MaxHealth,
+    pub armor: Armor,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct StatusEffectState {
+    pub kind: StatusEffectKind,
+    pub stacks: u32,
+    pub remaining: Second,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct InventoryState {
+    /// Every inventory slot, `None` means an empty slot
+    pub slots: Vec<Option<WeaponSlot>>,
+    pub selected: usize,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct WeaponSlot {
+    pub name: String,
+    pub magazine_size: u32,
+    pub ammo_in_magazine: u32,
 }
 
 #[derive(Copy, Clone, Serialize, Deserialize, Debug, PartialEq)]
 pub enum ErrorKind {
     AlreadyConnected,
+    ServerIsFull,
+    EmptyName,
+    NameTooLong,
+    InvalidNameCharacter,
+    ReservedName,
+    Banned,
+    PasswordRequired,
+    WrongPassword,
+    NotWhitelisted,
     Other,
 }
 
@@ -91,6 +260,15 @@ impl Display for ErrorKind {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         let literal = match self {
             ErrorKind::AlreadyConnected => "Client already connected",
+            ErrorKind::ServerIsFull => "Server is full",
+            ErrorKind::EmptyName => "Player name is empty",
+            ErrorKind::NameTooLong => "Player name is too long",
+            ErrorKind::InvalidNameCharacter => "Player name may only contain letters, digits, spaces and _-.",
+            ErrorKind::ReservedName => "Player name is reserved",
+            ErrorKind::Banned => "Banned from the server",
+            ErrorKind::PasswordRequired => "The server is password protected",
+            ErrorKind::WrongPassword => "Wrong server password",
+            ErrorKind::NotWhitelisted => "Not on the whitelist of the server",
             ErrorKind::Other => "Other error"
         };
 
@@ -0,0 +1,79 @@
+use serde::{Serialize, Deserialize};
+use std::fmt;
+
+use crate::network::ErrorKind;
+
+pub const MAX_PLAYER_NAME_LENGTH: usize = 20;
+
+/// These could be mistaken for messages of the server
+const RESERVED_PLAYER_NAMES: [&str; 4] = ["server", "admin", "console", "westiny"];
+
+#[derive(Eq, PartialEq, Debug, Clone, Serialize, Deserialize)]
+pub struct PlayerName(pub String);
+
+impl PlayerName {
+    /// Checks the requested name before it is shown to the other players.
+    /// Surrounding whitespace is trimmed.
+    pub fn validate(name: &str) -> Result<PlayerName, ErrorKind> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(ErrorKind::EmptyName);
+        }
+        if name.chars().count() > MAX_PLAYER_NAME_LENGTH {
+            return Err(ErrorKind::NameTooLong);
+        }
+        if !name.chars().all(|c| c.is_alphanumeric() || " _-.".contains(c)) {
+            return Err(ErrorKind::InvalidNameCharacter);
+        }
+        if RESERVED_PLAYER_NAMES.iter().any(|reserved| reserved.eq_ignore_ascii_case(name)) {
+            return Err(ErrorKind::ReservedName);
+        }
+        Ok(PlayerName(name.to_string()))
+    }
+
+    /// The name with a number appended, shortened to fit the length limit
+    pub fn numbered(&self, number: usize) -> PlayerName {
+        let suffix = format!("({})", number);
+        let base: String = self.0.chars()
+            .take(MAX_PLAYER_NAME_LENGTH.saturating_sub(suffix.len()))
+            .collect();
+        PlayerName(format!("{}{}", base.trim_end(), suffix))
+    }
+}
+
+impl fmt::Display for PlayerName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn valid_name_is_trimmed() {
+        assert_eq!(PlayerName::validate("  Clint Westwood "), Ok(PlayerName("Clint Westwood".to_string())));
+        assert_eq!(PlayerName::validate("Narancsos_Feco"), Ok(PlayerName("Narancsos_Feco".to_string())));
+        assert_eq!(PlayerName::validate("Bánát-Jóska.2"), Ok(PlayerName("Bánát-Jóska.2".to_string())));
+    }
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        assert_eq!(PlayerName::validate(" \t"), Err(ErrorKind::EmptyName));
+        assert_eq!(PlayerName::validate(&"a".repeat(MAX_PLAYER_NAME_LENGTH + 1)), Err(ErrorKind::NameTooLong));
+        assert_eq!(PlayerName::validate("Joe\u{7}"), Err(ErrorKind::InvalidNameCharacter));
+        assert_eq!(PlayerName::validate("<Joe>"), Err(ErrorKind::InvalidNameCharacter));
+        assert_eq!(PlayerName::validate("Server"), Err(ErrorKind::ReservedName));
+    }
+
+    #[test]
+    fn numbered_name_fits_the_length_limit() {
+        assert_eq!(PlayerName("Joe".to_string()).numbered(2), PlayerName("Joe(2)".to_string()));
+
+        let numbered = PlayerName("a".repeat(MAX_PLAYER_NAME_LENGTH)).numbered(12);
+        assert_eq!(numbered.0.chars().count(), MAX_PLAYER_NAME_LENGTH);
+        assert!(numbered.0.ends_with("a(12)"));
+    }
+}
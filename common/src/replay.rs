@@ -0,0 +1,237 @@
+use serde::{Serialize, Deserialize};
+use std::fs::File;
+use std::io::{BufWriter, Cursor, Write};
+use std::path::Path;
+use std::time::Duration;
+use anyhow::{anyhow, bail};
+
+use crate::components::NetworkId;
+use crate::discovery::PROTOCOL_VERSION;
+use crate::network::PacketType;
+use crate::resources::Seed;
+
+/// Replay files start with these bytes
+const MAGIC: &[u8] = b"WESTINY-REPLAY";
+
+pub const REPLAY_EXTENSION: &str = "replay";
+
+/// Everything needed to build the map the recording starts on, later maps are loaded by `MapChange` packets
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq)]
+pub struct ReplayHeader {
+    /// Replays are only played by clients of the same protocol version
+    pub protocol_version: u32,
+    pub map: String,
+    pub seed: Seed,
+    /// Map objects already destroyed when the recording started
+    pub destroyed_objects: Vec<NetworkId>,
+}
+
+impl ReplayHeader {
+    pub fn new(map: String, seed: Seed, destroyed_objects: Vec<NetworkId>) -> Self {
+        ReplayHeader {
+            protocol_version: PROTOCOL_VERSION,
+            map,
+            seed,
+            destroyed_objects,
+        }
+    }
+}
+
+/// The packets of a server tick
+#[derive(Debug, Serialize, Deserialize)]
+#[cfg_attr(test, derive(PartialEq))]
+pub struct ReplayFrame {
+    /// Elapsed since the start of the recording
+    pub time: Duration,
+    pub packets: Vec<PacketType>,
+}
+
+/// A frame of borrowed packets, written the same way as a `ReplayFrame`
+#[derive(Serialize)]
+struct BorrowedFrame<'a> {
+    time: Duration,
+    packets: &'a [&'a PacketType],
+}
+
+/// A recorded match, the header is followed by the frames in the file
+#[derive(Debug)]
+pub struct Replay {
+    pub header: ReplayHeader,
+    pub frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        let content = std::fs::read(path)?;
+        Replay::read(&content)
+    }
+
+    /// A truncated last frame is dropped, so the recordings of crashed servers can be played too
+    pub fn read(content: &[u8]) -> anyhow::Result<Self> {
+        if !content.starts_with(MAGIC) {
+            bail!("Not a replay file");
+        }
+        let mut cursor = Cursor::new(&content[MAGIC.len()..]);
+        let header: ReplayHeader = rmp_serde::from_read(&mut cursor)
+            .map_err(|err| anyhow!("Invalid replay header: {}", err))?;
+        if header.protocol_version != PROTOCOL_VERSION {
+            bail!("The replay was recorded with protocol version {}, this client plays version {}",
+                  header.protocol_version,
+                  PROTOCOL_VERSION);
+        }
+
+        let mut frames = Vec::new();
+        while (cursor.position() as usize) < cursor.get_ref().len() {
+            match rmp_serde::from_read::<_, ReplayFrame>(&mut cursor) {
+                Ok(frame) => frames.push(frame),
+                Err(err) => {
+                    log::warn!("Replay is cut after {} frames: {}", frames.len(), err);
+                    break;
+                }
+            }
+        }
+        Ok(Replay { header, frames })
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.frames.last().map_or(Duration::default(), |frame| frame.time)
+    }
+}
+
+/// Tells whether a packet sent to the clients is worth recording.
+/// Personal packets (connection, HUD, spectating) are left out, and so is the entity state
+/// sent to a single player, because the recorder saves the state of every entity instead.
+pub fn is_recorded(packet: &PacketType) -> bool {
+    match packet {
+        PacketType::EntityDelete(_)
+        | PacketType::Notification(_)
+        | PacketType::ShotEvent(_)
+        | PacketType::PlayerDeath(_)
+        | PacketType::Explosion(_)
+        | PacketType::TeamRoster(_)
+        | PacketType::MatchUpdate(_)
+        | PacketType::Scoreboard(_)
+        | PacketType::FlagStatus(_)
+        | PacketType::SafeZone(_)
+        | PacketType::MapChange(_) => true,
+        PacketType::Chat(message) => !message.team_only,
+        _ => false,
+    }
+}
+
+/// Packets describing a lasting state rather than a moment. When seeking in a replay
+/// only the latest of these are played, the skipped moments (shots, deaths, ...) are not.
+pub fn is_lasting_state(packet: &PacketType) -> bool {
+    matches!(packet,
+        PacketType::EntityStateUpdate(_)
+        | PacketType::TeamRoster(_)
+        | PacketType::MatchUpdate(_)
+        | PacketType::Scoreboard(_)
+        | PacketType::FlagStatus(_)
+        | PacketType::SafeZone(_))
+}
+
+/// Writes the replay file frame by frame while the match goes on
+pub struct ReplayWriter<W: Write> {
+    out: W,
+}
+
+impl ReplayWriter<BufWriter<File>> {
+    pub fn create(path: &Path, header: &ReplayHeader) -> anyhow::Result<Self> {
+        ReplayWriter::new(BufWriter::new(File::create(path)?), header)
+    }
+}
+
+impl<W: Write> ReplayWriter<W> {
+    pub fn new(mut out: W, header: &ReplayHeader) -> anyhow::Result<Self> {
+        out.write_all(MAGIC)?;
+        rmp_serde::encode::write(&mut out, header)?;
+        Ok(ReplayWriter { out })
+    }
+
+    pub fn write_frame(&mut self, frame: &ReplayFrame) -> anyhow::Result<()> {
+        let packets: Vec<_> = frame.packets.iter().collect();
+        self.write_packets(frame.time, &packets)
+    }
+
+    /// Writes a frame of the packets without taking them, they are read back as a `ReplayFrame`
+    pub fn write_packets(&mut self, time: Duration, packets: &[&PacketType]) -> anyhow::Result<()> {
+        rmp_serde::encode::write(&mut self.out, &BorrowedFrame { time, packets })?;
+        Ok(())
+    }
+
+    pub fn flush(&mut self) -> anyhow::Result<()> {
+        self.out.flush()?;
+        Ok(())
+    }
+
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::network::{ChatMessage, PlayerNotification};
+    use crate::PlayerName;
+
+    fn header() -> ReplayHeader {
+        ReplayHeader::new("rust2".to_string(), Seed(42), vec![])
+    }
+
+    fn notification(message: &str) -> PacketType {
+        PacketType::Notification(PlayerNotification { message: message.to_string() })
+    }
+
+    fn frame(millis: u64, packets: Vec<PacketType>) -> ReplayFrame {
+        ReplayFrame { time: Duration::from_millis(millis), packets }
+    }
+
+    fn recording(frames: &[ReplayFrame]) -> Vec<u8> {
+        let mut writer = ReplayWriter::new(Vec::new(), &header()).unwrap();
+        for frame in frames {
+            writer.write_frame(frame).unwrap();
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn replay_survives_recording() {
+        let frames = vec![
+            frame(0, vec![PacketType::EntityStateUpdate(vec![])]),
+            frame(16, vec![notification("Clint joined"), PacketType::EntityStateUpdate(vec![])]),
+        ];
+        let replay = Replay::read(&recording(&frames)).unwrap();
+
+        assert_eq!(replay.header, header());
+        assert_eq!(replay.frames, frames);
+        assert_eq!(replay.duration(), Duration::from_millis(16));
+    }
+
+    #[test]
+    fn truncated_frame_is_dropped() {
+        let mut content = recording(&[frame(0, vec![notification("first")]), frame(16, vec![notification("second")])]);
+        content.truncate(content.len() - 3);
+
+        let replay = Replay::read(&content).unwrap();
+        assert_eq!(replay.frames, vec![frame(0, vec![notification("first")])]);
+    }
+
+    #[test]
+    fn other_files_are_refused() {
+        assert!(Replay::read(b"(name: \"Clint\")").is_err());
+
+        let mut content = MAGIC.to_vec();
+        rmp_serde::encode::write(&mut content, &ReplayHeader { protocol_version: PROTOCOL_VERSION + 1, ..header() }).unwrap();
+        assert!(Replay::read(&content).is_err());
+    }
+
+    #[test]
+    fn team_chat_is_not_recorded() {
+        let chat = |team_only| PacketType::Chat(ChatMessage { sender: PlayerName("Clint".to_string()), text: "go".to_string(), team_only });
+        assert!(is_recorded(&chat(false)));
+        assert!(!is_recorded(&chat(true)));
+        assert!(!is_recorded(&PacketType::Kicked { reason: "Bye".to_string() }));
+    }
+}
@@ -7,29 +7,74 @@ use std::io::{BufReader, Read};
 use crate::resources::map::MapError::{InvalidMapCharacter, SeedError};
 use crate::resources::{SpriteId, Seed};
 use amethyst::core::math::Point2;
+use serde::Deserialize;
+use crate::components::{NetworkId, EntityType, StatusEffectDetails, Team};
+use crate::metric_dimension::length::Meter;
+use crate::explosion::ExplosionDetails;
+use crate::metric_dimension::Second;
+use crate::utilities::read_ron;
 
 const BARREL_CHAR: char = 'x';
+const EXPLOSIVE_BARREL_CHAR: char = 'o';
 const EMPTY_CHAR: char = ' ';
 
 const MAP_OFFSET: (i32, i32) = (-32, -32);
 
+/// The map played if the server config does not name any
+pub const MAP_NAME: &str = "rust2";
+
+/// The maps having both a layout and a metadata file in the directory, sorted by name
+pub fn available_maps(map_files_dir: &Path) -> std::io::Result<Vec<String>> {
+    let mut maps = Vec::new();
+    for entry in std::fs::read_dir(map_files_dir)? {
+        let path = entry?.path();
+        if path.extension().map_or(false, |ext| ext == "wmap") && path.with_extension("ron").is_file() {
+            if let Some(name) = path.file_stem().and_then(|stem| stem.to_str()) {
+                maps.push(name.to_string());
+            }
+        }
+    }
+    maps.sort();
+    Ok(maps)
+}
+
+/// Maps are not generated yet, the only buildable seed selects the shipped map
+pub fn is_buildable(seed: Seed) -> bool {
+    seed.0 == 0
+}
+
 pub fn build_map(world: &mut World,
+                 map_name: &str,
                  seed: Seed,
                  map_files_dir: &Path) -> Result<Vec<(Entity, SpriteId)>, MapError> {
     let mut entity_vec = Vec::new();
-    if seed.0 == 0 {
-        let map_reader = BufReader::new(File::open(map_files_dir.join("rust2.wmap"))?);
+    if is_buildable(seed) {
+        let map_reader = BufReader::new(File::open(map_files_dir.join(format!("{}.wmap", map_name)))?);
         let map_bytes = map_reader.bytes();
 
         let mut x = 0;
         let mut y = 0;
+        // Map objects are numbered in the order of appearance, so the network ids
+        // are the same on every side building the same map.
+        let mut barrel_count = 0;
+        let mut explosive_barrel_count = 0;
         for byte in map_bytes {
             match byte? as char {
                 BARREL_CHAR => {
                     // spawn a barrel
                     let pos = Point2::new(x + MAP_OFFSET.0, -(y + MAP_OFFSET.1));
-                    let barrel = crate::entities::place_barrel(world, pos);
+                    let network_id = NetworkId::new(EntityType::Barrel, barrel_count);
+                    let barrel = crate::entities::place_barrel(world, pos, network_id);
+                    entity_vec.push((barrel, SpriteId::Barrel));
+                    barrel_count += 1;
+                    x += 1;
+                },
+                EXPLOSIVE_BARREL_CHAR => {
+                    let pos = Point2::new(x + MAP_OFFSET.0, -(y + MAP_OFFSET.1));
+                    let network_id = NetworkId::new(EntityType::ExplosiveBarrel, explosive_barrel_count);
+                    let barrel = crate::entities::place_barrel(world, pos, network_id);
                     entity_vec.push((barrel, SpriteId::Barrel));
+                    explosive_barrel_count += 1;
                     x += 1;
                 },
                 EMPTY_CHAR => {
@@ -52,11 +97,107 @@ pub fn build_map(world: &mut World,
     }
 }
 
+/// Map related settings stored beside the map file
+#[derive(Deserialize, Clone, Debug, Default)]
+pub struct MapMetadata {
+    /// Barrels are indestructible if not set
+    #[serde(default)]
+    pub barrel: Option<DestructibleDetails>,
+    /// Explosive barrels are indestructible if not set
+    #[serde(default)]
+    pub explosive_barrel: Option<DestructibleDetails>,
+    /// Players are spawned inside these areas. Anywhere on the map if there is no zone for the team.
+    #[serde(default)]
+    pub spawn_zones: Vec<SpawnZone>,
+    /// Flags of the teams are placed here in capture the flag mode
+    #[serde(default)]
+    pub flag_bases: Vec<FlagBase>,
+    /// Medkits, armor and status effect pickups are placed here and respawned some time after they are picked up
+    #[serde(default)]
+    pub item_spawns: Vec<ItemSpawn>,
+}
+
+impl MapMetadata {
+    pub fn load(map_files_dir: &Path, map_name: &str, seed: Seed) -> Result<Self, MapError> {
+        if is_buildable(seed) {
+            let metadata: MapMetadata = read_ron(&map_files_dir.join(format!("{}.ron", map_name))).map_err(MapError::MetadataError)?;
+            metadata.validate().map_err(MapError::MetadataError)?;
+            Ok(metadata)
+        } else {
+            Err(SeedError(seed))
+        }
+    }
+
+    fn validate(&self) -> anyhow::Result<()> {
+        for zone in self.spawn_zones.iter() {
+            if !(zone.radius.0 > 0.0) {
+                anyhow::bail!("the spawn zone at ({}, {}) has radius {}, it must be positive", zone.x.0, zone.y.0, zone.radius.0);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct DestructibleDetails {
+    pub health: u16,
+    /// The object is placed back after this time. Destroyed objects stay destroyed if not set.
+    #[serde(default)]
+    pub respawn_time: Option<Second>,
+    /// Area damage caused when the object is destroyed
+    #[serde(default)]
+    pub explosion: Option<ExplosionDetails>,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct SpawnZone {
+    /// The zone is used by every team if not set
+    #[serde(default)]
+    pub team: Option<Team>,
+    pub x: Meter,
+    pub y: Meter,
+    pub radius: Meter,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct FlagBase {
+    pub team: Team,
+    pub x: Meter,
+    pub y: Meter,
+}
+
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+pub enum Item {
+    /// Heals this much health
+    Medkit(u16),
+    /// Gives this much armor
+    Armor(u16),
+    /// Applies the effect on the player, e.g. a speed boost
+    StatusEffect(StatusEffectDetails),
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct ItemSpawn {
+    pub item: Item,
+    pub x: Meter,
+    pub y: Meter,
+    pub respawn_time: Second,
+}
+
+impl MapMetadata {
+    pub fn spawn_zones_of(&self, team: Option<Team>) -> Vec<&SpawnZone> {
+        self.spawn_zones.iter()
+            .filter(|zone| zone.team.is_none() || zone.team == team)
+            .collect()
+    }
+}
+
 #[derive(Debug)]
 pub enum MapError {
     InvalidMapCharacter(char, i32, i32),
     MapFileError(std::io::Error),
     SeedError(Seed),
+    MetadataError(anyhow::Error),
 }
 
 impl Display for MapError {
@@ -65,6 +206,7 @@ impl Display for MapError {
             Self::InvalidMapCharacter(ch, x, y) => format!("Could not process char ({}) at ({}, {})", ch, x, y),
             Self::MapFileError(inner) => format!("File IO error: {}", inner),
             Self::SeedError(seed) => format!("Could not handle seed: {}", seed),
+            Self::MetadataError(inner) => format!("Could not read map metadata: {}", inner),
         };
         write!(f, "{}", literal)
     }
@@ -76,4 +218,29 @@ impl From<std::io::Error> for MapError {
     fn from(err: std::io::Error) -> Self {
         MapError::MapFileError(err)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::utils::application_root_dir;
+
+    #[test]
+    fn the_default_map_is_available() {
+        let map_dir = application_root_dir().unwrap().join("../resources/map");
+        let maps = available_maps(&map_dir).unwrap();
+
+        assert!(maps.iter().any(|map| map == MAP_NAME));
+    }
+
+    #[test]
+    fn spawn_zones_need_a_positive_radius() {
+        let zone = |radius| SpawnZone { team: None, x: Meter(1.0), y: Meter(2.0), radius: Meter(radius) };
+        let metadata = |radius| MapMetadata { spawn_zones: vec![zone(5.0), zone(radius)], ..MapMetadata::default() };
+
+        assert!(metadata(3.0).validate().is_ok());
+        assert!(metadata(0.0).validate().is_err());
+        assert!(metadata(-1.0).validate().is_err());
+        assert!(metadata(std::f32::NAN).validate().is_err());
+    }
+}
@@ -0,0 +1,33 @@
+use amethyst::core::ecs::World;
+use serde::Deserialize;
+use std::path::Path;
+use crate::utilities::read_ron;
+use crate::metric_dimension::{MeterPerSec, Second};
+use crate::metric_dimension::length::Meter;
+
+const MELEE_ASSET: &'static str = "assets/melee.ron";
+
+/// Tuning of the melee attack shared by all players
+#[derive(Deserialize, Clone, Debug)]
+pub struct MeleeDetails {
+    /// Damage caused by a single hit
+    pub damage: u16,
+    /// Distance from the attacker's center within the targets are hit
+    pub reach: Meter,
+    /// Width of the hit area in front of the attacker [degree]
+    pub arc: f32,
+    /// Minimum time between two attacks
+    pub cooldown: Second,
+    /// Speed the targets are pushed away with
+    pub knockback_speed: MeterPerSec,
+    /// For how long the targets are pushed
+    pub knockback_duration: Second,
+}
+
+impl MeleeDetails {
+    pub fn initialize<P: AsRef<Path>>(world: &mut World, resources_path: P) -> anyhow::Result<()> {
+        let details: MeleeDetails = read_ron(&resources_path.as_ref().join(MELEE_ASSET))?;
+        world.insert(details);
+        Ok(())
+    }
+}
@@ -1,11 +1,13 @@
 pub use audio::{SoundId, AudioQueue};
 pub use cursor_pos::CursorPosition;
+pub use melee::MeleeDetails;
 
 mod audio;
 mod cursor_pos;
 pub mod map;
 pub mod collision;
 pub mod weapon;
+mod melee;
 
 use std::fmt::{Display, Formatter};
 use std::net::SocketAddr;
@@ -32,6 +34,7 @@ pub enum SpriteId {
     Corpse = 4,
     Bullet = 5,
     HandWithPistol = 6,
+    Flag = 7,
 }
 
 #[derive(Copy, Clone, Serialize, Deserialize, PartialEq, Debug)]
@@ -4,7 +4,7 @@ use std::path::Path;
 use crate::utilities::read_ron;
 
 pub struct GunResource {
-    weapons: [WeaponDetails; 3],
+    weapons: [WeaponDetails; 5],
 }
 
 #[derive(Copy, Clone, PartialEq, Eq)]
@@ -13,6 +13,8 @@ pub enum GunId {
     Revolver = 0,
     Shotgun,
     Rifle,
+    RocketLauncher,
+    Grenade,
 }
 
 const WEAPON_ASSET_DIR: &'static str = "assets/weapons/";
@@ -24,9 +26,11 @@ impl GunResource {
         let revolver: WeaponDetails = read_ron(&path.join("revolver.ron"))?;
         let shotgun: WeaponDetails = read_ron(&path.join("shotgun.ron"))?;
         let rifle: WeaponDetails = read_ron(&path.join("rifle.ron"))?;
+        let rocket_launcher: WeaponDetails = read_ron(&path.join("rocket_launcher.ron"))?;
+        let grenade: WeaponDetails = read_ron(&path.join("grenade.ron"))?;
         // other weapons here
 
-        world.insert(GunResource { weapons: [revolver, shotgun, rifle]});
+        world.insert(GunResource { weapons: [revolver, shotgun, rifle, rocket_launcher, grenade]});
         Ok(())
     }
 
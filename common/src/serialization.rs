@@ -31,9 +31,10 @@ mod tests {
 
     fn packet_enum_strategy() -> impl Strategy<Value = PacketType> {
         prop_oneof![
-            any::<String>().prop_map(|name| PacketType::ConnectionRequest { player_name: name }),
+            (any::<String>(), any::<bool>(), any::<Option<String>>()).prop_map(|(name, observer, password)| PacketType::ConnectionRequest { player_name: name, observer, password }),
             input_state_gen(),
-            entity_state_update_gen()
+            entity_state_update_gen(),
+            (any::<String>(), any::<bool>()).prop_map(|(text, team_only)| PacketType::ChatRequest { text, team_only }),
         ]
     }
 
@@ -72,6 +73,14 @@ mod tests {
     fn entity_type_strategy() -> impl Strategy<Value = EntityType> {
         prop_oneof![
             Just(EntityType::Player),
+            Just(EntityType::WeaponPickup),
+            Just(EntityType::Barrel),
+            Just(EntityType::ExplosiveBarrel),
+            Just(EntityType::Grenade),
+            Just(EntityType::Flag),
+            Just(EntityType::Medkit),
+            Just(EntityType::ArmorPickup),
+            Just(EntityType::EffectPickup),
         ]
     }
 
@@ -88,5 +97,25 @@ mod tests {
             println!("{:?}", &packet);
             assert_eq!(packet, deserialize(&serialize(&packet).unwrap()).unwrap());
         }
+
+        #[test]
+        fn random_bytes_are_refused_without_panic(bytes in proptest::collection::vec(any::<u8>(), 0..2048)) {
+            let _ = deserialize(&bytes);
+        }
+
+        #[test]
+        fn truncated_packets_are_refused(packet in packet_enum_strategy(), cut in any::<prop::sample::Index>()) {
+            let bytes = serialize(&packet).unwrap();
+            let truncated = &bytes[..cut.index(bytes.len())];
+            assert!(deserialize(truncated).is_err());
+        }
+
+        #[test]
+        fn corrupted_packets_do_not_panic(packet in packet_enum_strategy(), position in any::<prop::sample::Index>(), value in any::<u8>()) {
+            let mut bytes = serialize(&packet).unwrap();
+            let index = position.index(bytes.len());
+            bytes[index] = value;
+            let _ = deserialize(&bytes);
+        }
     }
 }
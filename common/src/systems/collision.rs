@@ -4,9 +4,11 @@ use amethyst::ecs::prelude::Join;
 use amethyst::shrev::EventChannel;
 
 use crate::collision::{Collider, check_body_collision, check_projectile_collision};
-use crate::components::{Velocity, BoundingCircle, Projectile, Damage, Health};
+use crate::components::{Velocity, BoundingCircle, Projectile, Damage, Health, Explosive, Owner, WeaponName, OnHitEffect};
 use crate::resources::collision::{Collision, Collisions, ProjectileCollision, ProjectileCollisions};
-use crate::events::{EntityDelete, DamageEvent};
+use crate::events::{EntityDelete, DamageEvent, ExplosionEvent, StatusEffectEvent};
+use crate::metric_dimension::to_meter_vec;
+use amethyst::core::math::Point2;
 use amethyst::core::ecs::{World, DispatcherBuilder};
 
 pub struct CollisionBundle;
@@ -124,19 +126,53 @@ impl<'s> System<'s> for ProjectileCollisionHandler {
         ReadExpect<'s, ProjectileCollisions>,
         Write<'s, EventChannel<EntityDelete>>,
         Write<'s, EventChannel<DamageEvent>>,
+        Write<'s, EventChannel<ExplosionEvent>>,
+        Write<'s, EventChannel<StatusEffectEvent>>,
         ReadStorage<'s, Health>,
         ReadStorage<'s, Damage>,
+        ReadStorage<'s, Explosive>,
+        ReadStorage<'s, Owner>,
+        ReadStorage<'s, WeaponName>,
+        ReadStorage<'s, OnHitEffect>,
+        ReadStorage<'s, Transform>,
         );
 
     // Here Projectile components are not explicitly filtered. ProjectCollisionSystem is expected
     // to put proper entities in `collision.projectile`
-    fn run(&mut self, (collisions, mut entity_delete_channel, mut damage_event, healths, damages): Self::SystemData) {
+    fn run(&mut self, (collisions, mut entity_delete_channel, mut damage_event, mut explosion_event, mut status_effect_event, healths, damages, explosives, owners, weapon_names, on_hit_effects, transforms): Self::SystemData) {
+        // A projectile touching more objects at once must explode only once
+        let mut exploded = Vec::new();
 
         for collision in &collisions.0 {
-            if healths.contains(collision.target) {
+            // Explosives deal area damage instead of hitting the target directly
+            if let (Some(explosive), Some(transform)) = (explosives.get(collision.projectile), transforms.get(collision.projectile)) {
+                if exploded.contains(&collision.projectile) {
+                    continue;
+                }
+                exploded.push(collision.projectile);
+                explosion_event.single_write(ExplosionEvent {
+                    position: Point2 { coords: to_meter_vec(transform.translation().xy()) },
+                    details: explosive.0.clone(),
+                    owner: owners.get(collision.projectile).map(|owner| owner.0),
+                    weapon: weapon_names.get(collision.projectile).map(|name| name.0.clone()),
+                });
+            } else if healths.contains(collision.target) {
                 if let Some(damage) = damages.get(collision.projectile) {
-                    damage_event.single_write(DamageEvent { damage: *damage, target: collision.target })
-            }}
+                    damage_event.single_write(DamageEvent {
+                        damage: *damage,
+                        target: collision.target,
+                        attacker: owners.get(collision.projectile).map(|owner| owner.0),
+                        weapon: weapon_names.get(collision.projectile).map(|name| name.0.clone()),
+                    })
+                }
+                if let Some(on_hit_effect) = on_hit_effects.get(collision.projectile) {
+                    status_effect_event.single_write(StatusEffectEvent {
+                        target: collision.target,
+                        effect: on_hit_effect.0.clone(),
+                        source: owners.get(collision.projectile).map(|owner| owner.0),
+                    })
+                }
+            }
 
             entity_delete_channel.single_write(EntityDelete{entity_id: collision.projectile})
         }
@@ -0,0 +1,110 @@
+use amethyst::core::math::Point2;
+use std::collections::HashSet;
+use crate::metric_dimension::length::Meter;
+
+/// The cells of the map which block the line of sight. Map objects are placed in the middle of
+/// one meter wide cells, so a cell is blocked when there is an obstacle at its center.
+#[derive(Clone, Debug, Default)]
+pub struct OcclusionMap {
+    blocked: HashSet<(i32, i32)>,
+}
+
+impl OcclusionMap {
+    pub fn new<I: IntoIterator<Item = Point2<Meter>>>(obstacles: I) -> Self {
+        OcclusionMap {
+            blocked: obstacles.into_iter().map(|position| Self::cell_of(&position)).collect(),
+        }
+    }
+
+    pub fn cell_of(position: &Point2<Meter>) -> (i32, i32) {
+        ((position.x.0 + 0.5).floor() as i32, (position.y.0 + 0.5).floor() as i32)
+    }
+
+    pub fn is_blocked(&self, cell: (i32, i32)) -> bool {
+        self.blocked.contains(&cell)
+    }
+
+    pub fn len(&self) -> usize {
+        self.blocked.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.blocked.is_empty()
+    }
+
+    /// Whether nothing blocks the straight line between the two positions.
+    /// The cells of the endpoints do not block, so obstacles themselves are visible.
+    pub fn is_visible(&self, from: &Point2<Meter>, to: &Point2<Meter>) -> bool {
+        // Cells are shifted to span [n, n + 1), so the cell of a coordinate is its floor
+        let start = (from.x.0 + 0.5, from.y.0 + 0.5);
+        let end = (to.x.0 + 0.5, to.y.0 + 0.5);
+        let mut cell = Self::cell_of(from);
+        let target = Self::cell_of(to);
+
+        let axis = |start: f32, end: f32, cell: i32| -> (i32, f32, f32) {
+            let direction = end - start;
+            if direction > 0.0 {
+                (1, (cell as f32 + 1.0 - start) / direction, 1.0 / direction)
+            } else if direction < 0.0 {
+                (-1, (start - cell as f32) / -direction, 1.0 / -direction)
+            } else {
+                (0, f32::INFINITY, f32::INFINITY)
+            }
+        };
+        let (step_x, mut next_x, delta_x) = axis(start.0, end.0, cell.0);
+        let (step_y, mut next_y, delta_y) = axis(start.1, end.1, cell.1);
+
+        // Every step moves one cell closer to the target on one of the axes
+        let steps = (target.0 - cell.0).abs() + (target.1 - cell.1).abs();
+        for _ in 0..steps {
+            if next_x < next_y {
+                cell.0 += step_x;
+                next_x += delta_x;
+            } else {
+                cell.1 += step_y;
+                next_y += delta_y;
+            }
+
+            if cell != target && self.is_blocked(cell) {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn point(x: f32, y: f32) -> Point2<Meter> {
+        Point2::new(Meter(x), Meter(y))
+    }
+
+    fn wall() -> OcclusionMap {
+        OcclusionMap::new((-2..=2).map(|y| point(0.0, y as f32)))
+    }
+
+    #[test]
+    fn wall_blocks_the_sight() {
+        let map = wall();
+        assert!(!map.is_visible(&point(-3.0, 0.0), &point(3.0, 0.0)));
+        assert!(!map.is_visible(&point(-3.0, -1.2), &point(3.0, 1.7)));
+        assert!(!map.is_visible(&point(3.0, 0.5), &point(-3.0, -0.5)));
+    }
+
+    #[test]
+    fn sight_goes_around_the_wall() {
+        let map = wall();
+        assert!(map.is_visible(&point(-3.0, 4.0), &point(3.0, 4.0)));
+        assert!(map.is_visible(&point(-3.0, 0.0), &point(-3.0, 10.0)));
+        assert!(map.is_visible(&point(-1.0, -10.0), &point(-1.0, 10.0)));
+    }
+
+    #[test]
+    fn obstacles_themselves_are_visible() {
+        let map = wall();
+        assert!(map.is_visible(&point(-3.0, 0.0), &point(0.0, 0.0)));
+        assert!(map.is_visible(&point(0.0, 2.0), &point(5.0, 2.0)));
+    }
+}
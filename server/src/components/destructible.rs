@@ -0,0 +1,13 @@
+use westiny_common::resources::map::DestructibleDetails;
+use derive_new::new;
+use amethyst::core::ecs::{Component, DenseVecStorage};
+
+/// Map object which is removed when its health is drained
+#[derive(new, Clone, Debug)]
+pub struct Destructible {
+    pub details: DestructibleDetails,
+}
+
+impl Component for Destructible {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -0,0 +1,36 @@
+use amethyst::core::ecs::{Component, DenseVecStorage, Entity};
+use amethyst::core::math::Vector2;
+use std::time::Duration;
+use westiny_common::components::Team;
+use westiny_common::metric_dimension::length::Meter;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FlagLocation {
+    AtBase,
+    Carried(Entity),
+    Dropped {
+        return_at: Duration,
+    },
+}
+
+/// Objective of the capture the flag mode
+#[derive(Clone, Debug)]
+pub struct Flag {
+    pub team: Team,
+    pub base: Vector2<Meter>,
+    pub location: FlagLocation,
+}
+
+impl Flag {
+    pub fn new(team: Team, base: Vector2<Meter>) -> Self {
+        Flag {
+            team,
+            base,
+            location: FlagLocation::AtBase,
+        }
+    }
+}
+
+impl Component for Flag {
+    type Storage = DenseVecStorage<Self>;
+}
@@ -1,4 +1,10 @@
 pub(crate) use westiny_common::components::*;
 pub(crate) use client::Client;
+pub(crate) use destructible::Destructible;
+pub(crate) use flag::{Flag, FlagLocation};
+pub(crate) use spectator::Spectator;
 
-mod client;
\ No newline at end of file
+mod client;
+mod destructible;
+mod flag;
+mod spectator;
@@ -0,0 +1,10 @@
+use amethyst::core::ecs::{Component, NullStorage};
+
+/// Client without a character, e.g. eliminated in last man standing mode.
+/// Its entity keeps the client's network id until it is spawned again.
+#[derive(Default, Copy, Clone, Debug)]
+pub struct Spectator;
+
+impl Component for Spectator {
+    type Storage = NullStorage<Self>;
+}
@@ -6,12 +6,13 @@ use std::str::FromStr;
 use std::time::Duration;
 use amethyst::core::frame_limiter::FrameRateLimitStrategy;
 use westiny_common::{
-    resources::ServerAddress,
+    discovery::{DiscoveryResponder, DISCOVERY_PORT},
     events::{WestinyEvent, WestinyEventReader},
     utilities::read_ron,
     NetworkConfig,
 };
 use crate::systems::CollisionBundle;
+use crate::resources::{BanList, ServerConfig, SERVER_USAGE};
 
 pub mod resources;
 pub mod systems;
@@ -26,21 +27,23 @@ fn main() -> amethyst::Result<()> {
     let app_root = application_root_dir()?;
     let resources_dir = app_root.join(RESOURCES_RELATIVE_PATH);
 
-    let server_port: u16 = {
-        let ron_path = resources_dir.join("server_network.ron");
-        read_ron::<ServerAddress>(&ron_path)
-            .map(|addr| addr.address.port())
-            .unwrap_or_else(|err| {
-                let srv_port = ServerAddress::default().address.port();
-                log::warn!("Failed to read server network configuration file: {}, error: [{}] \
-                Using default server port ({})",
-                           ron_path.as_os_str().to_str().unwrap(),
-                           err,
-                           srv_port);
-                srv_port
-            })
-    };
-    let socket_address = SocketAddr::new(IpAddr::from_str("0.0.0.0").unwrap(), server_port);
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.iter().any(|arg| arg == "--help" || arg == "-h") {
+        println!("{}", SERVER_USAGE);
+        return Ok(());
+    }
+    let config = ServerConfig::from_args(&resources_dir, args).map_err(|err| {
+        log::error!("{}", err);
+        err
+    })?;
+    // Starting with a broken ban list would let the banned players in
+    let ban_file = resources_dir.join(&config.ban_file);
+    let ban_list = BanList::load(ban_file.clone()).map_err(|err| {
+        let message = format!("The ban list {} could not be loaded, fix or remove it: {:#}", ban_file.display(), err);
+        log::error!("{}", message);
+        amethyst::Error::from_string(message)
+    })?;
+    let socket_address = config.bind_address;
     log::info!("Start listening on {}", socket_address);
 
     let laminar_config= {
@@ -52,30 +55,67 @@ fn main() -> amethyst::Result<()> {
 
     let socket = LaminarSocket::bind_with_config(socket_address, laminar_config)?;
 
-    let game_data = GameDataBuilder::default()
+    let mut game_data = GameDataBuilder::default()
         .with_bundle(LaminarNetworkBundle::new(Some(socket)))?
         .with(systems::EntityStateBroadcasterSystem, "entity_state_broadcaster", &[])
         .with_system_desc(systems::NetworkMessageReceiverSystemDesc::default(), "msg_receiver", &[])
         .with_system_desc(systems::ClientIntroductionSystemDesc::default(), "client_intro", &["msg_receiver"])
         .with_system_desc(systems::CommandTransformerSystemDesc::default(), "command_transformer", &["msg_receiver"])
+        .with_system_desc(systems::ChatSystemDesc::default(), "chat", &["msg_receiver"])
+        .with(systems::AdminConsoleSystem::new(config.admin_port), "admin_console", &[])
+        .with_system_desc(systems::AdminCommandSystemDesc::default(), "admin_command", &["admin_console", "msg_receiver"])
+        .with_system_desc(systems::AntiCheatSystemDesc::default(), "anti_cheat", &["command_transformer"])
         .with(systems::PlayerMovementSystem, "player_movement", &["command_transformer"])
+        .with(systems::StaminaSystem::default(), "stamina", &["player_movement"])
         .with(systems::PhysicsSystem, "physics", &["player_movement"])
         .with_bundle(CollisionBundle)?
         .with(systems::LifespanSystem, "timing", &["collision"])
+        .with(systems::GrenadeSystem, "grenade", &["collision_handler"])
         .with(systems::ShooterSystem, "shooter", &["command_transformer"])
-        .with_system_desc(systems::HealthSystemDesc::default(), "health", &["projectile_collision_handler"])
-        .with(systems::DeathSystem, "death", &["health"])
+        .with(systems::PickupSystem::default(), "pickup", &["command_transformer"])
+        .with(systems::ItemSpawnSystem::default(), "item_spawn", &["pickup"])
+        .with(systems::MeleeSystem, "melee", &["command_transformer"])
+        .with_system_desc(systems::StatusEffectSystemDesc::default(), "status_effect", &["projectile_collision_handler", "pickup"])
+        .with_system_desc(systems::HealthSystemDesc::default(), "health", &["projectile_collision_handler", "melee", "status_effect"])
+        .with_system_desc(systems::DeathSystemDesc::default(), "death", &["health"])
+        .with(systems::DestructionSystem, "destruction", &["health"])
+        .with_system_desc(systems::ExplosionSystemDesc::default(), "explosion", &["destruction"])
+        .with(systems::ObjectRespawnSystem, "object_respawn", &["destruction"])
         .with(systems::RespawnSystem, "respawn", &["death"])
-        .with_system_desc(systems::SpawnSystemDesc::default(), "spawn", &["client_intro", "respawn"])
+        .with_system_desc(systems::ScoreSystemDesc::default(), "score", &["health"])
+        .with(systems::MatchSystem::default(), "match_control", &["score", "respawn"])
+        .with(systems::FlagSystem::default(), "flag", &["death", "match_control"])
+        .with(systems::SafeZoneSystem::default(), "safe_zone", &["match_control"])
+        .with_system_desc(systems::SpawnSystemDesc::default(), "spawn", &["client_intro", "respawn", "match_control"])
         .with_system_desc(systems::EntityDeleteBroadcasterSystemDesc::default(), "delete_broadcaster", &["collision_handler"])
         ;
 
-    let frame_limit = 60;
+    // The server is playable without being discoverable on the LAN
+    if config.lan_discovery {
+        let discovery_address = SocketAddr::new(IpAddr::from_str("0.0.0.0").unwrap(), DISCOVERY_PORT);
+        match DiscoveryResponder::bind(discovery_address) {
+            Ok(responder) => {
+                log::info!("Answering LAN discovery queries on {}", discovery_address);
+                game_data = game_data.with(systems::DiscoverySystem::new(responder, &config), "discovery", &[]);
+            }
+            Err(err) => log::warn!("LAN discovery is disabled, {} could not be bound: {}", discovery_address, err),
+        }
+    }
+
+    // The recorder runs after every system, so it sees all the packets broadcast in the tick
+    if let Some(replay_dir) = &config.replay_dir {
+        match systems::ReplayRecorderSystemDesc::create(&resources_dir.join(replay_dir), &config) {
+            Ok(recorder) => game_data = game_data.with_barrier().with_system_desc(recorder, "replay_recorder", &[]),
+            Err(err) => log::warn!("Matches are not recorded, replay file could not be created: {}", err),
+        }
+    }
+
+    let frame_limit = config.tick_rate;
 
     let mut game =
         CoreApplication::<_, WestinyEvent, WestinyEventReader>::build(
             resources_dir.clone(),
-            server_state::ServerState::new(resources_dir),
+            server_state::ServerState::new(resources_dir, config, ban_list),
         )?
         .with_frame_limit(
             FrameRateLimitStrategy::SleepAndYield(Duration::from_millis(2)),
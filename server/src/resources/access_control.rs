@@ -0,0 +1,305 @@
+use amethyst::config::Config;
+use serde::{Serialize, Deserialize};
+use std::convert::TryFrom;
+use std::fmt;
+use std::net::IpAddr;
+use std::path::PathBuf;
+use std::str::FromStr;
+use westiny_common::PlayerName;
+use westiny_common::network::ErrorKind;
+use westiny_common::utilities::read_ron;
+
+/// An IP address or a CIDR range, e.g. `10.0.0.0/8`
+#[derive(Copy, Clone, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct AddressRange {
+    network: IpAddr,
+    prefix_len: u8,
+}
+
+impl AddressRange {
+    pub fn contains(&self, address: &IpAddr) -> bool {
+        match (self.network, address) {
+            (IpAddr::V4(network), IpAddr::V4(address)) => prefix_matches(&network.octets(), &address.octets(), self.prefix_len),
+            (IpAddr::V6(network), IpAddr::V6(address)) => prefix_matches(&network.octets(), &address.octets(), self.prefix_len),
+            _ => false,
+        }
+    }
+
+    fn max_prefix_len(address: &IpAddr) -> u8 {
+        match address {
+            IpAddr::V4(_) => 32,
+            IpAddr::V6(_) => 128,
+        }
+    }
+}
+
+fn prefix_matches(network: &[u8], address: &[u8], prefix_len: u8) -> bool {
+    let full_bytes = (prefix_len / 8) as usize;
+    let remaining_bits = prefix_len % 8;
+    if network[..full_bytes] != address[..full_bytes] {
+        return false;
+    }
+    if remaining_bits == 0 {
+        return true;
+    }
+    let mask = 0xffu8 << (8 - remaining_bits);
+    network[full_bytes] & mask == address[full_bytes] & mask
+}
+
+impl FromStr for AddressRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (address, prefix_len) = match s.find('/') {
+            Some(index) => (&s[..index], Some(&s[index + 1..])),
+            None => (s, None),
+        };
+        let network = IpAddr::from_str(address.trim()).map_err(|_| format!("Invalid address: {}", address))?;
+        let max_prefix_len = AddressRange::max_prefix_len(&network);
+        let prefix_len = match prefix_len {
+            Some(prefix_len) => prefix_len.trim().parse::<u8>().ok()
+                .filter(|prefix_len| *prefix_len <= max_prefix_len)
+                .ok_or_else(|| format!("Invalid prefix length: {}", prefix_len))?,
+            None => max_prefix_len,
+        };
+        Ok(AddressRange { network, prefix_len })
+    }
+}
+
+impl fmt::Display for AddressRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.prefix_len == AddressRange::max_prefix_len(&self.network) {
+            write!(f, "{}", self.network)
+        } else {
+            write!(f, "{}/{}", self.network, self.prefix_len)
+        }
+    }
+}
+
+impl TryFrom<String> for AddressRange {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        value.parse()
+    }
+}
+
+impl From<AddressRange> for String {
+    fn from(range: AddressRange) -> Self {
+        range.to_string()
+    }
+}
+
+/// Bans are given either to addresses or to player names
+#[derive(Clone, Debug, PartialEq)]
+pub enum BanTarget {
+    Address(AddressRange),
+    Name(PlayerName),
+}
+
+impl FromStr for BanTarget {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match AddressRange::from_str(s) {
+            Ok(range) => Ok(BanTarget::Address(range)),
+            Err(_) => PlayerName::validate(s)
+                .map(BanTarget::Name)
+                .map_err(|err| format!("{} is neither an address range nor a player name: {}", s.trim(), err)),
+        }
+    }
+}
+
+impl fmt::Display for BanTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BanTarget::Address(range) => write!(f, "{}", range),
+            BanTarget::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
+
+/// Clients from these addresses or with these names are not let in.
+/// The list is stored in the ban file set in the server config, so bans survive restarts.
+#[derive(Default, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BanList {
+    addresses: Vec<AddressRange>,
+    /// Compared case-insensitively
+    names: Vec<String>,
+    #[serde(skip)]
+    file: Option<PathBuf>,
+}
+
+impl BanList {
+    /// An empty list is started if the file does not exist yet
+    pub fn load(file: PathBuf) -> anyhow::Result<Self> {
+        let mut ban_list = if file.exists() {
+            read_ron::<BanList>(&file)?
+        } else {
+            BanList::default()
+        };
+        ban_list.file = Some(file);
+        Ok(ban_list)
+    }
+
+    /// Writes the list back to the file it was loaded from
+    pub fn save(&self) -> anyhow::Result<()> {
+        if let Some(file) = &self.file {
+            self.write(file)?;
+        }
+        Ok(())
+    }
+
+    /// Returns false if the target was already banned
+    pub fn ban(&mut self, target: &BanTarget) -> bool {
+        if self.contains(target) {
+            return false;
+        }
+        match target {
+            BanTarget::Address(range) => self.addresses.push(*range),
+            BanTarget::Name(name) => self.names.push(name.0.clone()),
+        }
+        true
+    }
+
+    /// Returns false if the target was not banned
+    pub fn unban(&mut self, target: &BanTarget) -> bool {
+        let count = self.addresses.len() + self.names.len();
+        match target {
+            BanTarget::Address(range) => self.addresses.retain(|banned| banned != range),
+            BanTarget::Name(name) => self.names.retain(|banned| !banned.eq_ignore_ascii_case(&name.0)),
+        }
+        count != self.addresses.len() + self.names.len()
+    }
+
+    pub fn is_banned(&self, address: &IpAddr, player_name: &str) -> bool {
+        self.addresses.iter().any(|range| range.contains(address))
+            || self.names.iter().any(|banned| banned.eq_ignore_ascii_case(player_name.trim()))
+    }
+
+    fn contains(&self, target: &BanTarget) -> bool {
+        match target {
+            BanTarget::Address(range) => self.addresses.contains(range),
+            BanTarget::Name(name) => self.names.iter().any(|banned| banned.eq_ignore_ascii_case(&name.0)),
+        }
+    }
+}
+
+/// Restrictions of a private server, set in the server config. Everyone may join by default.
+#[derive(Default, Debug)]
+pub struct AccessPolicy {
+    password: Option<String>,
+    /// Names of the players let in, compared case-insensitively
+    whitelist: Option<Vec<String>>,
+}
+
+impl AccessPolicy {
+    pub fn new(password: Option<String>, whitelist: Option<Vec<String>>) -> Self {
+        AccessPolicy { password, whitelist }
+    }
+
+    /// Tells why the client may not join, the client is shown the reason
+    pub fn admit(&self, ban_list: &BanList, address: &IpAddr, player_name: &str, password: Option<&str>) -> Result<(), ErrorKind> {
+        if ban_list.is_banned(address, player_name) {
+            return Err(ErrorKind::Banned);
+        }
+        if let Some(expected) = &self.password {
+            match password {
+                None => return Err(ErrorKind::PasswordRequired),
+                Some(password) if password != expected => return Err(ErrorKind::WrongPassword),
+                Some(_) => {}
+            }
+        }
+        if let Some(whitelist) = &self.whitelist {
+            if !whitelist.iter().any(|name| name.eq_ignore_ascii_case(player_name.trim())) {
+                return Err(ErrorKind::NotWhitelisted);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn ip(address: &str) -> IpAddr {
+        address.parse().unwrap()
+    }
+
+    #[test]
+    fn address_ranges_are_matched_by_prefix() {
+        let range: AddressRange = "10.1.0.0/16".parse().unwrap();
+        assert!(range.contains(&ip("10.1.200.3")));
+        assert!(!range.contains(&ip("10.2.0.1")));
+        assert!(!range.contains(&ip("::1")));
+
+        let odd_prefix: AddressRange = "192.168.0.128/25".parse().unwrap();
+        assert!(odd_prefix.contains(&ip("192.168.0.200")));
+        assert!(!odd_prefix.contains(&ip("192.168.0.100")));
+
+        let single: AddressRange = "1.2.3.4".parse().unwrap();
+        assert!(single.contains(&ip("1.2.3.4")));
+        assert!(!single.contains(&ip("1.2.3.5")));
+        assert_eq!(single.to_string(), "1.2.3.4");
+
+        assert!("1.2.3.4/33".parse::<AddressRange>().is_err());
+        assert!("1.2.3.4:5745".parse::<AddressRange>().is_err());
+    }
+
+    #[test]
+    fn ban_targets_are_addresses_or_names() {
+        assert_eq!("10.0.0.0/8".parse(), Ok(BanTarget::Address("10.0.0.0/8".parse().unwrap())));
+        assert_eq!(" Clint ".parse(), Ok(BanTarget::Name(PlayerName("Clint".to_string()))));
+        assert!("10.0.0.2:5745".parse::<BanTarget>().is_err());
+    }
+
+    #[test]
+    fn banned_clients_are_recognized() {
+        let mut ban_list = BanList::default();
+        assert!(ban_list.ban(&"10.0.0.0/8".parse().unwrap()));
+        assert!(ban_list.ban(&"Clint".parse().unwrap()));
+        assert!(!ban_list.ban(&"clint".parse().unwrap()));
+
+        assert!(ban_list.is_banned(&ip("10.20.30.40"), "Joe"));
+        assert!(ban_list.is_banned(&ip("1.1.1.1"), "CLINT"));
+        assert!(!ban_list.is_banned(&ip("1.1.1.1"), "Joe"));
+
+        assert!(ban_list.unban(&"CLINT".parse().unwrap()));
+        assert!(!ban_list.unban(&"Clint".parse().unwrap()));
+        assert!(!ban_list.is_banned(&ip("1.1.1.1"), "Clint"));
+    }
+
+    #[test]
+    fn ban_list_survives_saving() {
+        let file = std::env::temp_dir().join("westiny_test_bans.ron");
+        let _ = std::fs::remove_file(&file);
+
+        let mut ban_list = BanList::load(file.clone()).unwrap();
+        ban_list.ban(&"10.0.0.0/8".parse().unwrap());
+        ban_list.ban(&"Clint".parse().unwrap());
+        ban_list.save().unwrap();
+
+        let loaded = BanList::load(file).unwrap();
+        assert!(loaded.is_banned(&ip("10.0.0.1"), "Joe"));
+        assert!(loaded.is_banned(&ip("1.1.1.1"), "Clint"));
+    }
+
+    #[test]
+    fn access_policy_tells_the_refusal_reason() {
+        let mut ban_list = BanList::default();
+        ban_list.ban(&"Joe".parse().unwrap());
+        let policy = AccessPolicy::new(Some("secret".to_string()), Some(vec!["Clint".to_string(), "Joe".to_string()]));
+        let address = ip("1.1.1.1");
+
+        assert_eq!(policy.admit(&ban_list, &address, "Clint", Some("secret")), Ok(()));
+        assert_eq!(policy.admit(&ban_list, &address, "Joe", Some("secret")), Err(ErrorKind::Banned));
+        assert_eq!(policy.admit(&ban_list, &address, "Clint", None), Err(ErrorKind::PasswordRequired));
+        assert_eq!(policy.admit(&ban_list, &address, "Clint", Some("guess")), Err(ErrorKind::WrongPassword));
+        assert_eq!(policy.admit(&ban_list, &address, "Tuco", Some("secret")), Err(ErrorKind::NotWhitelisted));
+
+        assert_eq!(AccessPolicy::default().admit(&ban_list, &address, "Tuco", None), Ok(()));
+    }
+}
@@ -0,0 +1,116 @@
+use std::str::FromStr;
+use std::sync::{mpsc, Arc, Mutex};
+
+use super::BanTarget;
+
+/// Commands of the server operator, typed into the admin console
+#[derive(Clone, Debug, PartialEq)]
+pub enum AdminCommand {
+    Status,
+    Kick(String),
+    Ban(BanTarget),
+    Unban(BanTarget),
+    Map(String),
+    Set { name: String, value: String },
+    Say(String),
+    Shutdown,
+}
+
+pub const ADMIN_COMMAND_HELP: &str = "Commands: status, kick <name>, ban <addr|range|name>, unban <addr|range|name>, map <name>, set <rule> <value>, say <message>, shutdown";
+
+impl FromStr for AdminCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (command, argument) = match line.find(char::is_whitespace) {
+            Some(index) => (&line[..index], line[index..].trim()),
+            None => (line, ""),
+        };
+
+        let required = |argument: &str, usage: &str| if argument.is_empty() {
+            Err(format!("Usage: {}", usage))
+        } else {
+            Ok(argument.to_string())
+        };
+
+        match command {
+            "status" => Ok(AdminCommand::Status),
+            "kick" => required(argument, "kick <name>").map(AdminCommand::Kick),
+            "ban" => BanTarget::from_str(argument)
+                .map(AdminCommand::Ban)
+                .map_err(|err| format!("Usage: ban <addr|range|name>, e.g. ban 10.0.0.2, ban 10.0.0.0/24. {}", err)),
+            "unban" => BanTarget::from_str(argument)
+                .map(AdminCommand::Unban)
+                .map_err(|err| format!("Usage: unban <addr|range|name>. {}", err)),
+            "map" => required(argument, "map <name>").map(AdminCommand::Map),
+            "set" => {
+                let mut words = argument.splitn(2, char::is_whitespace);
+                match (words.next(), words.next()) {
+                    (Some(name), Some(value)) if !name.is_empty() => Ok(AdminCommand::Set {
+                        name: name.to_string(),
+                        value: value.trim().to_string(),
+                    }),
+                    _ => Err("Usage: set <rule> <value>".to_string()),
+                }
+            }
+            "say" => required(argument, "say <message>").map(AdminCommand::Say),
+            "shutdown" => Ok(AdminCommand::Shutdown),
+            _ => Err(format!("Unknown command: {}. {}", command, ADMIN_COMMAND_HELP)),
+        }
+    }
+}
+
+/// Sends the answer of a command back to the console it was typed into
+#[derive(Clone)]
+pub struct ConsoleReply(Arc<Mutex<mpsc::Sender<String>>>);
+
+impl ConsoleReply {
+    pub fn new(sender: mpsc::Sender<String>) -> Self {
+        ConsoleReply(Arc::new(Mutex::new(sender)))
+    }
+
+    /// The console may have been closed since, then the answer is only logged
+    pub fn send<S: Into<String>>(&self, message: S) {
+        let message = message.into();
+        let sent = self.0.lock()
+            .map(|sender| sender.send(message.clone()).is_ok())
+            .unwrap_or(false);
+        if !sent {
+            log::info!("[admin] {}", message);
+        }
+    }
+}
+
+pub struct AdminCommandEvent {
+    pub command: AdminCommand,
+    pub reply: ConsoleReply,
+}
+
+/// Set by the shutdown command, the server stops at the end of the frame
+#[derive(Default)]
+pub struct ShutdownRequest(pub bool);
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn commands_are_parsed() {
+        assert_eq!("status".parse(), Ok(AdminCommand::Status));
+        assert_eq!("  kick  Narancsos Feco ".parse(), Ok(AdminCommand::Kick("Narancsos Feco".to_string())));
+        assert_eq!("ban 10.0.0.2".parse(), Ok(AdminCommand::Ban("10.0.0.2".parse().unwrap())));
+        assert_eq!("unban Clint".parse(), Ok(AdminCommand::Unban("Clint".parse().unwrap())));
+        assert_eq!("set round_time 90".parse(), Ok(AdminCommand::Set { name: "round_time".to_string(), value: "90".to_string() }));
+        assert_eq!("say Hello  there".parse(), Ok(AdminCommand::Say("Hello  there".to_string())));
+        assert_eq!("shutdown".parse(), Ok(AdminCommand::Shutdown));
+    }
+
+    #[test]
+    fn invalid_commands_are_explained() {
+        assert!("kick".parse::<AdminCommand>().unwrap_err().starts_with("Usage"));
+        assert!("ban 10.0.0.2:5745".parse::<AdminCommand>().is_err());
+        assert!("set round_time".parse::<AdminCommand>().is_err());
+        assert!("teleport".parse::<AdminCommand>().unwrap_err().starts_with("Unknown command"));
+    }
+}
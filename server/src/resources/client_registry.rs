@@ -2,10 +2,11 @@ use std::fmt;
 use std::net::SocketAddr;
 use thiserror::Error;
 use westiny_common::PlayerName;
+use westiny_common::network::ErrorKind;
 
 /// An ID that uniquely identifies a network client.
 /// Can be used in game logic to match relevant entities to network clients.
-#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+#[derive(Eq, PartialEq, Hash, Debug, Copy, Clone)]
 pub struct ClientID(pub u32);
 
 pub struct ClientHandle {
@@ -13,19 +14,27 @@ pub struct ClientHandle {
     pub addr: SocketAddr,
     /// Right now it is used as a user_name, but no further authentication done.
     pub player_name: PlayerName,
+    /// The name sent by the client, it differs from `player_name` when it was already taken
+    requested_name: PlayerName,
+    /// Observers only watch the game, they never get a character
+    pub observer: bool,
 }
 
 pub struct ClientRegistry {
     max_slots: usize,
+    max_observer_slots: usize,
     next_id: u32,
     clients: Vec<ClientHandle>,
 }
 
 #[derive(Error, Debug)]
 pub enum AddError {
-    #[error("Player with already connected with same address xor name, not authorizing. Possibly malicious attempt?")]
+    #[error("Client already connected from the same address with a different name, not authorizing. Possibly malicious attempt?")]
     Unauthorized,
 
+    #[error("Invalid player name: {0}")]
+    InvalidName(ErrorKind),
+
     #[error("Server is full")]
     ServerIsFull,
 }
@@ -36,25 +45,38 @@ pub enum RemoveError {
     NoSuchClient,
 }
 
+impl ClientHandle {
+    pub fn requested_name(&self) -> &PlayerName {
+        &self.requested_name
+    }
+}
+
 impl ClientRegistry {
     pub fn new(max_slots: usize) -> Self {
         ClientRegistry {
             max_slots,
+            max_observer_slots: 0,
             next_id: 0,
             clients: vec![],
         }
     }
 
+    /// Observers have their own slots, they do not take the place of players
+    pub fn with_observer_slots(mut self, max_observer_slots: usize) -> Self {
+        self.max_observer_slots = max_observer_slots;
+        self
+    }
+
     pub fn add(&mut self, addr: &SocketAddr, player_name: &str) -> Result<ClientID, AddError> {
-        if self.clients.len() >= self.max_slots {
-            return Err(AddError::ServerIsFull);
-        }
+        self.register(addr, player_name, false)
+    }
 
-        match self.find_by_addr_or_name(&addr, player_name) {
-            Some(h) if h.player_name.0 == player_name && &h.addr == addr => Ok(h.id),
-            Some(_) => Err(AddError::Unauthorized),
-            None => Ok(self.add_new_client(*addr, player_name)),
-        }
+    pub fn add_observer(&mut self, addr: &SocketAddr, player_name: &str) -> Result<ClientID, AddError> {
+        self.register(addr, player_name, true)
+    }
+
+    pub fn max_slots(&self) -> usize {
+        self.max_slots
     }
 
     pub fn get_clients(&self) -> Vec<&ClientHandle> {
@@ -83,21 +105,64 @@ impl ClientRegistry {
         self.clients.len()
     }
 
-    fn add_new_client(&mut self, addr: SocketAddr, player_name: &str) -> ClientID {
+    /// Number of connected clients, observers excluded
+    pub fn player_count(&self) -> usize {
+        self.clients.iter().filter(|handle| !handle.observer).count()
+    }
+
+    fn observer_count(&self) -> usize {
+        self.client_count() - self.player_count()
+    }
+
+    fn register(&mut self, addr: &SocketAddr, player_name: &str, observer: bool) -> Result<ClientID, AddError> {
+        let requested_name = PlayerName::validate(player_name).map_err(AddError::InvalidName)?;
+        // Connection requests are repeated until the client gets the answer
+        if let Some(handle) = self.find_by_addr(addr) {
+            return if handle.requested_name == requested_name && handle.observer == observer {
+                Ok(handle.id)
+            } else {
+                Err(AddError::Unauthorized)
+            };
+        }
+
+        let is_full = if observer {
+            self.observer_count() >= self.max_observer_slots
+        } else {
+            self.player_count() >= self.max_slots
+        };
+        if is_full {
+            return Err(AddError::ServerIsFull);
+        }
+
+        Ok(self.add_new_client(*addr, requested_name, observer))
+    }
+
+    fn add_new_client(&mut self, addr: SocketAddr, requested_name: PlayerName, observer: bool) -> ClientID {
         let id = ClientID(self.next_id);
         self.next_id += 1;
+        let player_name = self.unique_name(&requested_name);
         self.clients.push(ClientHandle {
             id: id,
             addr,
-            player_name: PlayerName(player_name.into()),
+            player_name,
+            requested_name,
+            observer,
         });
         id
     }
 
-    fn find_by_addr_or_name(&self, addr: &SocketAddr, name: &str) -> Option<&ClientHandle> {
-        self.clients
-            .iter()
-            .find(|&handle| &handle.addr == addr || handle.player_name.0 == name)
+    /// Players with the same name are told apart by a number appended to the name
+    fn unique_name(&self, requested_name: &PlayerName) -> PlayerName {
+        let is_taken = |name: &PlayerName| self.clients.iter()
+            .any(|handle| handle.player_name.0.to_lowercase() == name.0.to_lowercase());
+
+        if !is_taken(requested_name) {
+            return requested_name.clone();
+        }
+        (2..)
+            .map(|number| requested_name.numbered(number))
+            .find(|name| !is_taken(name))
+            .expect("there are more numbers than clients")
     }
 }
 
@@ -107,8 +172,9 @@ impl fmt::Display for ClientRegistry {
         for handle in &self.clients {
             write!(
                 f,
-                "\n  - ID={}, address={}, player_name={}",
-                handle.id.0, handle.addr, handle.player_name.0
+                "\n  - ID={}, address={}, player_name={}{}",
+                handle.id.0, handle.addr, handle.player_name.0,
+                if handle.observer { " (observer)" } else { "" }
             )?;
         }
         Ok(())
@@ -223,15 +289,36 @@ mod test {
     }
 
     #[test]
-    fn test_register_different_address_with_same_name_should_return_error() {
-        let mut reg = ClientRegistry::new(2);
+    fn test_register_different_address_with_same_name_should_number_the_name() {
+        let mut reg = ClientRegistry::new(3);
         reg.add(&make_addr("8.8.8.8", 1234), "NariFeco")
             .expect("could not add NariFeco");
-        let err = reg
+        let second = reg
             .add(&make_addr("1.1.1.1", 1234), "NariFeco")
-            .expect_err("could added another NariFeco?");
+            .expect("could not add another NariFeco");
+        let third = reg
+            .add(&make_addr("2.2.2.2", 1234), "narifeco")
+            .expect("could not add a third NariFeco");
 
-        assert!(matches!(err, AddError::Unauthorized));
+        assert_eq!(reg.find_client(second).expect("second not found").player_name.0, "NariFeco(2)");
+        assert_eq!(reg.find_client(third).expect("third not found").player_name.0, "narifeco(3)");
+
+        let again = reg
+            .add(&make_addr("1.1.1.1", 1234), "NariFeco")
+            .expect("repeated request of the second NariFeco refused");
+        assert_eq!(again, second);
+        assert_eq!(reg.client_count(), 3);
+    }
+
+    #[test]
+    fn test_register_invalid_name_should_return_error() {
+        let mut reg = ClientRegistry::new(2);
+        let err = reg
+            .add(&make_addr("8.8.8.8", 1234), "Nari\nFeco")
+            .expect_err("name with newline added?");
+
+        assert!(matches!(err, AddError::InvalidName(ErrorKind::InvalidNameCharacter)));
+        assert_eq!(reg.client_count(), 0);
     }
 
     #[test]
@@ -257,4 +344,22 @@ mod test {
 
         assert!(matches!(err, RemoveError::NoSuchClient));
     }
+
+    #[test]
+    fn test_observers_do_not_take_player_slots() {
+        let mut reg = ClientRegistry::new(1).with_observer_slots(1);
+        reg.add(&make_addr("8.8.8.8", 1234), "NariFeco")
+            .expect("could not add NariFeco");
+        let id = reg.add_observer(&make_addr("1.1.1.1", 1234), "BananJoe")
+            .expect("could not add BananJoe as observer");
+
+        assert!(reg.find_client(id).expect("observer not found").observer);
+        assert_eq!(reg.client_count(), 2);
+        assert_eq!(reg.player_count(), 1);
+
+        let err = reg
+            .add_observer(&make_addr("2.2.2.2", 1234), "Overdose")
+            .expect_err("Overdose added?");
+        assert!(matches!(err, AddError::ServerIsFull));
+    }
 }
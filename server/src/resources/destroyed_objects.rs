@@ -0,0 +1,22 @@
+use amethyst::core::Transform;
+use westiny_common::components::NetworkId;
+use westiny_common::resources::map::DestructibleDetails;
+use std::time::Duration;
+
+/// A destroyed map object. Clients joining later must be told to remove it from their map.
+pub struct DestroyedObject {
+    pub network_id: NetworkId,
+    pub transform: Transform,
+    pub details: DestructibleDetails,
+    /// The object is never placed back if not set
+    pub respawn_at: Option<Duration>,
+}
+
+#[derive(Default)]
+pub struct DestroyedObjects(pub Vec<DestroyedObject>);
+
+impl DestroyedObjects {
+    pub fn network_ids(&self) -> Vec<NetworkId> {
+        self.0.iter().map(|object| object.network_id).collect()
+    }
+}
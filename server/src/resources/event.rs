@@ -2,6 +2,8 @@ use super::ClientID;
 
 use westiny_common::components::Input;
 use westiny_common::PlayerName;
+use westiny_common::network::PacketType;
+use amethyst::ecs::Entity;
 
 #[derive(Debug, Eq, PartialEq)]
 pub enum ClientNetworkEvent {
@@ -14,5 +16,24 @@ pub enum NetworkCommand {
     Input {
         id: ClientID,
         input: Input
-    }
+    },
+    Chat {
+        id: ClientID,
+        text: String,
+        team_only: bool,
+    },
+}
+
+/// Emitted when the health of an entity is drained
+pub struct KillEvent {
+    pub victim: Entity,
+    /// The entity causing the last damage, if known
+    pub killer: Option<Entity>,
+    /// Name of the weapon the last damage was dealt with
+    pub weapon: Option<String>,
+    /// Other entities that hurt the victim shortly before the kill
+    pub assists: Vec<Entity>,
 }
+
+/// A packet broadcast to the clients, emitted for the replay recorder
+pub struct ReplayEvent(pub PacketType);
@@ -0,0 +1,347 @@
+use amethyst::core::ecs::World;
+use serde::Deserialize;
+use std::path::Path;
+use std::str::FromStr;
+use westiny_common::utilities::read_ron;
+use westiny_common::metric_dimension::{MeterPerSec, Second};
+use westiny_common::metric_dimension::length::Meter;
+
+const GAME_RULES_FILE: &'static str = "game_rules.ron";
+
+/// Faster players would run through the walls between two physics frames
+pub const MAX_WALK_SPEED: MeterPerSec = MeterPerSec(20.0);
+
+#[derive(Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum GameMode {
+    /// Everyone for themselves
+    Deathmatch,
+    /// Players are assigned to teams on join
+    TeamDeathmatch,
+    /// Teams score by bringing the enemy flag to their own base
+    CaptureTheFlag,
+    /// Players have a single life, the last survivor wins the round
+    LastManStanding,
+}
+
+impl FromStr for GameMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "Deathmatch" => Ok(GameMode::Deathmatch),
+            "TeamDeathmatch" => Ok(GameMode::TeamDeathmatch),
+            "CaptureTheFlag" => Ok(GameMode::CaptureTheFlag),
+            "LastManStanding" => Ok(GameMode::LastManStanding),
+            _ => Err(format!("Unknown game mode: {}", s)),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct GameRules {
+    pub mode: GameMode,
+    /// Whether players can hurt their teammates
+    #[serde(default)]
+    pub friendly_fire: bool,
+    /// The match is in warmup until this many players join
+    #[serde(default = "default_min_players")]
+    pub min_players: usize,
+    /// Length of a round
+    #[serde(default = "default_round_time")]
+    pub round_time: Second,
+    /// Time between the end of a round and the start of the next one, the results are shown meanwhile
+    #[serde(default = "default_intermission_time")]
+    pub intermission_time: Second,
+    /// The round ends when a player (or a team in team modes) reaches this many kills
+    #[serde(default)]
+    pub score_limit: Option<u32>,
+    /// A dropped flag is returned to its base automatically after this time
+    #[serde(default = "default_flag_return_time")]
+    pub flag_return_time: Second,
+    /// Playing area of the last man standing mode
+    #[serde(default)]
+    pub safe_zone: SafeZoneDetails,
+    /// Health of the players at spawn, healing does not go above it
+    #[serde(default = "default_max_health")]
+    pub max_health: u16,
+    /// Ratio of the damage absorbed by the armor
+    #[serde(default = "default_armor_absorption")]
+    pub armor_absorption: f32,
+    /// Players out of combat regenerate health if set
+    #[serde(default)]
+    pub health_regen: Option<HealthRegenDetails>,
+    /// Time between the death and the respawn of a player
+    #[serde(default = "default_respawn_time")]
+    pub respawn_time: Second,
+    /// Sprinting and the status effects change this speed
+    #[serde(default = "default_walk_speed")]
+    pub walk_speed: MeterPerSec,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+pub struct HealthRegenDetails {
+    /// Regeneration starts this long after the last damage taken
+    pub delay: Second,
+    pub health_per_sec: f32,
+}
+
+#[derive(Deserialize, Clone, Debug)]
+#[serde(default)]
+pub struct SafeZoneDetails {
+    pub x: Meter,
+    pub y: Meter,
+    /// Radius at the start of the round
+    pub initial_radius: Meter,
+    /// The zone stops shrinking at this radius
+    pub final_radius: Meter,
+    /// Time from the start of the round until the zone reaches its final radius
+    pub shrink_time: Second,
+    /// Players outside the zone lose this much health every second
+    pub damage_per_sec: u16,
+}
+
+impl Default for SafeZoneDetails {
+    fn default() -> Self {
+        SafeZoneDetails {
+            x: Meter(0.0),
+            y: Meter(0.0),
+            initial_radius: Meter(24.0),
+            final_radius: Meter(3.0),
+            shrink_time: Second(180.0),
+            damage_per_sec: 5,
+        }
+    }
+}
+
+impl SafeZoneDetails {
+    /// Radius of the zone when `elapsed` time has passed since the start of the round
+    pub fn radius_at(&self, elapsed: Second) -> Meter {
+        let progress = (elapsed.0 / self.shrink_time.0).min(1.0).max(0.0);
+        self.initial_radius - (self.initial_radius - self.final_radius) * progress
+    }
+}
+
+fn default_min_players() -> usize {
+    2
+}
+
+fn default_round_time() -> Second {
+    Second(600.0)
+}
+
+fn default_intermission_time() -> Second {
+    Second(15.0)
+}
+
+fn default_flag_return_time() -> Second {
+    Second(30.0)
+}
+
+fn default_max_health() -> u16 {
+    100
+}
+
+fn default_armor_absorption() -> f32 {
+    0.5
+}
+
+fn default_respawn_time() -> Second {
+    Second(5.0)
+}
+
+fn default_walk_speed() -> MeterPerSec {
+    MeterPerSec(4.0)
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        GameRules {
+            mode: GameMode::Deathmatch,
+            friendly_fire: false,
+            min_players: default_min_players(),
+            round_time: default_round_time(),
+            intermission_time: default_intermission_time(),
+            score_limit: None,
+            flag_return_time: default_flag_return_time(),
+            safe_zone: SafeZoneDetails::default(),
+            max_health: default_max_health(),
+            armor_absorption: default_armor_absorption(),
+            health_regen: None,
+            respawn_time: default_respawn_time(),
+            walk_speed: default_walk_speed(),
+        }
+    }
+}
+
+/// Times are turned into `Duration`s, so they must be finite and not negative
+pub fn validate_time(time: Second) -> Result<(), String> {
+    if time.0.is_finite() && time.0 >= 0.0 {
+        Ok(())
+    } else {
+        Err(format!("must be a finite, not negative number of seconds, got {}", time.0))
+    }
+}
+
+pub fn validate_walk_speed(walk_speed: MeterPerSec) -> Result<(), String> {
+    if walk_speed.0.is_finite() && walk_speed.0 > 0.0 && walk_speed.0 <= MAX_WALK_SPEED.0 {
+        Ok(())
+    } else {
+        Err(format!("must be above 0 and at most {} m/s, got {}", MAX_WALK_SPEED.0, walk_speed.0))
+    }
+}
+
+impl GameRules {
+    pub fn initialize<P: AsRef<Path>>(world: &mut World, resources_path: P) -> anyhow::Result<()> {
+        let rules = GameRules::load(&resources_path.as_ref().join(GAME_RULES_FILE))?;
+        world.insert(rules);
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> anyhow::Result<GameRules> {
+        let rules: GameRules = read_ron(path)?;
+        rules.validate().map_err(|err| anyhow::anyhow!("Invalid game rules in {}: {}", path.display(), err))?;
+        Ok(rules)
+    }
+
+    /// Checks the values the game could not run with
+    pub fn validate(&self) -> Result<(), String> {
+        let times = [
+            ("round_time", self.round_time),
+            ("intermission_time", self.intermission_time),
+            ("flag_return_time", self.flag_return_time),
+            ("respawn_time", self.respawn_time),
+            ("safe_zone.shrink_time", self.safe_zone.shrink_time),
+        ];
+        for (name, time) in times.iter() {
+            validate_time(*time).map_err(|err| format!("{} {}", name, err))?;
+        }
+        if let Some(health_regen) = &self.health_regen {
+            validate_time(health_regen.delay).map_err(|err| format!("health_regen.delay {}", err))?;
+        }
+        validate_walk_speed(self.walk_speed).map_err(|err| format!("walk_speed {}", err))?;
+        if self.max_health == 0 {
+            // Players spawned without health would die without a kill
+            return Err("max_health must be positive".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.armor_absorption) {
+            return Err("armor_absorption must be between 0 and 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Players are not respawned after their death in the round
+    pub fn has_single_life(&self) -> bool {
+        self.mode == GameMode::LastManStanding
+    }
+
+    pub fn has_teams(&self) -> bool {
+        match self.mode {
+            GameMode::TeamDeathmatch | GameMode::CaptureTheFlag => true,
+            GameMode::Deathmatch | GameMode::LastManStanding => false,
+        }
+    }
+
+    /// Changes a rule at runtime by its name in the rules file. Times are given in seconds,
+    /// the score limit can be turned off with `none`. The value is checked as in the rules file.
+    pub fn set(&mut self, name: &str, value: &str) -> Result<(), String> {
+        fn parse<T: FromStr>(value: &str) -> Result<T, String> {
+            value.parse().map_err(|_| format!("Invalid value: {}", value))
+        }
+
+        let mut rules = self.clone();
+        match name {
+            "friendly_fire" => rules.friendly_fire = parse(value)?,
+            "min_players" => rules.min_players = parse(value)?,
+            "round_time" => rules.round_time = Second(parse(value)?),
+            "intermission_time" => rules.intermission_time = Second(parse(value)?),
+            "score_limit" => rules.score_limit = match value {
+                "none" => None,
+                _ => Some(parse(value)?),
+            },
+            "flag_return_time" => rules.flag_return_time = Second(parse(value)?),
+            "max_health" => rules.max_health = parse(value)?,
+            "armor_absorption" => rules.armor_absorption = parse(value)?,
+            "respawn_time" => rules.respawn_time = Second(parse(value)?),
+            "walk_speed" => rules.walk_speed = MeterPerSec(parse(value)?),
+            _ => return Err(format!("Unknown rule: {}", name)),
+        }
+        rules.validate()?;
+        *self = rules;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn safe_zone_shrinks_until_final_radius() {
+        let zone = SafeZoneDetails {
+            initial_radius: Meter(20.0),
+            final_radius: Meter(4.0),
+            shrink_time: Second(100.0),
+            ..SafeZoneDetails::default()
+        };
+        assert_eq!(zone.radius_at(Second(0.0)), Meter(20.0));
+        assert_eq!(zone.radius_at(Second(50.0)), Meter(12.0));
+        assert_eq!(zone.radius_at(Second(100.0)), Meter(4.0));
+        assert_eq!(zone.radius_at(Second(500.0)), Meter(4.0));
+    }
+
+    #[test]
+    fn rules_are_set_by_name() {
+        let mut rules = GameRules::default();
+        rules.set("friendly_fire", "true").unwrap();
+        rules.set("round_time", "90").unwrap();
+        rules.set("score_limit", "25").unwrap();
+        assert!(rules.friendly_fire);
+        assert_eq!(rules.round_time, Second(90.0));
+        assert_eq!(rules.score_limit, Some(25));
+
+        rules.set("score_limit", "none").unwrap();
+        assert_eq!(rules.score_limit, None);
+
+        assert!(rules.set("max_health", "lots").is_err());
+        assert!(rules.set("armor_absorption", "2").is_err());
+        assert!(rules.set("gravity", "1").is_err());
+    }
+
+    #[test]
+    fn invalid_times_speeds_and_health_are_refused() {
+        let mut rules = GameRules::default();
+        for value in ["-1", "inf", "NaN"].iter() {
+            assert!(rules.set("respawn_time", value).is_err(), "respawn_time {} was accepted", value);
+            assert!(rules.set("round_time", value).is_err(), "round_time {} was accepted", value);
+        }
+        assert!(rules.set("walk_speed", "0").is_err());
+        assert!(rules.set("walk_speed", "100").is_err());
+        assert!(rules.set("max_health", "0").is_err());
+        assert_eq!(rules.respawn_time, default_respawn_time());
+        assert_eq!(rules.max_health, default_max_health());
+        assert_eq!(rules.walk_speed, default_walk_speed());
+
+        rules.set("respawn_time", "0").unwrap();
+        assert_eq!(rules.respawn_time, Second(0.0));
+    }
+
+    #[test]
+    fn invalid_rules_file_is_refused() {
+        let path = std::env::temp_dir().join("westiny_invalid_game_rules.ron");
+        std::fs::write(&path, "(mode: Deathmatch, respawn_time: Second(-5.0))").unwrap();
+        let err = GameRules::load(&path).unwrap_err();
+        assert!(err.to_string().contains("respawn_time"), "{}", err);
+    }
+
+    #[test]
+    fn shipped_rules_are_valid() {
+        let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources").join(GAME_RULES_FILE);
+        GameRules::load(&path).unwrap();
+    }
+
+    #[test]
+    fn game_mode_is_parsed_by_name() {
+        assert_eq!("CaptureTheFlag".parse(), Ok(GameMode::CaptureTheFlag));
+        assert!("Tag".parse::<GameMode>().is_err());
+    }
+}
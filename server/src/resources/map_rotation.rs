@@ -0,0 +1,106 @@
+/// The map being played and the one to load next. The requested map is loaded by the server
+/// state after the frame it is requested in.
+pub struct MapRotation {
+    current: String,
+    requested: Option<String>,
+    loads: u32,
+    /// The maps played in turn, the first one is loaded at start
+    rotation: Vec<String>,
+    /// Index of the map in the rotation loaded at the end of the match
+    next: usize,
+    /// The maps found in the map directory, these can be requested by name
+    available: Vec<String>,
+}
+
+impl MapRotation {
+    /// The rotation must not be empty, the server config is validated for it
+    pub fn new(rotation: Vec<String>, available: Vec<String>) -> Self {
+        MapRotation {
+            current: rotation[0].clone(),
+            requested: None,
+            loads: 0,
+            next: 1 % rotation.len(),
+            rotation,
+            available,
+        }
+    }
+
+    pub fn current(&self) -> &str {
+        &self.current
+    }
+
+    /// How many maps have been loaded since the start. Systems holding on to map entities
+    /// start over when it changes.
+    pub fn loads(&self) -> u32 {
+        self.loads
+    }
+
+    /// The rotation goes on where it was left even if a map has been loaded by the admin meanwhile.
+    /// With a single map in the rotation the next round is played on a fresh copy of it.
+    pub fn request_next(&mut self) {
+        self.requested = Some(self.rotation[self.next].clone());
+        self.next = (self.next + 1) % self.rotation.len();
+    }
+
+    pub fn is_available(&self, map: &str) -> bool {
+        self.available.iter().any(|available| available == map)
+    }
+
+    /// Loads the map after the current frame, unknown maps are refused
+    pub fn request(&mut self, map: &str) -> Result<(), String> {
+        if !self.is_available(map) {
+            return Err(format!("Unknown map: {}, available maps: {}", map, self.available.join(", ")));
+        }
+        self.requested = Some(map.to_string());
+        Ok(())
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.is_some()
+    }
+
+    /// The taken map becomes the current one
+    pub fn take_requested(&mut self) -> Option<String> {
+        let requested = self.requested.take()?;
+        self.current = requested.clone();
+        self.loads += 1;
+        Some(requested)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn maps(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn maps_are_loaded_in_turn() {
+        let mut rotation = MapRotation::new(maps(&["rust2", "canyon"]), maps(&["canyon", "rust2"]));
+        assert_eq!(rotation.current(), "rust2");
+
+        let mut loaded = Vec::new();
+        for _ in 0..3 {
+            rotation.request_next();
+            loaded.push(rotation.take_requested().unwrap());
+        }
+
+        assert_eq!(loaded, maps(&["canyon", "rust2", "canyon"]));
+        assert_eq!(rotation.current(), "canyon");
+        assert_eq!(rotation.loads(), 3);
+    }
+
+    #[test]
+    fn only_available_maps_can_be_requested() {
+        let mut rotation = MapRotation::new(maps(&["rust2"]), maps(&["canyon", "rust2"]));
+
+        assert!(rotation.request("moon").is_err());
+        assert!(!rotation.is_requested());
+
+        assert!(rotation.request("canyon").is_ok());
+        assert_eq!(rotation.take_requested(), Some("canyon".to_string()));
+        assert_eq!(rotation.take_requested(), None);
+    }
+}
@@ -0,0 +1,48 @@
+use std::time::Duration;
+use westiny_common::network::PlayerScore;
+use westiny_common::PlayerName;
+
+pub enum Phase {
+    Warmup,
+    Live {
+        ends_at: Duration,
+    },
+    Intermission {
+        ends_at: Duration,
+        results: Vec<PlayerScore>,
+        winner: Option<PlayerName>,
+    },
+}
+
+/// The state of the match lifecycle: warmup → live round → intermission → next round
+pub struct MatchState {
+    pub phase: Phase,
+    /// Number of rounds started since the server is running
+    pub round: u32,
+}
+
+impl Default for MatchState {
+    fn default() -> Self {
+        MatchState {
+            phase: Phase::Warmup,
+            round: 0,
+        }
+    }
+}
+
+impl MatchState {
+    pub fn is_live(&self) -> bool {
+        match self.phase {
+            Phase::Live { .. } => true,
+            _ => false,
+        }
+    }
+
+    /// Nobody gets hurt or respawned while the results are shown
+    pub fn is_intermission(&self) -> bool {
+        match self.phase {
+            Phase::Intermission { .. } => true,
+            _ => false,
+        }
+    }
+}
@@ -1,11 +1,29 @@
 pub(crate) use client_registry::ClientID;
-pub(crate) use event::{ClientNetworkEvent, NetworkCommand};
+pub(crate) use event::{ClientNetworkEvent, NetworkCommand, KillEvent, ReplayEvent};
 pub(crate) use network_stream_id::StreamId;
 
+pub use access_control::{AccessPolicy, AddressRange, BanList, BanTarget};
+pub use admin::{AdminCommand, AdminCommandEvent, ConsoleReply, ShutdownRequest, ADMIN_COMMAND_HELP};
 pub use network_id_supplier::NetworkIdSupplier;
-pub use client_registry::ClientRegistry;
+pub use client_registry::{AddError, ClientRegistry};
+pub use destroyed_objects::{DestroyedObject, DestroyedObjects};
+pub use game_rules::{GameMode, GameRules};
+pub use map_rotation::MapRotation;
+pub use match_state::{Phase, MatchState};
+pub use packet_guard::{PacketGuard, Verdict, MAX_PACKET_SIZE};
+pub use scoreboard::{PlayerStats, Scoreboard};
+pub use server_config::{ConfigError, ServerConfig, SERVER_USAGE};
 
+mod access_control;
+mod admin;
 mod client_registry;
+mod destroyed_objects;
+mod game_rules;
+mod map_rotation;
+mod match_state;
+mod scoreboard;
+mod server_config;
 mod event;
 mod network_id_supplier;
 mod network_stream_id;
+mod packet_guard;
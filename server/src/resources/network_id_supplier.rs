@@ -33,7 +33,11 @@ mod test {
             assert_eq!(expected, actual, "With Player entity")
         }
 
-        // TODO place here another network EntitiyType when we have other than the Player
+        for i in 0..100 {
+            let actual = supplier.next(EntityType::WeaponPickup);
+            let expected = NetworkId { entity_type: EntityType::WeaponPickup, id: i};
+            assert_eq!(expected, actual, "With WeaponPickup entity")
+        }
 
         for i in 1000..1100 {
             let actual = supplier.next(EntityType::Player);
@@ -4,9 +4,14 @@ pub enum StreamId {
     EntityStateUpdate,
     HealthUpdate,
     AmmoUpdate,
-    WeaponSwitch,
+    InventoryUpdate,
     ShotEvent,
     PlayerDeath,
+    MatchUpdate,
+    SafeZone,
+    StatusEffects,
+    StaminaUpdate,
+    Chat,
 }
 
 impl Into<Option<u8>> for StreamId {
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+/// Larger payloads are dropped before deserialization
+pub const MAX_PACKET_SIZE: usize = 1024;
+/// Clients send their input every frame, the packets above this rate are dropped.
+/// The clients behind the same IP address share the limit.
+const MAX_PACKETS_PER_SEC: u32 = 250;
+/// Sending this many times more packets than the rate limit counts as flooding
+const FLOOD_MULTIPLIER: u32 = 4;
+/// Invalid packets tolerated from an address before it is disconnected
+const MAX_VIOLATIONS: u32 = 10;
+/// Packets of disconnected offenders are ignored meanwhile
+const BLOCK_DURATION: Duration = Duration::from_secs(30);
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(1);
+/// Records of addresses silent for this long are dropped
+const RECORD_LIFETIME: Duration = Duration::from_secs(60);
+/// At most this many addresses are followed, so senders spoofing their address cannot exhaust the memory
+const MAX_RECORDS: usize = 4096;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Verdict {
+    Accept,
+    Drop,
+    /// The sender has misbehaved too many times
+    Disconnect,
+}
+
+#[derive(Default)]
+struct AddressRecord {
+    window_start: Duration,
+    packets: u32,
+    violations: u32,
+    blocked_until: Option<Duration>,
+}
+
+impl AddressRecord {
+    fn violation(&mut self, max_violations: u32, now: Duration) -> Verdict {
+        self.violations += 1;
+        if self.violations >= max_violations {
+            self.blocked_until = Some(now + BLOCK_DURATION);
+            Verdict::Disconnect
+        } else {
+            Verdict::Drop
+        }
+    }
+}
+
+/// Keeps count of the packets and the violations of every sender address.
+/// The ports are not told apart, so changing the source port does not get around the limits.
+pub struct PacketGuard {
+    records: HashMap<IpAddr, AddressRecord>,
+    max_packets_per_sec: u32,
+    max_violations: u32,
+}
+
+impl Default for PacketGuard {
+    fn default() -> Self {
+        PacketGuard::new(MAX_PACKETS_PER_SEC, MAX_VIOLATIONS)
+    }
+}
+
+impl PacketGuard {
+    pub fn new(max_packets_per_sec: u32, max_violations: u32) -> Self {
+        PacketGuard {
+            records: HashMap::new(),
+            max_packets_per_sec,
+            max_violations,
+        }
+    }
+
+    /// Decides on a packet before it is deserialized. Packets above the rate limit are dropped,
+    /// flooding counts as a single violation in every window.
+    pub fn admit(&mut self, addr: &SocketAddr, size: usize, now: Duration) -> Verdict {
+        let max_packets_per_sec = self.max_packets_per_sec;
+        let max_violations = self.max_violations;
+        let record = match self.record_of(addr.ip()) {
+            Some(record) => record,
+            None => return Verdict::Drop,
+        };
+
+        if let Some(blocked_until) = record.blocked_until {
+            if now < blocked_until {
+                return Verdict::Drop;
+            }
+            record.blocked_until = None;
+            record.violations = 0;
+        }
+
+        if now >= record.window_start + RATE_LIMIT_WINDOW {
+            record.window_start = now;
+            record.packets = 0;
+        }
+        record.packets += 1;
+        if record.packets > max_packets_per_sec {
+            return if record.packets == max_packets_per_sec * FLOOD_MULTIPLIER + 1 {
+                record.violation(max_violations, now)
+            } else {
+                Verdict::Drop
+            };
+        }
+
+        if size > MAX_PACKET_SIZE {
+            return record.violation(max_violations, now);
+        }
+        Verdict::Accept
+    }
+
+    /// Called when an admitted packet turns out to be malformed or unexpected
+    pub fn report(&mut self, addr: &SocketAddr, now: Duration) -> Verdict {
+        let max_violations = self.max_violations;
+        match self.record_of(addr.ip()) {
+            Some(record) => record.violation(max_violations, now),
+            None => Verdict::Drop,
+        }
+    }
+
+    /// When every record is taken, the least recently active address is forgotten for the new one.
+    /// Blocked addresses are kept, none is returned if all of them are blocked.
+    fn record_of(&mut self, ip: IpAddr) -> Option<&mut AddressRecord> {
+        if !self.records.contains_key(&ip) && self.records.len() >= MAX_RECORDS {
+            let least_active = self.records.iter()
+                .filter(|(_, record)| record.blocked_until.is_none())
+                .min_by_key(|(_, record)| record.window_start)
+                .map(|(ip, _)| *ip)?;
+            self.records.remove(&least_active);
+        }
+        Some(self.records.entry(ip).or_default())
+    }
+
+    /// Forgets the addresses not heard from recently, blocked ones are kept until their block expires
+    pub fn forget_idle(&mut self, now: Duration) {
+        self.records.retain(|_, record| {
+            now < record.window_start + RECORD_LIFETIME
+                || record.blocked_until.map_or(false, |blocked_until| now < blocked_until)
+        });
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn addr() -> SocketAddr {
+        SocketAddr::from(([10, 0, 0, 1], 5000))
+    }
+
+    #[test]
+    fn packets_above_the_rate_limit_are_dropped() {
+        let mut guard = PacketGuard::new(3, 10);
+        let now = Duration::from_secs(5);
+        for _ in 0..3 {
+            assert_eq!(guard.admit(&addr(), 10, now), Verdict::Accept);
+        }
+        assert_eq!(guard.admit(&addr(), 10, now), Verdict::Drop);
+        assert_eq!(guard.admit(&SocketAddr::from(([10, 0, 0, 1], 5001)), 10, now), Verdict::Drop, "the port is not a new address");
+        assert_eq!(guard.admit(&SocketAddr::from(([10, 0, 0, 2], 5000)), 10, now), Verdict::Accept);
+
+        assert_eq!(guard.admit(&addr(), 10, now + RATE_LIMIT_WINDOW), Verdict::Accept);
+    }
+
+    #[test]
+    fn flooding_is_a_violation() {
+        let mut guard = PacketGuard::new(3, 2);
+        for second in 0..2 {
+            let now = Duration::from_secs(second);
+            let verdicts: Vec<_> = (0..=3 * FLOOD_MULTIPLIER).map(|_| guard.admit(&addr(), 10, now)).collect();
+            assert_eq!(verdicts.iter().filter(|verdict| **verdict == Verdict::Accept).count(), 3);
+            assert_eq!(verdicts.last(), Some(&if second == 0 { Verdict::Drop } else { Verdict::Disconnect }));
+        }
+    }
+
+    #[test]
+    fn oversized_packets_are_dropped() {
+        let mut guard = PacketGuard::default();
+        assert_eq!(guard.admit(&addr(), MAX_PACKET_SIZE + 1, Duration::from_secs(0)), Verdict::Drop);
+        assert_eq!(guard.admit(&addr(), MAX_PACKET_SIZE, Duration::from_secs(0)), Verdict::Accept);
+    }
+
+    #[test]
+    fn repeat_offenders_are_disconnected_and_blocked() {
+        let mut guard = PacketGuard::new(100, 3);
+        let now = Duration::from_secs(1);
+        assert_eq!(guard.report(&addr(), now), Verdict::Drop);
+        assert_eq!(guard.report(&addr(), now), Verdict::Drop);
+        assert_eq!(guard.report(&addr(), now), Verdict::Disconnect);
+
+        assert_eq!(guard.admit(&addr(), 10, now + Duration::from_secs(1)), Verdict::Drop);
+        assert_eq!(guard.admit(&SocketAddr::from(([10, 0, 0, 1], 6000)), 10, now + Duration::from_secs(1)), Verdict::Drop);
+        assert_eq!(guard.admit(&addr(), 10, now + BLOCK_DURATION), Verdict::Accept);
+        assert_eq!(guard.report(&addr(), now + BLOCK_DURATION), Verdict::Drop, "violations are reset after the block");
+    }
+
+    #[test]
+    fn idle_addresses_are_forgotten() {
+        let mut guard = PacketGuard::new(100, 1);
+        guard.admit(&addr(), 10, Duration::from_secs(0));
+        guard.report(&SocketAddr::from(([10, 0, 0, 2], 5000)), Duration::from_secs(50));
+
+        guard.forget_idle(RECORD_LIFETIME);
+        assert_eq!(guard.records.len(), 1, "the blocked address is kept");
+    }
+
+    #[test]
+    fn number_of_records_is_capped() {
+        let mut guard = PacketGuard::new(100, 1);
+        guard.report(&addr(), Duration::from_secs(0));
+
+        for host in 0..MAX_RECORDS as u32 + 100 {
+            let now = Duration::from_millis(host as u64);
+            let spoofed = SocketAddr::new(IpAddr::from(std::net::Ipv4Addr::from(0x0b00_0000 + host)), 5000);
+            assert_eq!(guard.admit(&spoofed, 10, now), Verdict::Accept);
+        }
+        assert_eq!(guard.records.len(), MAX_RECORDS);
+        assert_eq!(guard.admit(&addr(), 10, Duration::from_secs(1)), Verdict::Drop, "the blocked address is kept");
+    }
+}
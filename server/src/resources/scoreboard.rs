@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+use super::ClientID;
+use westiny_common::components::Team;
+
+#[derive(Default, Clone, Debug)]
+pub struct PlayerStats {
+    pub kills: u32,
+    pub deaths: u32,
+    pub assists: u32,
+    pub captures: u32,
+}
+
+/// Statistics of the players in the current round
+#[derive(Default)]
+pub struct Scoreboard {
+    stats: HashMap<ClientID, PlayerStats>,
+    /// Flag captures are kept by team too, so they are not lost when the capturer leaves
+    team_captures: HashMap<Team, u32>,
+    /// Increased on every change, so the clients are notified only when needed
+    revision: u32,
+}
+
+impl Scoreboard {
+    pub fn add_kill(&mut self, client_id: ClientID) {
+        self.stats.entry(client_id).or_default().kills += 1;
+        self.revision += 1;
+    }
+
+    pub fn add_death(&mut self, client_id: ClientID) {
+        self.stats.entry(client_id).or_default().deaths += 1;
+        self.revision += 1;
+    }
+
+    pub fn add_assist(&mut self, client_id: ClientID) {
+        self.stats.entry(client_id).or_default().assists += 1;
+        self.revision += 1;
+    }
+
+    pub fn add_capture(&mut self, client_id: ClientID, team: Team) {
+        self.stats.entry(client_id).or_default().captures += 1;
+        *self.team_captures.entry(team).or_default() += 1;
+        self.revision += 1;
+    }
+
+    pub fn stats_of(&self, client_id: ClientID) -> PlayerStats {
+        self.stats.get(&client_id).cloned().unwrap_or_default()
+    }
+
+    pub fn team_captures(&self, team: Team) -> u32 {
+        self.team_captures.get(&team).copied().unwrap_or_default()
+    }
+
+    pub fn reset(&mut self) {
+        self.stats.clear();
+        self.team_captures.clear();
+        self.revision += 1;
+    }
+
+    pub fn revision(&self) -> u32 {
+        self.revision
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn stats_are_counted_until_reset() {
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.add_kill(ClientID(1));
+        scoreboard.add_kill(ClientID(1));
+        scoreboard.add_kill(ClientID(2));
+        scoreboard.add_death(ClientID(2));
+        scoreboard.add_assist(ClientID(3));
+
+        assert_eq!(scoreboard.stats_of(ClientID(1)).kills, 2);
+        assert_eq!(scoreboard.stats_of(ClientID(2)).kills, 1);
+        assert_eq!(scoreboard.stats_of(ClientID(2)).deaths, 1);
+        assert_eq!(scoreboard.stats_of(ClientID(3)).kills, 0);
+        assert_eq!(scoreboard.stats_of(ClientID(3)).assists, 1);
+
+        scoreboard.reset();
+        assert_eq!(scoreboard.stats_of(ClientID(1)).kills, 0);
+    }
+}
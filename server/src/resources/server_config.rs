@@ -0,0 +1,351 @@
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+use westiny_common::utilities::read_ron;
+use westiny_common::metric_dimension::{MeterPerSec, Second};
+use westiny_common::resources::Seed;
+use westiny_common::resources::map::{available_maps, is_buildable, MAP_NAME};
+use westiny_common::PlayerName;
+
+use super::{GameMode, GameRules};
+use super::game_rules::{validate_time, validate_walk_speed};
+
+pub const SERVER_CONFIG_FILE: &str = "server.ron";
+
+pub const SERVER_USAGE: &str = "\
+Usage: westiny_server [OPTIONS]
+
+Options override the values of the configuration file:
+    --config <path>          configuration file, resources/server.ron by default
+    --bind <address>         address of the game socket, e.g. 0.0.0.0:5745
+    --name <name>            server name shown in the LAN server list
+    --max-players <count>    number of player slots
+    --tick-rate <ticks>      simulation frames per second
+    --maps <names>           comma separated maps played in turn, e.g. rust2,canyon
+    --seed <seed>            seed of the map generation, only 0 is supported yet
+    --mode <mode>            Deathmatch, TeamDeathmatch, CaptureTheFlag or LastManStanding
+    --respawn-time <secs>    time between death and respawn
+    --walk-speed <m/s>       walk speed of the players
+    --admin-port <port>      accept admin commands over TCP on the loopback interface
+    --password <password>    only clients knowing the password may join
+    --kick-cheaters <bool>   kick the players behaving suspiciously instead of only logging them
+    --record <dir>           record the matches into replay files in this directory
+    --help                   print this message";
+
+#[derive(Error, Debug, PartialEq)]
+pub enum ConfigError {
+    #[error("Server config {path} could not be read: {message}")]
+    Unreadable { path: String, message: String },
+    #[error("Invalid `{key}` in server config: {message}")]
+    InvalidValue { key: String, message: String },
+    #[error("Unknown option {0}, see --help")]
+    UnknownOption(String),
+    #[error("Missing value of option {0}")]
+    MissingValue(String),
+}
+
+impl ConfigError {
+    fn invalid(key: &str, message: impl Into<String>) -> Self {
+        ConfigError::InvalidValue { key: key.to_string(), message: message.into() }
+    }
+}
+
+/// Settings of the server process, read from `server.ron` and overridden on the command line.
+/// The gameplay values are optional, if given they override the ones in `game_rules.ron`.
+#[derive(Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub bind_address: SocketAddr,
+    /// Shown in the LAN server list
+    pub name: String,
+    pub max_players: usize,
+    /// Observer slots are not counted in the player limit
+    pub max_observers: usize,
+    /// Simulation frames per second
+    pub tick_rate: u32,
+    /// Seed of the map generation, only the shipped map of seed 0 can be built yet
+    pub seed: u64,
+    /// Maps played in turn, the next one is loaded at the end of every match.
+    /// These must be found in `resources/map`, the clients need the same files.
+    pub map_rotation: Vec<String>,
+    pub mode: Option<GameMode>,
+    pub respawn_time: Option<Second>,
+    pub walk_speed: Option<MeterPerSec>,
+    /// The admin console is reachable over TCP on this loopback port if set
+    pub admin_port: Option<u16>,
+    /// Whether the server answers the LAN discovery queries
+    pub lan_discovery: bool,
+    /// Clients have to send this password to join if set
+    pub password: Option<String>,
+    /// Only these players may join if set
+    pub whitelist: Option<Vec<String>>,
+    /// Bans are stored here, a relative path is relative to the resources directory
+    pub ban_file: PathBuf,
+    /// Players caught by the anti-cheat heuristics are kicked, otherwise they are only logged
+    pub kick_cheaters: bool,
+    /// Matches are recorded into replay files here if set, a relative path is relative to the resources directory
+    pub replay_dir: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            bind_address: SocketAddr::from(([0, 0, 0, 0], 5745)),
+            name: "Westiny server".to_string(),
+            max_players: 16,
+            max_observers: 4,
+            tick_rate: 60,
+            seed: 0,
+            map_rotation: vec![MAP_NAME.to_string()],
+            mode: None,
+            respawn_time: None,
+            walk_speed: None,
+            admin_port: None,
+            lan_discovery: true,
+            password: None,
+            whitelist: None,
+            ban_file: PathBuf::from("bans.ron"),
+            kick_cheaters: false,
+            replay_dir: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// Reads the config file and applies the command line options to it.
+    /// The file is `resources_dir/server.ron` unless `--config` is given,
+    /// the defaults are used if the default file does not exist.
+    pub fn from_args<I>(resources_dir: &Path, args: I) -> Result<ServerConfig, ConfigError>
+        where I: IntoIterator<Item=String>
+    {
+        let options = parse_options(args)?;
+        let mut config = match options.iter().find(|(option, _)| option == "--config") {
+            Some((_, path)) => ServerConfig::load(Path::new(path))?,
+            None => {
+                let path = resources_dir.join(SERVER_CONFIG_FILE);
+                if path.exists() {
+                    ServerConfig::load(&path)?
+                } else {
+                    log::warn!("{} not found, using the default server config", path.display());
+                    ServerConfig::default()
+                }
+            }
+        };
+
+        for (option, value) in options.iter().filter(|(option, _)| option != "--config") {
+            config.apply_option(option, value)?;
+        }
+        config.validate()?;
+        config.validate_maps(&resources_dir.join("map"))?;
+        Ok(config)
+    }
+
+    pub fn load(path: &Path) -> Result<ServerConfig, ConfigError> {
+        let config: ServerConfig = read_ron(path).map_err(|err| ConfigError::Unreadable {
+            path: path.display().to_string(),
+            message: format!("{:#}", err),
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        if self.name.trim().is_empty() {
+            return Err(ConfigError::invalid("name", "must not be empty"));
+        }
+        if self.max_players == 0 {
+            return Err(ConfigError::invalid("max_players", "at least one player slot is needed"));
+        }
+        if !(1..=240).contains(&self.tick_rate) {
+            return Err(ConfigError::invalid("tick_rate", "must be between 1 and 240"));
+        }
+        if !is_buildable(Seed(self.seed)) {
+            return Err(ConfigError::invalid("seed", format!("the map can not be built from seed {}, only seed 0 is supported", self.seed)));
+        }
+        if self.map_rotation.is_empty() {
+            return Err(ConfigError::invalid("map_rotation", "at least one map is needed"));
+        }
+        if let Some(respawn_time) = self.respawn_time {
+            validate_time(respawn_time).map_err(|err| ConfigError::invalid("respawn_time", err))?;
+        }
+        if let Some(walk_speed) = self.walk_speed {
+            validate_walk_speed(walk_speed).map_err(|err| ConfigError::invalid("walk_speed", err))?;
+        }
+        if let Some(password) = &self.password {
+            if password.is_empty() {
+                return Err(ConfigError::invalid("password", "must not be empty, remove it to make the server public"));
+            }
+        }
+        if let Some(whitelist) = &self.whitelist {
+            for name in whitelist {
+                PlayerName::validate(name)
+                    .map_err(|err| ConfigError::invalid("whitelist", format!("{}: {}", name, err)))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Every map of the rotation must be in the map directory
+    pub fn validate_maps(&self, map_dir: &Path) -> Result<(), ConfigError> {
+        let available = available_maps(map_dir).map_err(|err| ConfigError::Unreadable {
+            path: map_dir.display().to_string(),
+            message: err.to_string(),
+        })?;
+        match self.map_rotation.iter().find(|map| !available.contains(map)) {
+            Some(map) => Err(ConfigError::invalid("map_rotation", format!("unknown map {}, available maps: {}", map, available.join(", ")))),
+            None => Ok(()),
+        }
+    }
+
+    fn apply_option(&mut self, option: &str, value: &str) -> Result<(), ConfigError> {
+        fn parse<T: std::str::FromStr>(key: &str, value: &str) -> Result<T, ConfigError> {
+            value.parse().map_err(|_| ConfigError::invalid(key, format!("invalid value: {}", value)))
+        }
+
+        match option {
+            "--bind" => self.bind_address = parse("bind_address", value)?,
+            "--name" => self.name = value.to_string(),
+            "--max-players" => self.max_players = parse("max_players", value)?,
+            "--tick-rate" => self.tick_rate = parse("tick_rate", value)?,
+            "--seed" => self.seed = parse("seed", value)?,
+            "--maps" => self.map_rotation = value.split(',').map(str::trim).filter(|map| !map.is_empty()).map(String::from).collect(),
+            "--mode" => self.mode = Some(parse("mode", value)?),
+            "--respawn-time" => self.respawn_time = Some(Second(parse("respawn_time", value)?)),
+            "--walk-speed" => self.walk_speed = Some(MeterPerSec(parse("walk_speed", value)?)),
+            "--admin-port" => self.admin_port = Some(parse("admin_port", value)?),
+            "--password" => self.password = Some(value.to_string()),
+            "--kick-cheaters" => self.kick_cheaters = parse("kick_cheaters", value)?,
+            "--record" => self.replay_dir = Some(PathBuf::from(value)),
+            _ => return Err(ConfigError::UnknownOption(option.to_string())),
+        }
+        Ok(())
+    }
+
+    /// Overrides the game rules given in the config
+    pub fn apply_to(&self, rules: &mut GameRules) {
+        if let Some(mode) = self.mode {
+            rules.mode = mode;
+        }
+        if let Some(respawn_time) = self.respawn_time {
+            rules.respawn_time = respawn_time;
+        }
+        if let Some(walk_speed) = self.walk_speed {
+            rules.walk_speed = walk_speed;
+        }
+    }
+}
+
+/// Pairs the options with their values, `--help` is handled before by the caller
+fn parse_options<I>(args: I) -> Result<Vec<(String, String)>, ConfigError>
+    where I: IntoIterator<Item=String>
+{
+    let mut options = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(option) = args.next() {
+        if !option.starts_with("--") {
+            return Err(ConfigError::UnknownOption(option));
+        }
+        match args.next() {
+            Some(value) => options.push((option, value)),
+            None => return Err(ConfigError::MissingValue(option)),
+        }
+    }
+    Ok(options)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
+    }
+
+    fn resources_dir() -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("../resources")
+    }
+
+    fn write_config(file_name: &str, content: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(file_name);
+        std::fs::write(&path, content).unwrap();
+        path
+    }
+
+    #[test]
+    fn options_override_the_defaults() {
+        let path = write_config("westiny_defaults.ron", "()");
+        let config = ServerConfig::from_args(&resources_dir(), args(&[
+            "--config", path.to_str().unwrap(),
+            "--bind", "127.0.0.1:6000",
+            "--max-players", "8",
+            "--tick-rate", "30",
+            "--maps", "canyon, rust2",
+            "--mode", "CaptureTheFlag",
+            "--respawn-time", "2.5",
+            "--walk-speed", "5",
+            "--record", "replays",
+        ])).unwrap();
+
+        assert_eq!(config.bind_address, SocketAddr::from(([127, 0, 0, 1], 6000)));
+        assert_eq!(config.max_players, 8);
+        assert_eq!(config.tick_rate, 30);
+        assert_eq!(config.map_rotation, vec!["canyon".to_string(), "rust2".to_string()]);
+        assert_eq!(config.replay_dir, Some(PathBuf::from("replays")));
+
+        let mut rules = GameRules::default();
+        config.apply_to(&mut rules);
+        assert_eq!(rules.mode, GameMode::CaptureTheFlag);
+        assert_eq!(rules.respawn_time, Second(2.5));
+        assert_eq!(rules.walk_speed, MeterPerSec(5.0));
+    }
+
+    #[test]
+    fn options_override_the_config_file() {
+        let path = write_config("westiny_options_override.ron", "(max_players: 4, tick_rate: 20)");
+        let config = ServerConfig::from_args(&resources_dir(), args(&[
+            "--config", path.to_str().unwrap(),
+            "--tick-rate", "50",
+        ])).unwrap();
+
+        assert_eq!(config.max_players, 4);
+        assert_eq!(config.tick_rate, 50);
+        assert_eq!(config.name, ServerConfig::default().name);
+    }
+
+    #[test]
+    fn errors_name_the_offending_key() {
+        let invalid = |options: &[&str]| ServerConfig::from_args(&resources_dir(), args(options)).unwrap_err();
+
+        assert_eq!(invalid(&["--max-players", "0"]), ConfigError::invalid("max_players", "at least one player slot is needed"));
+        assert!(matches!(invalid(&["--tick-rate", "fast"]), ConfigError::InvalidValue { key, .. } if key == "tick_rate"));
+        assert!(matches!(invalid(&["--maps", "rust2,moon"]), ConfigError::InvalidValue { key, .. } if key == "map_rotation"));
+        assert!(matches!(invalid(&["--maps", ","]), ConfigError::InvalidValue { key, .. } if key == "map_rotation"));
+        assert!(matches!(invalid(&["--seed", "7"]), ConfigError::InvalidValue { key, .. } if key == "seed"));
+        assert!(matches!(invalid(&["--walk-speed", "-1"]), ConfigError::InvalidValue { key, .. } if key == "walk_speed"));
+        assert!(matches!(invalid(&["--respawn-time", "inf"]), ConfigError::InvalidValue { key, .. } if key == "respawn_time"));
+        assert_eq!(invalid(&["--gravity", "1"]), ConfigError::UnknownOption("--gravity".to_string()));
+        assert_eq!(invalid(&["--bind"]), ConfigError::MissingValue("--bind".to_string()));
+        assert!(matches!(invalid(&["--password", ""]), ConfigError::InvalidValue { key, .. } if key == "password"));
+    }
+
+    #[test]
+    fn unknown_keys_of_the_file_are_reported() {
+        let path = write_config("westiny_unknown_key.ron", "(max_player: 4)");
+        let err = ServerConfig::load(&path).unwrap_err();
+        assert!(err.to_string().contains("max_player"), "{}", err);
+    }
+
+    #[test]
+    fn whitelisted_names_are_validated() {
+        let path = write_config("westiny_whitelist.ron", "(whitelist: Some([\"Clint\", \"Tuco:)\"]))");
+        let err = ServerConfig::load(&path).unwrap_err();
+        assert!(matches!(err, ConfigError::InvalidValue { key, .. } if key == "whitelist"));
+    }
+
+    #[test]
+    fn shipped_config_is_valid() {
+        let config = ServerConfig::load(&resources_dir().join(SERVER_CONFIG_FILE)).unwrap();
+        assert!(config.validate_maps(&resources_dir().join("map")).is_ok());
+    }
+}
@@ -1,23 +1,96 @@
 use amethyst::prelude::*;
 use amethyst::core::Time;
-use crate::resources::{ClientRegistry, NetworkIdSupplier};
+use crate::resources::{AccessPolicy, BanList, ClientRegistry, DestroyedObjects, GameMode, GameRules, MapRotation, MatchState, NetworkIdSupplier, ReplayEvent, Scoreboard, ServerConfig, ShutdownRequest};
+use crate::components::{Client, Destructible, EntityType, Flag, Health, NetworkId};
 
 use log::info;
 use std::path::PathBuf;
 use derive_new::new;
-use westiny_common::resources::map::build_map;
-use westiny_common::resources::{Seed, weapon::GunResource};
+use amethyst::ecs::{Entity, Join};
+use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
+use amethyst::shrev::EventChannel;
+use westiny_common::resources::map::{available_maps, build_map, MapMetadata};
+use westiny_common::network::{MapChange, PacketType};
+use westiny_common::serialize;
+use westiny_common::entities::spawn_flag;
+use amethyst::core::Transform;
+use amethyst::core::math::Vector2;
+use westiny_common::resources::{Seed, MeleeDetails, weapon::GunResource};
 use westiny_common::events::WestinyEvent;
 
 #[derive(new)]
 pub struct ServerState {
     resources: PathBuf,
+    config: ServerConfig,
+    ban_list: BanList,
 }
 
 impl ServerState {
-    fn place_objects(&self, world: &mut World, seed: Seed) {
-        build_map(world, seed, &self.resources.join("map"))
+    fn place_objects(&self, world: &mut World, map: &str, seed: Seed) {
+        let map_dir = self.resources.join("map");
+        let entities = build_map(world, map, seed, &map_dir)
             .expect("Map could not be created");
+        let metadata = MapMetadata::load(&map_dir, map, seed)
+            .expect("Map metadata could not be loaded");
+
+        world.register::<Destructible>();
+        {
+            let network_ids = world.read_storage::<NetworkId>();
+            let mut healths = world.write_storage::<Health>();
+            let mut destructibles = world.write_storage::<Destructible>();
+
+            for (entity, _) in entities {
+                let details = match network_ids.get(entity).map(|network_id| network_id.entity_type) {
+                    Some(EntityType::Barrel) => &metadata.barrel,
+                    Some(EntityType::ExplosiveBarrel) => &metadata.explosive_barrel,
+                    _ => &None,
+                };
+
+                if let Some(details) = details {
+                    healths.insert(entity, Health(details.health)).expect("Unable to add health to map object");
+                    destructibles.insert(entity, Destructible::new(details.clone())).expect("Unable to make map object destructible");
+                }
+            }
+        }
+
+        if world.read_resource::<GameRules>().mode == GameMode::CaptureTheFlag {
+            world.register::<Flag>();
+            for base in metadata.flag_bases.iter() {
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(base.x.into_pixel(), base.y.into_pixel(), 0.0);
+                let network_id = world.write_resource::<NetworkIdSupplier>().next(EntityType::Flag);
+                spawn_flag(transform, network_id, world.create_entity())
+                    .with(Flag::new(base.team, Vector2::new(base.x, base.y)))
+                    .build();
+            }
+        }
+
+        world.insert(metadata);
+    }
+
+    /// Everything but the players is removed, then the map is built again from the files of the new map.
+    /// The clients are told to do the same, because the map objects are numbered by the map.
+    fn change_map(&self, world: &mut World, map: &str) {
+        // Entities created lazily in the last frame are removed too
+        world.maintain();
+        let map_entities: Vec<Entity> = (&world.entities(), &world.read_storage::<NetworkId>(), !&world.read_storage::<Client>()).join()
+            .map(|(entity, _, _)| entity)
+            .collect();
+        world.delete_entities(&map_entities).expect("Unable to delete the objects of the previous map");
+        world.write_resource::<DestroyedObjects>().0.clear();
+
+        let seed = *world.read_resource::<Seed>();
+        self.place_objects(world, map, seed);
+        log::info!("Map {} loaded", map);
+
+        let packet = PacketType::MapChange(MapChange { map: map.to_string(), seed });
+        let msg = serialize(&packet).expect("MapChange could not be serialized");
+        let client_registry = world.read_resource::<ClientRegistry>();
+        let mut net = world.write_resource::<TransportResource>();
+        for handle in client_registry.get_clients() {
+            net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick);
+        }
+        world.write_resource::<EventChannel<ReplayEvent>>().single_write(ReplayEvent(packet));
     }
 }
 
@@ -39,24 +112,46 @@ fn log_clients(time: &Time, registry: &ClientRegistry) {
 
 impl State<GameData<'static, 'static>, WestinyEvent> for ServerState {
     fn on_start(&mut self, data: StateData<'_, GameData<'static, 'static>>) {
-        const MAGIC_SEED: u64 = 0;
-
-        let seed = Seed(MAGIC_SEED);
-        data.world.insert(ClientRegistry::new(16));
+        let seed = Seed(self.config.seed);
+        data.world.insert(ClientRegistry::new(self.config.max_players).with_observer_slots(self.config.max_observers));
         data.world.insert(NetworkIdSupplier::new());
+        data.world.insert(DestroyedObjects::default());
+        data.world.insert(MatchState::default());
+        data.world.insert(Scoreboard::default());
 
         data.world.insert(seed);
+        let maps = available_maps(&self.resources.join("map")).expect("Map directory could not be read");
+        let map_rotation = MapRotation::new(self.config.map_rotation.clone(), maps);
+        let first_map = map_rotation.current().to_string();
+        data.world.insert(map_rotation);
 
         GunResource::initialize(data.world, self.resources.clone()).expect("Unable to initialize gun assets");
+        MeleeDetails::initialize(data.world, self.resources.clone()).expect("Unable to initialize melee assets");
+        GameRules::initialize(data.world, self.resources.clone()).expect("Unable to initialize game rules");
+        self.config.apply_to(&mut data.world.write_resource::<GameRules>());
+        data.world.insert(self.config.clone());
+
+        data.world.insert(std::mem::take(&mut self.ban_list));
+        data.world.insert(AccessPolicy::new(self.config.password.clone(), self.config.whitelist.clone()));
 
-        self.place_objects(data.world, seed);
+        self.place_objects(data.world, &first_map, seed);
     }
 
     fn update(&mut self, data: StateData<'_, GameData<'static, 'static>>) -> Trans<GameData<'static, 'static>, WestinyEvent> {
         let time = *data.world.fetch::<Time>();
         log_fps(&time);
+        // One more frame is run after the shutdown command, so the clients are notified
+        let shutdown = data.world.try_fetch::<ShutdownRequest>().map_or(false, |request| request.0);
         data.data.update(&data.world);
+        let requested_map = data.world.write_resource::<MapRotation>().take_requested();
+        if let Some(map) = requested_map {
+            self.change_map(data.world, &map);
+        }
         log_clients(&time, &data.world.fetch::<ClientRegistry>());
+        if shutdown {
+            log::info!("Shutting down");
+            return Trans::Quit;
+        }
         Trans::None
     }
 }
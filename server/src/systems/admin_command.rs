@@ -0,0 +1,153 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, Write, WriteExpect},
+    network::simulation::{DeliveryRequirement, TransportResource, UrgencyRequirement},
+    shrev::{EventChannel, ReaderId},
+};
+use derive_new::new;
+use std::net::SocketAddr;
+
+use crate::resources::{
+    AdminCommand, AdminCommandEvent, BanList, ClientNetworkEvent, ClientRegistry, ConsoleReply, GameRules, MapRotation, ReplayEvent, ShutdownRequest, StreamId,
+};
+use westiny_common::network::{ChatMessage, PacketType};
+use westiny_common::{serialize, PlayerName};
+
+/// Carries out the commands typed into the admin console
+#[derive(SystemDesc, new)]
+#[system_desc(name(AdminCommandSystemDesc))]
+pub struct AdminCommandSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<AdminCommandEvent>,
+}
+
+impl<'s> System<'s> for AdminCommandSystem {
+    type SystemData = (
+        Read<'s, EventChannel<AdminCommandEvent>>,
+        WriteExpect<'s, ClientRegistry>,
+        Write<'s, EventChannel<ClientNetworkEvent>>,
+        WriteExpect<'s, TransportResource>,
+        WriteExpect<'s, GameRules>,
+        WriteExpect<'s, MapRotation>,
+        Write<'s, BanList>,
+        Write<'s, ShutdownRequest>,
+        Write<'s, EventChannel<ReplayEvent>>,
+    );
+
+    fn run(&mut self, (commands, mut client_registry, mut client_net_ec, mut net, mut rules, mut map_rotation, mut ban_list, mut shutdown, mut replay_channel): Self::SystemData) {
+        for AdminCommandEvent { command, reply } in commands.read(&mut self.reader) {
+            match command {
+                AdminCommand::Status => {
+                    reply.send(format!(
+                        "Map: {}, mode: {:?}, players: {}/{}",
+                        map_rotation.current(), rules.mode, client_registry.player_count(), client_registry.max_slots()
+                    ));
+                    reply.send(client_registry.to_string());
+                }
+                AdminCommand::Kick(name) => {
+                    let address = client_registry.get_clients().iter()
+                        .find(|handle| handle.player_name.0.eq_ignore_ascii_case(name))
+                        .map(|handle| handle.addr);
+                    match address {
+                        Some(address) => kick(&address, "Kicked by the admin", &mut client_registry, &mut client_net_ec, &mut net, reply),
+                        None => reply.send(format!("No player named {}", name)),
+                    }
+                }
+                AdminCommand::Ban(target) => {
+                    if !ban_list.ban(target) {
+                        reply.send(format!("{} is already banned", target));
+                        continue;
+                    }
+                    save_ban_list(&ban_list, reply);
+                    reply.send(format!("{} is banned", target));
+                    let addresses: Vec<_> = client_registry.get_clients().iter()
+                        .filter(|handle| ban_list.is_banned(&handle.addr.ip(), &handle.requested_name().0))
+                        .map(|handle| handle.addr)
+                        .collect();
+                    for address in addresses {
+                        kick(&address, "Banned by the admin", &mut client_registry, &mut client_net_ec, &mut net, reply);
+                    }
+                }
+                AdminCommand::Unban(target) => {
+                    if ban_list.unban(target) {
+                        save_ban_list(&ban_list, reply);
+                        reply.send(format!("{} is unbanned", target));
+                    } else {
+                        reply.send(format!("{} is not banned", target));
+                    }
+                }
+                // The map is loaded after this frame and a new round starts on it
+                AdminCommand::Map(name) => match map_rotation.request(name) {
+                    Ok(()) => reply.send(format!("Loading {}", name)),
+                    Err(err) => reply.send(err),
+                },
+                AdminCommand::Set { name, value } => match rules.set(name, value) {
+                    Ok(()) => reply.send(format!("{} is set to {}", name, value)),
+                    Err(err) => reply.send(err),
+                },
+                AdminCommand::Say(text) => {
+                    let message = ChatMessage { sender: PlayerName("Server".to_string()), text: text.clone(), team_only: false };
+                    let packet = PacketType::Chat(message);
+                    let msg = serialize(&packet).expect("ChatMessage could not be serialized");
+                    for handle in client_registry.get_clients() {
+                        net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::ReliableOrdered(StreamId::Chat.into()), UrgencyRequirement::OnTick);
+                    }
+                    replay_channel.single_write(ReplayEvent(packet));
+                    reply.send("Message sent");
+                }
+                AdminCommand::Shutdown => {
+                    let msg = serialize(&PacketType::Kicked { reason: "Server is shutting down".to_string() })
+                        .expect("Kicked could not be serialized");
+                    for handle in client_registry.get_clients() {
+                        net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::Immediate);
+                    }
+                    shutdown.0 = true;
+                    reply.send("Shutting down");
+                }
+            }
+        }
+    }
+}
+
+fn save_ban_list(ban_list: &BanList, reply: &ConsoleReply) {
+    if let Err(err) = ban_list.save() {
+        reply.send(format!("Ban list could not be saved, the change is lost on restart: {}", err));
+    }
+}
+
+fn kick(
+    address: &SocketAddr,
+    reason: &str,
+    client_registry: &mut ClientRegistry,
+    client_net_ec: &mut EventChannel<ClientNetworkEvent>,
+    net: &mut TransportResource,
+    reply: &ConsoleReply,
+) {
+    let player_name = match client_registry.find_by_addr(address) {
+        Some(handle) => handle.player_name.clone(),
+        None => return,
+    };
+    match kick_client(address, reason, client_registry, client_net_ec, net) {
+        Ok(()) => reply.send(format!("{} is kicked", player_name)),
+        Err(err) => reply.send(format!("{} could not be kicked: {}", player_name, err)),
+    }
+}
+
+/// The client is told the reason and its character is removed as if it disconnected
+pub(super) fn kick_client(
+    address: &SocketAddr,
+    reason: &str,
+    client_registry: &mut ClientRegistry,
+    client_net_ec: &mut EventChannel<ClientNetworkEvent>,
+    net: &mut TransportResource,
+) -> anyhow::Result<()> {
+    let msg = serialize(&PacketType::Kicked { reason: reason.to_string() })?;
+    net.send_with_requirements(*address, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::Immediate);
+
+    let player_name = client_registry.find_by_addr(address)
+        .map(|handle| handle.player_name.clone())
+        .ok_or_else(|| anyhow::anyhow!("No client from {}", address))?;
+    let id = client_registry.remove(address)?;
+    client_net_ec.single_write(ClientNetworkEvent::ClientDisconnected(id, player_name));
+    Ok(())
+}
@@ -0,0 +1,123 @@
+use amethyst::ecs::{System, Write};
+use amethyst::shrev::EventChannel;
+use std::io::{self, BufRead, BufReader, Write as IoWrite};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+
+use crate::resources::{AdminCommand, AdminCommandEvent, ConsoleReply};
+
+/// A line typed into one of the consoles
+struct ConsoleLine {
+    line: String,
+    reply: ConsoleReply,
+}
+
+/// Reads the admin commands from the standard input and, if a port is given, from TCP connections
+/// on the loopback interface. The commands are passed to the systems through the `AdminCommandEvent` channel.
+pub struct AdminConsoleSystem {
+    lines: mpsc::Receiver<ConsoleLine>,
+}
+
+impl AdminConsoleSystem {
+    pub fn new(tcp_port: Option<u16>) -> Self {
+        let (sender, lines) = mpsc::channel();
+        spawn_stdin_reader(sender.clone());
+
+        if let Some(port) = tcp_port {
+            let address = SocketAddr::from(([127, 0, 0, 1], port));
+            match TcpListener::bind(address) {
+                Ok(listener) => {
+                    log::info!("Admin console is listening on {}", address);
+                    thread::spawn(move || accept_connections(listener, sender));
+                }
+                Err(err) => log::warn!("Admin console could not listen on {}: {}", address, err),
+            }
+        }
+
+        AdminConsoleSystem { lines }
+    }
+}
+
+impl<'s> System<'s> for AdminConsoleSystem {
+    type SystemData = Write<'s, EventChannel<AdminCommandEvent>>;
+
+    fn run(&mut self, mut command_channel: Self::SystemData) {
+        while let Ok(ConsoleLine { line, reply }) = self.lines.try_recv() {
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match line.parse::<AdminCommand>() {
+                Ok(command) => {
+                    log::info!("Admin command: {:?}", command);
+                    command_channel.single_write(AdminCommandEvent { command, reply });
+                }
+                Err(err) => reply.send(err),
+            }
+        }
+    }
+}
+
+fn spawn_stdin_reader(lines: mpsc::Sender<ConsoleLine>) {
+    let (reply_sender, replies) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for reply in replies {
+            println!("{}", reply);
+        }
+    });
+
+    thread::spawn(move || {
+        let reply = ConsoleReply::new(reply_sender);
+        for line in io::stdin().lock().lines() {
+            let line = match line {
+                Ok(line) => line,
+                Err(err) => {
+                    log::warn!("Admin console could not read the standard input: {}", err);
+                    return;
+                }
+            };
+            if lines.send(ConsoleLine { line, reply: reply.clone() }).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+fn accept_connections(listener: TcpListener, lines: mpsc::Sender<ConsoleLine>) {
+    for stream in listener.incoming() {
+        match stream {
+            Ok(stream) => {
+                let lines = lines.clone();
+                thread::spawn(move || {
+                    if let Err(err) = serve_connection(stream, lines) {
+                        log::warn!("Admin console connection failed: {}", err);
+                    }
+                });
+            }
+            Err(err) => log::warn!("Admin console connection could not be accepted: {}", err),
+        }
+    }
+}
+
+/// Every line of the connection is a command, the answers are written back
+fn serve_connection(stream: TcpStream, lines: mpsc::Sender<ConsoleLine>) -> io::Result<()> {
+    log::info!("Admin console connected from {}", stream.peer_addr()?);
+    let mut writer = stream.try_clone()?;
+    let (reply_sender, replies) = mpsc::channel::<String>();
+    thread::spawn(move || {
+        for reply in replies {
+            if writeln!(writer, "{}", reply).is_err() {
+                return;
+            }
+        }
+    });
+
+    let reply = ConsoleReply::new(reply_sender);
+    for line in BufReader::new(stream).lines() {
+        if lines.send(ConsoleLine { line: line?, reply: reply.clone() }).is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
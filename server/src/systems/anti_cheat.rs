@@ -0,0 +1,355 @@
+use amethyst::{
+    core::{math::Vector2, Time, Transform},
+    derive::SystemDesc,
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, SystemData, Write, WriteExpect},
+    network::simulation::TransportResource,
+    shrev::{EventChannel, ReaderId},
+};
+use derive_new::new;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::Duration;
+
+use crate::components::{Client, InputFlags, weapon::Holster};
+use crate::resources::{ClientID, ClientNetworkEvent, ClientRegistry, NetworkCommand, ServerConfig};
+use super::admin_command::kick_client;
+use westiny_common::metric_dimension::length::Meter;
+use westiny_common::metric_dimension::to_meter_vec;
+
+const DETECTION_WINDOW: Duration = Duration::from_secs(1);
+/// Clicking faster than this is taken as an auto clicker
+const MAX_SHOOT_PRESSES_PER_SEC: usize = 20;
+/// Switching faster than this is taken as a script bypassing the fire rate
+const MAX_WEAPON_SWITCHES_PER_SEC: usize = 10;
+/// Reloads interrupted by weapon switches within `RELOAD_CANCEL_WINDOW`
+const MAX_RELOAD_CANCELS: usize = 3;
+const RELOAD_CANCEL_WINDOW: Duration = Duration::from_secs(5);
+/// Moving the cursor faster than this while shooting is taken as aim snapping [m/s]
+const MAX_AIM_SPEED: f32 = 250.0;
+/// Shorter cursor moves are not counted as snaps, however fast they are
+const MIN_SNAP_DISTANCE: Meter = Meter(4.0);
+/// The cursor is not followed this close to the player, nothing is aimed at there
+const MIN_AIM_DISTANCE: Meter = Meter(1.5);
+/// Clients send their input once in a frame, packets arriving closer to each other are taken this far apart
+const INPUT_INTERVAL: Duration = Duration::from_millis(16);
+/// Players are kicked after this many suspicious events within `STRIKE_LIFETIME`
+/// if `kick_cheaters` is set in the server config
+const MAX_STRIKES: usize = 5;
+const STRIKE_LIFETIME: Duration = Duration::from_secs(600);
+
+/// Behaviour hardly possible for a human player
+#[derive(Clone, Debug, PartialEq)]
+pub enum Suspicion {
+    ClickRate(usize),
+    WeaponSwitchRate(usize),
+    ReloadCancels(usize),
+    AimSnap { meters_per_sec: f32 },
+}
+
+impl fmt::Display for Suspicion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Suspicion::ClickRate(count) => write!(f, "pressed shoot {} times in a second", count),
+            Suspicion::WeaponSwitchRate(count) => write!(f, "switched weapons {} times in a second", count),
+            Suspicion::ReloadCancels(count) => write!(f, "interrupted {} reloads in {} seconds", count, RELOAD_CANCEL_WINDOW.as_secs()),
+            Suspicion::AimSnap { meters_per_sec } => write!(f, "moved the cursor {:.0} m/s while shooting", meters_per_sec),
+        }
+    }
+}
+
+/// Times of the events in a sliding window
+#[derive(Default)]
+struct EventWindow {
+    times: VecDeque<Duration>,
+}
+
+impl EventWindow {
+    /// Returns the number of events in the window ending now, the new one included
+    fn record(&mut self, now: Duration, length: Duration) -> usize {
+        self.times.push_back(now);
+        self.count(now, length)
+    }
+
+    /// Returns the number of events in the window ending now
+    fn count(&mut self, now: Duration, length: Duration) -> usize {
+        while self.times.front().map_or(false, |&time| time + length <= now) {
+            self.times.pop_front();
+        }
+        self.times.len()
+    }
+
+    /// The window is cleared after a suspicion, so a continued abuse is reported again
+    fn over_limit(&mut self, count: usize, limit: usize) -> bool {
+        if count > limit {
+            self.times.clear();
+            true
+        } else {
+            false
+        }
+    }
+}
+
+/// Watches the input stream of a single player
+pub struct PlayerMonitor {
+    last_flags: InputFlags,
+    shoot_presses: EventWindow,
+    switches: EventWindow,
+    reload_cancels: EventWindow,
+    /// The cursor relative to the player in meters, and the time it was received
+    last_aim: Option<(Vector2<f32>, Duration)>,
+    strikes: EventWindow,
+}
+
+impl Default for PlayerMonitor {
+    fn default() -> Self {
+        PlayerMonitor {
+            last_flags: InputFlags::empty(),
+            shoot_presses: EventWindow::default(),
+            switches: EventWindow::default(),
+            reload_cancels: EventWindow::default(),
+            last_aim: None,
+            strikes: EventWindow::default(),
+        }
+    }
+}
+
+impl PlayerMonitor {
+    /// Checks every input packet of the player. `reloading` tells whether a switch now would interrupt a reload.
+    pub fn observe_input(&mut self, flags: InputFlags, reloading: bool, now: Duration) -> Vec<Suspicion> {
+        let pressed = flags & !self.last_flags;
+        self.last_flags = flags;
+
+        let mut suspicions = Vec::new();
+        if pressed.intersects(InputFlags::SHOOT) {
+            let count = self.shoot_presses.record(now, DETECTION_WINDOW);
+            if self.shoot_presses.over_limit(count, MAX_SHOOT_PRESSES_PER_SEC) {
+                suspicions.push(Suspicion::ClickRate(count));
+            }
+        }
+
+        let selections = InputFlags::SELECT1 | InputFlags::SELECT2 | InputFlags::SELECT3 | InputFlags::SELECT4 | InputFlags::SELECT5;
+        if pressed.intersects(selections) {
+            let count = self.switches.record(now, DETECTION_WINDOW);
+            if self.switches.over_limit(count, MAX_WEAPON_SWITCHES_PER_SEC) {
+                suspicions.push(Suspicion::WeaponSwitchRate(count));
+            }
+            if reloading {
+                let cancels = self.reload_cancels.record(now, RELOAD_CANCEL_WINDOW);
+                if self.reload_cancels.over_limit(cancels, MAX_RELOAD_CANCELS) {
+                    suspicions.push(Suspicion::ReloadCancels(cancels));
+                }
+            }
+        }
+
+        for _ in &suspicions {
+            self.strikes.record(now, STRIKE_LIFETIME);
+        }
+        suspicions
+    }
+
+    /// Checks the cursor movement of every input packet. `cursor_offset` is the cursor relative to
+    /// the player in meters, the camera follows the player so walking and respawning do not move it.
+    pub fn observe_aim(&mut self, cursor_offset: Vector2<f32>, shooting: bool, now: Duration) -> Option<Suspicion> {
+        if cursor_offset.magnitude() < MIN_AIM_DISTANCE.0 {
+            self.last_aim = None;
+            return None;
+        }
+        let (last_offset, last_time) = self.last_aim.replace((cursor_offset, now))?;
+        // Jitter may deliver the packets of more frames at once
+        let elapsed = now.checked_sub(last_time).unwrap_or_default().max(INPUT_INTERVAL).as_secs_f32();
+
+        let distance = (cursor_offset - last_offset).magnitude();
+        let speed = distance / elapsed;
+        if shooting && distance >= MIN_SNAP_DISTANCE.0 && speed > MAX_AIM_SPEED {
+            self.strikes.record(now, STRIKE_LIFETIME);
+            Some(Suspicion::AimSnap { meters_per_sec: speed })
+        } else {
+            None
+        }
+    }
+
+    /// The suspicious events of the last `STRIKE_LIFETIME`
+    pub fn strikes(&mut self, now: Duration) -> usize {
+        self.strikes.count(now, STRIKE_LIFETIME)
+    }
+}
+
+/// Logs the players behaving suspiciously, and kicks them if the server is configured so
+#[derive(SystemDesc, new)]
+#[system_desc(name(AntiCheatSystemDesc))]
+pub struct AntiCheatSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<NetworkCommand>,
+
+    #[system_desc(skip)]
+    #[new(default)]
+    monitors: HashMap<ClientID, PlayerMonitor>,
+}
+
+impl<'s> System<'s> for AntiCheatSystem {
+    type SystemData = (
+        Read<'s, EventChannel<NetworkCommand>>,
+        ReadStorage<'s, Client>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Holster>,
+        Read<'s, Time>,
+        ReadExpect<'s, ServerConfig>,
+        WriteExpect<'s, ClientRegistry>,
+        Write<'s, EventChannel<ClientNetworkEvent>>,
+        WriteExpect<'s, TransportResource>,
+    );
+
+    fn run(&mut self, (command_channel, clients, transforms, holsters, time, config, mut client_registry, mut client_net_ec, mut net): Self::SystemData) {
+        let now = time.absolute_time();
+        self.monitors.retain(|id, _| client_registry.find_client(*id).is_some());
+
+        let holster_of: HashMap<ClientID, &Holster> = (&clients, &holsters).join()
+            .map(|(client, holster)| (client.id, holster))
+            .collect();
+        let position_of: HashMap<ClientID, Vector2<Meter>> = (&clients, &transforms).join()
+            .map(|(client, transform)| (client.id, to_meter_vec(transform.translation().xy())))
+            .collect();
+
+        let mut suspicions = Vec::new();
+        for command in command_channel.read(&mut self.reader) {
+            if let NetworkCommand::Input { id, input } = command {
+                let reloading = holster_of.get(id).map_or(false, |holster| {
+                    holster.active_gun().map_or(false, |gun| gun.reload_started_at.is_some())
+                        && input.selected_slot().map_or(false, |slot| slot != holster.active_slot())
+                });
+                let monitor = self.monitors.entry(*id).or_default();
+                suspicions.extend(monitor.observe_input(input.flags, reloading, now).into_iter().map(|suspicion| (*id, suspicion)));
+
+                // Observers and eliminated players have no position to aim from
+                if let Some(position) = position_of.get(id) {
+                    let cursor_offset = Vector2::new(input.cursor.x.0 - position.x.0, input.cursor.y.0 - position.y.0);
+                    let shooting = input.flags.intersects(InputFlags::SHOOT);
+                    suspicions.extend(monitor.observe_aim(cursor_offset, shooting, now).map(|suspicion| (*id, suspicion)));
+                }
+            }
+        }
+
+        for (id, suspicion) in suspicions {
+            let handle = match client_registry.find_client(id) {
+                Some(handle) => handle,
+                None => continue,
+            };
+            let strikes = self.monitors.get_mut(&id).map_or(0, |monitor| monitor.strikes(now));
+            log::warn!("[anti-cheat] {} ({}) {}, strikes: {}", handle.player_name, handle.addr, suspicion, strikes);
+
+            if config.kick_cheaters && strikes >= MAX_STRIKES {
+                let address = handle.addr;
+                match kick_client(&address, "Kicked for suspicious behaviour", &mut client_registry, &mut client_net_ec, &mut net) {
+                    Ok(()) => log::warn!("[anti-cheat] {} is kicked", address),
+                    Err(err) => log::error!("[anti-cheat] {} could not be kicked: {}", address, err),
+                }
+                self.monitors.remove(&id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn at_millis(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    /// Feeds the flags to the monitor every `interval_ms`, like a client sending its input in every frame
+    fn feed(monitor: &mut PlayerMonitor, stream: &[InputFlags], interval_ms: u64, reloading: bool) -> Vec<Suspicion> {
+        stream.iter().enumerate()
+            .flat_map(|(frame, flags)| monitor.observe_input(*flags, reloading, at_millis(frame as u64 * interval_ms)))
+            .collect()
+    }
+
+    #[test]
+    fn human_clicking_is_not_suspicious() {
+        let mut monitor = PlayerMonitor::default();
+        // 8 clicks per second for two seconds at 60 FPS
+        let stream: Vec<_> = (0..120)
+            .map(|frame| if frame % 8 < 4 { InputFlags::SHOOT } else { InputFlags::empty() })
+            .collect();
+        assert!(feed(&mut monitor, &stream, 16, false).is_empty());
+        assert_eq!(monitor.strikes(at_millis(2000)), 0);
+    }
+
+    #[test]
+    fn toggling_shoot_in_every_packet_is_suspicious() {
+        let mut monitor = PlayerMonitor::default();
+        let stream: Vec<_> = (0..120)
+            .map(|frame| if frame % 2 == 0 { InputFlags::SHOOT } else { InputFlags::empty() })
+            .collect();
+        let suspicions = feed(&mut monitor, &stream, 16, false);
+        assert!(!suspicions.is_empty());
+        assert!(suspicions.iter().all(|suspicion| matches!(suspicion, Suspicion::ClickRate(_))));
+        assert_eq!(monitor.strikes(at_millis(2000)), suspicions.len());
+
+        // Strikes are forgotten after a while
+        assert_eq!(monitor.strikes(at_millis(2000) + STRIKE_LIFETIME), 0);
+    }
+
+    #[test]
+    fn switch_and_shoot_script_is_suspicious() {
+        let mut monitor = PlayerMonitor::default();
+        let stream: Vec<_> = (0..60)
+            .map(|frame| match frame % 4 {
+                0 => InputFlags::SELECT1,
+                1 => InputFlags::SELECT1 | InputFlags::SHOOT,
+                2 => InputFlags::SELECT2,
+                _ => InputFlags::SELECT2 | InputFlags::SHOOT,
+            })
+            .collect();
+        let suspicions = feed(&mut monitor, &stream, 16, false);
+        assert!(suspicions.iter().any(|suspicion| matches!(suspicion, Suspicion::WeaponSwitchRate(_))));
+        assert!(suspicions.iter().any(|suspicion| matches!(suspicion, Suspicion::ClickRate(_))));
+    }
+
+    #[test]
+    fn repeated_reload_cancels_are_suspicious() {
+        let mut monitor = PlayerMonitor::default();
+        let stream: Vec<_> = (0..8)
+            .map(|frame| if frame % 2 == 0 { InputFlags::SELECT2 } else { InputFlags::empty() })
+            .collect();
+        assert!(feed(&mut monitor, &stream, 500, false).is_empty(), "switching without reloading is fine");
+
+        let mut monitor = PlayerMonitor::default();
+        let suspicions = feed(&mut monitor, &stream, 500, true);
+        assert_eq!(suspicions, vec![Suspicion::ReloadCancels(MAX_RELOAD_CANCELS + 1)]);
+    }
+
+    #[test]
+    fn snapping_aim_while_shooting_is_suspicious() {
+        let mut monitor = PlayerMonitor::default();
+        assert_eq!(monitor.observe_aim(Vector2::new(5.0, 0.0), true, at_millis(0)), None);
+        // Smooth aiming
+        assert_eq!(monitor.observe_aim(Vector2::new(5.0, 0.5), true, at_millis(16)), None);
+        // Turning around without shooting
+        assert_eq!(monitor.observe_aim(Vector2::new(-5.0, 0.5), false, at_millis(32)), None);
+
+        let suspicion = monitor.observe_aim(Vector2::new(5.0, 0.5), true, at_millis(48));
+        assert!(matches!(suspicion, Some(Suspicion::AimSnap { meters_per_sec }) if meters_per_sec > 600.0));
+        assert_eq!(monitor.strikes(at_millis(48)), 1);
+    }
+
+    #[test]
+    fn cursor_next_to_the_player_is_not_followed() {
+        let mut monitor = PlayerMonitor::default();
+        // Crossing the player with the cursor turns the aim around
+        assert_eq!(monitor.observe_aim(Vector2::new(1.0, 0.0), true, at_millis(0)), None);
+        assert_eq!(monitor.observe_aim(Vector2::new(-1.0, 0.0), true, at_millis(16)), None);
+        assert_eq!(monitor.observe_aim(Vector2::new(-2.0, 0.0), true, at_millis(32)), None);
+        assert_eq!(monitor.strikes(at_millis(32)), 0);
+    }
+
+    #[test]
+    fn packets_arriving_together_are_timed_by_the_input_interval() {
+        let mut monitor = PlayerMonitor::default();
+        // Three frames of quick aiming delivered in the same server frame
+        for x in [2.0, 3.5, 5.0, 6.5].iter() {
+            assert_eq!(monitor.observe_aim(Vector2::new(*x, 0.0), true, at_millis(100)), None);
+        }
+        assert_eq!(monitor.strikes(at_millis(100)), 0);
+    }
+}
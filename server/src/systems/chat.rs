@@ -0,0 +1,158 @@
+use amethyst::{
+    core::Time,
+    derive::SystemDesc,
+    ecs::{Join, Read, ReadExpect, ReadStorage, System, SystemData, Write, WriteExpect},
+    network::simulation::{DeliveryRequirement, TransportResource, UrgencyRequirement},
+    shrev::{EventChannel, ReaderId},
+};
+use derive_new::new;
+use std::collections::{HashMap, VecDeque};
+use std::time::Duration;
+
+use crate::components::{Client, Team};
+use crate::resources::{ClientID, ClientRegistry, NetworkCommand, ReplayEvent, StreamId};
+use westiny_common::network::{ChatMessage, PacketType, PlayerNotification, MAX_CHAT_MESSAGE_LENGTH};
+use westiny_common::serialize;
+
+const MAX_MESSAGES_PER_WINDOW: usize = 3;
+const RATE_LIMIT_WINDOW: Duration = Duration::from_secs(5);
+
+/// Forwards the chat messages of the players to everyone, or only to their team.
+/// Players sending too many messages are told to slow down.
+#[derive(SystemDesc, new)]
+#[system_desc(name(ChatSystemDesc))]
+pub struct ChatSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<NetworkCommand>,
+
+    #[system_desc(skip)]
+    #[new(default)]
+    rate_limiters: HashMap<ClientID, RateLimiter>,
+}
+
+impl<'s> System<'s> for ChatSystem {
+    type SystemData = (
+        Read<'s, EventChannel<NetworkCommand>>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        ReadStorage<'s, Client>,
+        ReadStorage<'s, Team>,
+        Read<'s, Time>,
+        Write<'s, EventChannel<ReplayEvent>>,
+    );
+
+    fn run(&mut self, (command_channel, client_registry, mut net, clients, teams, time, mut replay_channel): Self::SystemData) {
+        self.rate_limiters.retain(|id, _| client_registry.find_client(*id).is_some());
+
+        for command in command_channel.read(&mut self.reader) {
+            let (id, text, team_only) = match command {
+                NetworkCommand::Chat { id, text, team_only } => (*id, text, *team_only),
+                _ => continue,
+            };
+            let sender = match client_registry.find_client(id) {
+                Some(sender) => sender,
+                None => continue,
+            };
+            let text = match sanitize_chat_text(text) {
+                Some(text) => text,
+                None => continue,
+            };
+
+            if !self.rate_limiters.entry(id).or_default().try_send(time.absolute_time()) {
+                let notification = PlayerNotification { message: "You are sending messages too fast.".to_string() };
+                let msg = serialize(&PacketType::Notification(notification)).expect("PlayerNotification could not be serialized");
+                net.send_with_requirements(sender.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick);
+                continue;
+            }
+
+            // Without teams the message goes to everyone
+            let sender_team = team_of(id, &clients, &teams);
+            let team_only = team_only && sender_team.is_some();
+            log::info!("[chat{}] {}: {}", if team_only { " team" } else { "" }, sender.player_name, text);
+
+            let message = ChatMessage { sender: sender.player_name.clone(), text, team_only };
+            let packet = PacketType::Chat(message);
+            let msg = serialize(&packet).expect("ChatMessage could not be serialized");
+            for handle in client_registry.get_clients() {
+                if team_only && team_of(handle.id, &clients, &teams) != sender_team {
+                    continue;
+                }
+                net.send_with_requirements(
+                    handle.addr,
+                    &msg,
+                    DeliveryRequirement::ReliableOrdered(StreamId::Chat.into()),
+                    UrgencyRequirement::OnTick,
+                );
+            }
+            // Team messages are left out of the replay by the recorder
+            replay_channel.single_write(ReplayEvent(packet));
+        }
+    }
+}
+
+fn team_of(id: ClientID, clients: &ReadStorage<'_, Client>, teams: &ReadStorage<'_, Team>) -> Option<Team> {
+    (clients, teams).join()
+        .find(|(client, _)| client.id == id)
+        .map(|(_, team)| *team)
+}
+
+/// Control characters are removed and the message is cut to the length limit.
+/// Nothing remains of a message with only whitespace.
+fn sanitize_chat_text(text: &str) -> Option<String> {
+    let text: String = text.chars()
+        .filter(|c| !c.is_control())
+        .collect::<String>()
+        .trim()
+        .chars()
+        .take(MAX_CHAT_MESSAGE_LENGTH)
+        .collect();
+    if text.is_empty() {
+        None
+    } else {
+        Some(text)
+    }
+}
+
+/// Allows a limited number of messages in a sliding time window
+#[derive(Default)]
+struct RateLimiter {
+    sent_at: VecDeque<Duration>,
+}
+
+impl RateLimiter {
+    fn try_send(&mut self, now: Duration) -> bool {
+        while self.sent_at.front().map_or(false, |&sent_at| now >= sent_at + RATE_LIMIT_WINDOW) {
+            self.sent_at.pop_front();
+        }
+        if self.sent_at.len() >= MAX_MESSAGES_PER_WINDOW {
+            return false;
+        }
+        self.sent_at.push_back(now);
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn chat_text_is_sanitized() {
+        assert_eq!(sanitize_chat_text("  hello\u{7} there\n "), Some("hello there".to_string()));
+        assert_eq!(sanitize_chat_text(" \t\r\n"), None);
+        assert_eq!(sanitize_chat_text(&"a".repeat(500)).map(|text| text.len()), Some(MAX_CHAT_MESSAGE_LENGTH));
+    }
+
+    #[test]
+    fn rate_limiter_allows_messages_again_after_the_window() {
+        let mut limiter = RateLimiter::default();
+        for second in 0..MAX_MESSAGES_PER_WINDOW as u64 {
+            assert!(limiter.try_send(Duration::from_secs(second)));
+        }
+        assert!(!limiter.try_send(Duration::from_secs(MAX_MESSAGES_PER_WINDOW as u64)));
+
+        // The first message left the window
+        assert!(limiter.try_send(RATE_LIMIT_WINDOW));
+        assert!(!limiter.try_send(RATE_LIMIT_WINDOW));
+    }
+}
@@ -1,7 +1,7 @@
 use amethyst::{
     derive::SystemDesc,
     ecs::{
-        Entities, Join, Read, ReadExpect, ReadStorage, System, SystemData, WriteExpect,
+        Entities, Join, LazyUpdate, Read, ReadExpect, ReadStorage, System, SystemData, Write, WriteExpect,
     },
     network::simulation::{DeliveryRequirement, TransportResource, UrgencyRequirement},
     shrev::{EventChannel, ReaderId},
@@ -18,8 +18,9 @@ use westiny_common::{
 use crate::{
     components,
     components::EntityType,
-    resources::{ClientID, ClientNetworkEvent, ClientRegistry, NetworkIdSupplier},
+    resources::{ClientID, ClientNetworkEvent, ClientRegistry, DestroyedObjects, GameRules, MapRotation, MatchState, NetworkIdSupplier, ReplayEvent},
 };
+use super::death::{notify_spectator, spawn_spectator};
 use westiny_common::resources::Seed;
 use crate::systems::SpawnPlayerEvent;
 
@@ -38,10 +39,17 @@ impl<'s> System<'s> for ClientIntroductionSystem {
         WriteExpect<'s, TransportResource>,
         ReadExpect<'s, ClientRegistry>,
         ReadExpect<'s, Seed>,
+        ReadExpect<'s, MapRotation>,
         WriteExpect<'s, NetworkIdSupplier>,
         ReadStorage<'s, components::NetworkId>,
         ReadStorage<'s, components::Client>,
         WriteExpect<'s, EventChannel<SpawnPlayerEvent>>,
+        ReadExpect<'s, DestroyedObjects>,
+        ReadStorage<'s, components::Team>,
+        ReadExpect<'s, GameRules>,
+        Read<'s, MatchState>,
+        Read<'s, LazyUpdate>,
+        Write<'s, EventChannel<ReplayEvent>>,
     );
 
     fn run(
@@ -53,15 +61,22 @@ impl<'s> System<'s> for ClientIntroductionSystem {
             mut net,
             client_registry,
             seed,
+            map_rotation,
             mut net_id_supplier,
             network_ids,
             client,
-            mut spawn_player_event_channel
+            mut spawn_player_event_channel,
+            destroyed_objects,
+            teams,
+            rules,
+            match_state,
+            lazy,
+            mut replay_channel,
         ): Self::SystemData,
     ) {
         // This vector is used for deduplicating ClientConnected events within one frame to avoid
         // multiple spawn for a single client.
-        let mut added_clients = Vec::<(ClientID, components::NetworkId)>::new();
+        let mut added_clients = Vec::<(ClientID, components::NetworkId, Option<components::Team>)>::new();
 
         for client_network_event in client_net_ec.read(&mut self.reader) {
             match client_network_event {
@@ -71,15 +86,18 @@ impl<'s> System<'s> for ClientIntroductionSystem {
                         client_id
                     ));
 
-                    let entity_network_id = if let Some((_, net_id)) =
-                        added_clients.iter().find(|(cli_id, _)| cli_id == client_id)
+                    let entity_network_id = if client_handle.observer {
+                        log::debug!("{} joined as observer, no player entity is spawned", client_handle.player_name);
+                        None
+                    } else if let Some((_, net_id, _)) =
+                        added_clients.iter().find(|(cli_id, _, _)| cli_id == client_id)
                     {
                         log::info!(
                             "Player for {:?} already spawned: {:?}, not respawning.",
                             client_id,
                             net_id
                         );
-                        *net_id
+                        Some(*net_id)
                     } else {
                         let net_id = net_id_supplier.next(EntityType::Player);
 
@@ -94,10 +112,27 @@ impl<'s> System<'s> for ClientIntroductionSystem {
                             );
                         }
 
-                        spawn_player_event_channel.single_write(SpawnPlayerEvent {
-                            client: components::Client { id: *client_id },
-                            network_id: net_id,
-                        });
+                        // New players are put into the smaller team
+                        let team = if rules.has_teams() {
+                            let members = (&client, &teams).join()
+                                .map(|(_, team)| *team)
+                                .chain(added_clients.iter().filter_map(|(_, _, team)| *team));
+                            Some(components::Team::balanced(members))
+                        } else {
+                            None
+                        };
+
+                        if rules.has_single_life() && match_state.is_live() {
+                            // Late joiners of a last man standing round wait for the next one
+                            spawn_spectator(&entities, &lazy, components::Client { id: *client_id }, net_id, team);
+                            notify_spectator(&client_registry, &mut net, *client_id, true);
+                        } else {
+                            spawn_player_event_channel.single_write(SpawnPlayerEvent {
+                                client: components::Client { id: *client_id },
+                                network_id: net_id,
+                                team,
+                            });
+                        }
 
                         log::debug!(
                             "Player entity spawn requested for {}, {:?}, {:?}",
@@ -105,16 +140,18 @@ impl<'s> System<'s> for ClientIntroductionSystem {
                             client_id,
                             net_id
                         );
-                        added_clients.push((*client_id, net_id));
-                        net_id
+                        added_clients.push((*client_id, net_id, team));
+                        Some(net_id)
                     };
 
                     // Send response to client
                     let connection_response =
                         PacketType::ConnectionResponse(Ok(ClientInitialData {
                             player_network_id: entity_network_id,
+                            map: map_rotation.current().to_string(),
                             // TODO initial_pos should not be sent here. On the client side it will be processed from EntityStateUpdate messages anyway.
-                            seed: *seed
+                            seed: *seed,
+                            destroyed_objects: destroyed_objects.network_ids(),
                         })
                     );
                     net.send_with_requirements(
@@ -124,10 +161,12 @@ impl<'s> System<'s> for ClientIntroductionSystem {
                         UrgencyRequirement::OnTick,
                     );
 
+                    let joined_as = if client_handle.observer { " as observer" } else { "" };
                     broadcast_notification(
                         &mut net,
+                        &mut replay_channel,
                         &client_registry,
-                        PlayerNotification{message: format!("{} joined.", &client_handle.player_name)});
+                        PlayerNotification{message: format!("{} joined{}.", &client_handle.player_name, joined_as)});
                 }
                 ClientNetworkEvent::ClientDisconnected(client_id, player_name) => {
                     log::debug!("Removing disconnecting client's player entity [client_id: {:?}]", client_id);
@@ -135,6 +174,7 @@ impl<'s> System<'s> for ClientIntroductionSystem {
 
                     broadcast_notification(
                         &mut net,
+                        &mut replay_channel,
                         &client_registry,
                         PlayerNotification{message: format!("{} left the game.", &player_name)});
                 }
@@ -145,16 +185,19 @@ impl<'s> System<'s> for ClientIntroductionSystem {
 
 fn broadcast_notification(
     net: &mut TransportResource,
+    replay_channel: &mut EventChannel<ReplayEvent>,
     client_registry: &ClientRegistry,
     notification: PlayerNotification)
 {
-    let msg = serialize(&PacketType::Notification(notification)).expect("PlayerNotification could not be serialized");
+    let packet = PacketType::Notification(notification);
+    let msg = serialize(&packet).expect("PlayerNotification could not be serialized");
     for &handle in client_registry.get_clients().iter() {
         net.send_with_requirements(handle.addr,
                                    &msg,
                                    DeliveryRequirement::Reliable,
                                    UrgencyRequirement::OnTick)
     }
+    replay_channel.single_write(ReplayEvent(packet));
 }
 
 impl ClientIntroductionSystem {
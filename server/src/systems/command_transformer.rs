@@ -26,6 +26,8 @@ impl<'s> System<'s> for CommandTransformerSystem {
         for command in command_channel.read(&mut self.reader) {
             match command {
                 NetworkCommand::Input { id, input } => self.apply_client_input(id, &input, &clients, &mut inputs),
+                // Forwarded by the ChatSystem
+                NetworkCommand::Chat { .. } => {}
             }
         }
     }
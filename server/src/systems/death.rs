@@ -1,22 +1,41 @@
-use amethyst::core::ecs::{System, ReadStorage, Entities, Write, ReadExpect, Join};
-use crate::components::{Eliminated, Player, Client};
-use amethyst::shrev::EventChannel;
+use amethyst::core::ecs::{System, SystemData, ReadStorage, WriteStorage, Entities, Entity, Write, Read, ReadExpect, Join, LazyUpdate};
+use amethyst::derive::SystemDesc;
+use crate::components::{Eliminated, Player, Client, NetworkId, Respawn, Spectator, Team, weapon::Holster};
+use amethyst::shrev::{EventChannel, ReaderId};
 use westiny_common::events::EntityDelete;
-use crate::resources::{ClientRegistry, StreamId};
-use amethyst::core::Transform;
+use crate::resources::{ClientID, ClientRegistry, NetworkIdSupplier, StreamId, KillEvent, ReplayEvent};
+use amethyst::core::{Transform, Time};
 use amethyst::shred::WriteExpect;
 use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
 use westiny_common::serialize;
-use westiny_common::network::{PacketType, PlayerDeath};
+use westiny_common::network::{PacketType, PlayerDeath, SpectatorUpdate};
 use amethyst::core::math::Point2;
 use westiny_common::metric_dimension::to_meter_vec;
+use westiny_common::metric_dimension::length::Meter;
+use super::inventory::drop_weapon;
+use derive_new::new;
+use std::collections::HashMap;
+
+/// Distance of the dropped weapons from the position of death
+const WEAPON_SCATTER_RADIUS: Meter = Meter(0.5);
 
 
 /// Game logic related to player death
-pub struct DeathSystem;
+#[derive(SystemDesc, new)]
+#[system_desc(name(DeathSystemDesc))]
+pub struct DeathSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<KillEvent>,
+
+    /// Killer and weapon of the players died but not handled yet
+    #[system_desc(skip)]
+    #[new(default)]
+    kills: HashMap<Entity, (Option<Entity>, Option<String>)>,
+}
 
 impl<'s> System<'s> for DeathSystem {
     type SystemData = (
+        Read<'s, EventChannel<KillEvent>>,
         ReadStorage<'s, Eliminated>,
         ReadStorage<'s, Player>,
         ReadStorage<'s, Transform>,
@@ -25,10 +44,19 @@ impl<'s> System<'s> for DeathSystem {
         Entities<'s>,
         Write<'s, EventChannel<EntityDelete>>,
         WriteExpect<'s, TransportResource>,
+        WriteStorage<'s, Holster>,
+        WriteExpect<'s, NetworkIdSupplier>,
+        ReadExpect<'s, LazyUpdate>,
+        Read<'s, Time>,
+        ReadStorage<'s, NetworkId>,
+        ReadStorage<'s, Respawn>,
+        ReadStorage<'s, Team>,
+        Write<'s, EventChannel<ReplayEvent>>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
-        let (eliminates,
+        let (kill_event_channel,
+            eliminates,
             players,
             transforms,
             clients,
@@ -36,22 +64,67 @@ impl<'s> System<'s> for DeathSystem {
             entities,
             mut entity_delete_event_channel,
             mut net,
+            mut holsters,
+            mut net_id_supplier,
+            lazy,
+            time,
+            network_ids,
+            respawns,
+            teams,
+            mut replay_channel,
         ) = data;
 
-        for (_eliminated, _player, transform, entity, client) in (&eliminates, &players, &transforms, &entities, &clients).join() {
+        for kill in kill_event_channel.read(&mut self.reader) {
+            if players.contains(kill.victim) {
+                self.kills.insert(kill.victim, (kill.killer, kill.weapon.clone()));
+            }
+        }
+
+        for (_eliminated, _player, transform, entity, client, holster) in (&eliminates, &players, &transforms, &entities, &clients, (&mut holsters).maybe()).join() {
             let player_name = client_registry.find_client(client.id).unwrap().player_name.clone();
-            log::info!("{} died", player_name);
+            let (killer, weapon) = self.kills.remove(&entity).unwrap_or_default();
+            let killer_name = killer
+                .filter(|&killer| killer != entity)
+                .and_then(|killer| clients.get(killer))
+                .and_then(|killer| client_registry.find_client(killer.id))
+                .map(|killer| killer.player_name.clone());
+            match &killer_name {
+                Some(killer_name) => log::info!("{} killed {}", killer_name, player_name),
+                None => log::info!("{} died", player_name),
+            }
             // Dead player must be removed
             entity_delete_event_channel.single_write(EntityDelete {entity_id: entity});
 
-            let death_event_msg = serialize(&PacketType::PlayerDeath(
+            // Without respawn the player watches the others until the next round
+            if let (None, Some(network_id)) = (respawns.get(entity), network_ids.get(entity)) {
+                spawn_spectator(&entities, &lazy, *client, *network_id, teams.get(entity).copied());
+                notify_spectator(&client_registry, &mut net, client.id, true);
+            }
+
+            // Weapons of the dead player are left on the ground for others
+            if let Some(holster) = holster {
+                let weapons = holster.take_all();
+                let weapon_count = weapons.len();
+                for (index, weapon) in weapons.into_iter().enumerate() {
+                    let angle = 2.0 * std::f32::consts::PI * index as f32 / weapon_count as f32;
+                    let mut drop_position = transform.clone();
+                    drop_position.prepend_translation_x(WEAPON_SCATTER_RADIUS.into_pixel() * angle.cos());
+                    drop_position.prepend_translation_y(WEAPON_SCATTER_RADIUS.into_pixel() * angle.sin());
+                    drop_weapon(weapon, &drop_position, &entities, &lazy, &mut net_id_supplier, &time);
+                }
+            }
+
+            let death_event = PacketType::PlayerDeath(
                     PlayerDeath {
                         player_name,
                         position: Point2 {
                             coords: to_meter_vec(transform.translation().xy())
-                        }
+                        },
+                        killer: killer_name,
+                        weapon,
                     }
-            )).expect("Could not serialize PlayerDeath");
+            );
+            let death_event_msg = serialize(&death_event).expect("Could not serialize PlayerDeath");
 
             client_registry.get_clients().iter().for_each(|&handle| {
                 net.send_with_requirements(
@@ -59,7 +132,35 @@ impl<'s> System<'s> for DeathSystem {
                     &death_event_msg,
                     DeliveryRequirement::ReliableSequenced(StreamId::PlayerDeath.into()),
                 UrgencyRequirement::OnTick);
-            })
+            });
+            replay_channel.single_write(ReplayEvent(death_event));
         }
     }
 }
+
+/// Keeps the client in the game without a character
+pub(super) fn spawn_spectator(
+    entities: &Entities<'_>,
+    lazy: &LazyUpdate,
+    client: Client,
+    network_id: NetworkId,
+    team: Option<Team>,
+) {
+    let builder = lazy.create_entity(entities)
+        .with(client)
+        .with(network_id)
+        .with(Spectator);
+    match team {
+        Some(team) => builder.with(team).build(),
+        None => builder.build(),
+    };
+}
+
+/// Tells the client whether it watches the others or plays its own character
+pub(super) fn notify_spectator(client_registry: &ClientRegistry, net: &mut TransportResource, client_id: ClientID, spectating: bool) {
+    if let Some(handle) = client_registry.find_client(client_id) {
+        let msg = serialize(&PacketType::SpectatorUpdate(SpectatorUpdate { spectating }))
+            .expect("SpectatorUpdate could not be serialized");
+        net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick);
+    }
+}
@@ -0,0 +1,138 @@
+use amethyst::core::{Transform, Time};
+use amethyst::core::math::Point2;
+use amethyst::ecs::{System, ReadStorage, WriteStorage, ReadExpect, Read, Write, WriteExpect, Entities, Join, LazyUpdate};
+use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
+use amethyst::shrev::EventChannel;
+use std::time::Duration;
+
+use crate::components::{BoundingCircle, Destructible, Eliminated, Health, NetworkId};
+use crate::resources::{ClientRegistry, DestroyedObject, DestroyedObjects};
+use westiny_common::collision::{Collider, check_body_collision};
+use westiny_common::entities::{spawn_barrel, BARREL_RADIUS};
+use westiny_common::events::{EntityDelete, ExplosionEvent};
+use westiny_common::metric_dimension::to_meter_vec;
+use westiny_common::{network, serialize};
+
+/// Removes the destroyed map objects, sets off their explosion and schedules their respawn
+pub struct DestructionSystem;
+
+impl<'s> System<'s> for DestructionSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Eliminated>,
+        WriteStorage<'s, Destructible>,
+        ReadStorage<'s, NetworkId>,
+        ReadStorage<'s, Transform>,
+        Write<'s, EventChannel<EntityDelete>>,
+        Write<'s, EventChannel<ExplosionEvent>>,
+        WriteExpect<'s, DestroyedObjects>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            eliminates,
+            mut destructibles,
+            network_ids,
+            transforms,
+            mut entity_delete_channel,
+            mut explosion_channel,
+            mut destroyed_objects,
+            time,
+        ) = data;
+
+        let destroyed: Vec<_> = (&entities, &eliminates, &destructibles, &network_ids, &transforms).join()
+            .map(|(entity, _, _, network_id, transform)| (entity, *network_id, transform.clone()))
+            .collect();
+
+        for (entity, network_id, transform) in destroyed {
+            // Removing the component ensures the object is handled only once even if
+            // the deletion happens in a later frame.
+            let details = match destructibles.remove(entity) {
+                Some(destructible) => destructible.details,
+                None => continue,
+            };
+            log::debug!("{:?} destroyed", network_id);
+            entity_delete_channel.single_write(EntityDelete { entity_id: entity });
+
+            if let Some(explosion) = &details.explosion {
+                explosion_channel.single_write(ExplosionEvent {
+                    position: Point2 { coords: to_meter_vec(transform.translation().xy()) },
+                    details: explosion.clone(),
+                    owner: None,
+                    weapon: None,
+                });
+            }
+
+            let respawn_at = details.respawn_time
+                .map(|respawn_time| time.absolute_time() + Duration::from_secs_f32(respawn_time.0));
+            destroyed_objects.0.push(DestroyedObject { network_id, transform, details, respawn_at });
+        }
+    }
+}
+
+/// Places back the destroyed map objects when their respawn time is over
+pub struct ObjectRespawnSystem;
+
+impl<'s> System<'s> for ObjectRespawnSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, BoundingCircle>,
+        WriteExpect<'s, DestroyedObjects>,
+        ReadExpect<'s, LazyUpdate>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, transforms, bounds, mut destroyed_objects, lazy, client_registry, mut net, time): Self::SystemData) {
+        let now = time.absolute_time();
+        let (respawning, waiting): (Vec<_>, Vec<_>) = destroyed_objects.0.drain(..)
+            .partition(|object| {
+                object.respawn_at.map_or(false, |respawn_at| respawn_at <= now)
+                    && !is_occupied(&object.transform, &transforms, &bounds)
+            });
+        destroyed_objects.0 = waiting;
+
+        for object in respawning {
+            log::debug!("{:?} respawned", object.network_id);
+            let entity_state = network::EntityState {
+                network_id: object.network_id,
+                position: Point2 { coords: to_meter_vec(object.transform.translation().xy()) },
+                rotation: 0.0,
+            };
+
+            spawn_barrel(object.transform, object.network_id, lazy.create_entity(&entities))
+                .with(Health(object.details.health))
+                .with(Destructible::new(object.details))
+                .build();
+
+            // Static entities are not broadcast continuously, clients are notified once
+            let msg = serialize(&network::PacketType::EntityStateUpdate(vec![entity_state]))
+                .expect("entity state update could not be serialized");
+            client_registry.get_clients().iter().for_each(|&handle| {
+                net.send_with_requirements(
+                    handle.addr,
+                    &msg,
+                    DeliveryRequirement::Reliable,
+                    UrgencyRequirement::OnTick
+                )
+            });
+        }
+    }
+}
+
+/// Objects must not be placed back onto someone
+fn is_occupied(
+    transform: &Transform,
+    transforms: &ReadStorage<'_, Transform>,
+    bounds: &ReadStorage<'_, BoundingCircle>,
+) -> bool {
+    let object_bound = BoundingCircle { radius: BARREL_RADIUS };
+    (transforms, bounds).join()
+        .any(|(other_transform, other_bound)| check_body_collision(
+            Collider { transform, bound: &object_bound },
+            Collider { transform: other_transform, bound: other_bound }).is_some())
+}
@@ -0,0 +1,40 @@
+use amethyst::ecs::{System, ReadExpect};
+
+use crate::resources::{ClientRegistry, MapRotation, ServerConfig};
+use westiny_common::discovery::{DiscoveryResponder, ServerInfo, PROTOCOL_VERSION};
+
+/// Answers the LAN discovery queries of the clients looking for a server
+pub struct DiscoverySystem {
+    responder: DiscoveryResponder,
+    name: String,
+    game_port: u16,
+}
+
+impl DiscoverySystem {
+    pub fn new(responder: DiscoveryResponder, config: &ServerConfig) -> Self {
+        DiscoverySystem {
+            responder,
+            name: config.name.clone(),
+            game_port: config.bind_address.port(),
+        }
+    }
+}
+
+impl<'s> System<'s> for DiscoverySystem {
+    type SystemData = (ReadExpect<'s, ClientRegistry>, ReadExpect<'s, MapRotation>);
+
+    fn run(&mut self, (client_registry, map_rotation): Self::SystemData) {
+        let info = ServerInfo {
+            name: self.name.clone(),
+            map: map_rotation.current().to_string(),
+            player_count: client_registry.player_count(),
+            max_players: client_registry.max_slots(),
+            protocol_version: PROTOCOL_VERSION,
+            game_port: self.game_port,
+        };
+
+        if let Err(err) = self.responder.answer_queries(&info) {
+            log::warn!("Discovery queries could not be answered: {}", err);
+        }
+    }
+}
@@ -4,7 +4,7 @@ use amethyst::derive::SystemDesc;
 use amethyst::shrev::{ReaderId, EventChannel};
 use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
 
-use crate::resources::ClientRegistry;
+use crate::resources::{ClientRegistry, ReplayEvent};
 use crate::components::NetworkId;
 use westiny_common::events::EntityDelete;
 use westiny_common::{network, serialize};
@@ -27,23 +27,33 @@ impl<'s> System<'s> for EntityDeleteBroadcasterSystem {
         ReadExpect<'s, ClientRegistry>,
         Write<'s, TransportResource>,
         ReadStorage<'s, NetworkId>,
+        Write<'s, EventChannel<ReplayEvent>>,
         );
 
-    fn run(&mut self, (id_channel, entities, clients, mut net, network_ids): Self::SystemData) {
+    fn run(&mut self, (id_channel, entities, clients, mut net, network_ids, mut replay_channel): Self::SystemData) {
         for EntityDelete{entity_id} in id_channel.read(&mut self.reader) {
+            if !entities.is_alive(*entity_id) {
+                // More systems may decide to delete the same entity in a frame
+                log::warn!("Entity {:?} is already deleted", entity_id);
+                continue;
+            }
             log::debug!("Delete entity: {:?}", entity_id);
             if let Some(network_id) = network_ids.get(*entity_id) {
                 log::debug!("Notify client about entity deletion: {:?}, network_id:{:?}", entity_id, network_id);
-                send_to_clients(&clients, &mut net, network::NetworkEntityDelete{network_id: *network_id});
+                let packet = network::PacketType::EntityDelete(network::NetworkEntityDelete{network_id: *network_id});
+                send_to_clients(&clients, &mut net, &packet);
+                replay_channel.single_write(ReplayEvent(packet));
+            }
+            if let Err(err) = entities.delete(*entity_id) {
+                log::warn!("Entity {:?} could not be deleted: {}", entity_id, err);
             }
-            entities.delete(*entity_id).expect("Could not delete entity!");
         }
     }
 }
 
-fn send_to_clients(clients: &ClientRegistry, net: &mut TransportResource, network_entity_delete: network::NetworkEntityDelete)
+fn send_to_clients(clients: &ClientRegistry, net: &mut TransportResource, packet: &network::PacketType)
 {
-    let message = serialize(&network::PacketType::EntityDelete(network_entity_delete))
+    let message = serialize(packet)
         .expect("NetworkEntityDelete could not be serialized");
 
     clients.get_clients().iter().for_each(|&client|{
@@ -51,3 +61,51 @@ fn send_to_clients(clients: &ClientRegistry, net: &mut TransportResource, networ
     }
     );
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::Error;
+    use amethyst::prelude::{World, WorldExt, Builder};
+    use amethyst::ecs::Entity;
+    use amethyst_test::prelude::*;
+    use std::net::SocketAddr;
+    use westiny_common::components::EntityType;
+    use westiny_common::deserialize;
+
+    #[test]
+    fn entity_deleted_twice_in_a_frame_is_deleted_and_broadcast_once() -> Result<(), Error> {
+        amethyst::start_logger(Default::default());
+        let mut client_registry = ClientRegistry::new(1);
+        client_registry.add(&SocketAddr::from(([10, 0, 0, 1], 5000)), "Clint")?;
+
+        AmethystApplication::blank()
+            .with_setup(|world: &mut World| {
+                world.register::<NetworkId>();
+            })
+            .with_resource(client_registry)
+            .with_resource(TransportResource::new())
+            .with_system_desc(EntityDeleteBroadcasterSystemDesc::default(), "delete_broadcaster", &[])
+            .with_effect(|world: &mut World| {
+                let placeholder = world.create_entity()
+                    .with(NetworkId::new(EntityType::Player, 1))
+                    .build();
+                world.insert(EffectReturn(placeholder));
+
+                let mut channel = world.fetch_mut::<EventChannel<EntityDelete>>();
+                channel.single_write(EntityDelete { entity_id: placeholder });
+                channel.single_write(EntityDelete { entity_id: placeholder });
+            })
+            .with_assertion(|world: &mut World| {
+                let placeholder = world.read_resource::<EffectReturn<Entity>>().0;
+                assert!(!world.entities().is_alive(placeholder));
+
+                let net = world.fetch::<TransportResource>();
+                let deletes = net.get_messages().iter()
+                    .filter(|message| matches!(deserialize(&message.payload), Ok(network::PacketType::EntityDelete(_))))
+                    .count();
+                assert_eq!(deletes, 1);
+            })
+            .run()
+    }
+}
@@ -1,39 +1,67 @@
 use amethyst::core::Transform;
-use amethyst::core::ecs::{System, ReadStorage, WriteExpect, Join};
+use amethyst::core::ecs::{System, ReadStorage, WriteExpect, Entities, Entity, Join};
 use amethyst::core::math::{Point2, UnitQuaternion};
 use amethyst::shred::ReadExpect;
 use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
-use crate::resources::{ClientRegistry, StreamId};
+use std::collections::HashMap;
+use crate::resources::{ClientID, ClientRegistry, StreamId};
 use crate::components;
 use westiny_common::{network, serialize};
 use westiny_common::metric_dimension::length::Meter;
+use westiny_common::visibility::OcclusionMap;
 
-/// This system is responsible for sending the transform of all the entities that has NetworkID
-/// to every connected clients
+/// This system is responsible for sending the transform of the entities that has NetworkID
+/// to the connected clients. Static entities are known by the clients from the map.
+/// Players only receive what is in their line of sight, besides themselves and their teammates.
 pub struct EntityStateBroadcasterSystem;
 
+/// The character of a client looking around
+struct Viewer {
+    entity: Entity,
+    position: Point2<Meter>,
+    team: Option<components::Team>,
+}
+
 impl<'s> System<'s> for EntityStateBroadcasterSystem {
     type SystemData = (
         ReadExpect<'s, ClientRegistry>,
         WriteExpect<'s, TransportResource>,
+        Entities<'s>,
         ReadStorage<'s, components::NetworkId>,
         ReadStorage<'s, Transform>,
+        ReadStorage<'s, components::Client>,
+        ReadStorage<'s, components::Team>,
     );
 
-    fn run(&mut self, (client_registry, mut net, network_ids, transforms): Self::SystemData) {
+    fn run(&mut self, (client_registry, mut net, entities, network_ids, transforms, clients, teams): Self::SystemData) {
+        let occlusion_map = OcclusionMap::new((&network_ids, &transforms).join()
+            .filter(|(network_id, _)| network_id.entity_type.is_static())
+            .map(|(_, transform)| position_of(transform)));
+
         let mut network_entities = Vec::new();
-        for (network_id, transform) in (&network_ids, &transforms).join() {
-            let entity_state = network::EntityState {
-                network_id: *network_id,
-                position: Point2::new(Meter::from_pixel(transform.translation().x), Meter::from_pixel(transform.translation().y)),
-                rotation: get_angle(transform.rotation()),
-            };
-
-            network_entities.push(entity_state);
+        for (entity, network_id, transform, team) in (&entities, &network_ids, &transforms, (&teams).maybe()).join() {
+            if network_id.entity_type.is_static() {
+                continue;
+            }
+
+            network_entities.push((entity, team.copied(), entity_state(network_id, transform)));
         }
 
-        let msg = serialize(&network::PacketType::EntityStateUpdate(network_entities)).expect("entity state update could not be serialized");
-        client_registry.get_clients().iter().for_each(|&handle|{
+        let viewers: HashMap<ClientID, Viewer> = (&entities, &clients, &transforms, (&teams).maybe()).join()
+            .map(|(entity, client, transform, team)| (client.id, Viewer { entity, position: position_of(transform), team: team.copied() }))
+            .collect();
+
+        client_registry.get_clients().iter().for_each(|&handle| {
+            // Players waiting for respawn and observers see everything
+            let visible_entities: Vec<_> = network_entities.iter()
+                .filter(|(entity, team, entity_state)| match viewers.get(&handle.id) {
+                    Some(viewer) => is_visible_to(viewer, *entity, *team, &entity_state.position, &occlusion_map),
+                    None => true,
+                })
+                .map(|(_, _, entity_state)| entity_state.clone())
+                .collect();
+
+            let msg = serialize(&network::PacketType::EntityStateUpdate(visible_entities)).expect("entity state update could not be serialized");
             net.send_with_requirements(
                 handle.addr,
                 &msg,
@@ -44,6 +72,23 @@ impl<'s> System<'s> for EntityStateBroadcasterSystem {
     }
 }
 
+fn is_visible_to(viewer: &Viewer, entity: Entity, team: Option<components::Team>, position: &Point2<Meter>, occlusion_map: &OcclusionMap) -> bool {
+    let is_teammate = viewer.team.is_some() && viewer.team == team;
+    entity == viewer.entity || is_teammate || occlusion_map.is_visible(&viewer.position, position)
+}
+
+pub(super) fn entity_state(network_id: &components::NetworkId, transform: &Transform) -> network::EntityState {
+    network::EntityState {
+        network_id: *network_id,
+        position: position_of(transform),
+        rotation: get_angle(transform.rotation()),
+    }
+}
+
+fn position_of(transform: &Transform) -> Point2<Meter> {
+    Point2::new(Meter::from_pixel(transform.translation().x), Meter::from_pixel(transform.translation().y))
+}
+
 fn get_angle(rotation: &UnitQuaternion<f32>) -> f32 {
     if rotation.coords.w < 0.0 {
         2.0 * std::f32::consts::PI - rotation.angle()
@@ -0,0 +1,208 @@
+use amethyst::{
+    core::Transform,
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, Write, ReadStorage, ReadExpect, WriteExpect, Entities, Join},
+    network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement},
+    shrev::{ReaderId, EventChannel},
+};
+use derive_new::new;
+
+use crate::components::{BoundingCircle, Damage, Health, Velocity};
+use crate::resources::{ClientRegistry, ReplayEvent};
+use westiny_common::collision::{Collider, check_projectile_collision, is_segment_blocked};
+use westiny_common::events::{DamageEvent, ExplosionEvent, StatusEffectEvent};
+use westiny_common::metric_dimension::length::{Meter, magnitude};
+use westiny_common::metric_dimension::to_meter_vec;
+use westiny_common::network::{ExplosionNotification, PacketType};
+use westiny_common::serialize;
+
+/// Deals area damage to every entity with `Health` in range of the explosions.
+/// Obstacles (bodies that can not move) between the center and the target block the damage.
+#[derive(SystemDesc, new)]
+#[system_desc(name(ExplosionSystemDesc))]
+pub struct ExplosionSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<ExplosionEvent>,
+}
+
+impl<'s> System<'s> for ExplosionSystem {
+    type SystemData = (
+        Read<'s, EventChannel<ExplosionEvent>>,
+        Write<'s, EventChannel<DamageEvent>>,
+        Write<'s, EventChannel<StatusEffectEvent>>,
+        Entities<'s>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, BoundingCircle>,
+        ReadStorage<'s, Health>,
+        ReadStorage<'s, Velocity>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Write<'s, EventChannel<ReplayEvent>>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            explosion_channel,
+            mut damage_channel,
+            mut status_effect_channel,
+            entities,
+            transforms,
+            bounds,
+            healths,
+            velocities,
+            client_registry,
+            mut net,
+            mut replay_channel,
+        ) = data;
+
+        for explosion in explosion_channel.read(&mut self.reader) {
+            let center = {
+                let mut transform = Transform::default();
+                transform.set_translation_xyz(explosion.position.x.into_pixel(), explosion.position.y.into_pixel(), 0.0);
+                transform
+            };
+
+            for (target, transform, bound, _) in (&entities, &transforms, (&bounds).maybe(), &healths).join() {
+                if !explosion.details.self_damage && explosion.owner == Some(target) {
+                    continue;
+                }
+
+                let disposition = to_meter_vec((transform.translation() - center.translation()).xy());
+                let target_radius = bound.map_or(Meter(0.0), |bound| bound.radius);
+                let distance = magnitude(disposition) - target_radius;
+
+                let damage = match explosion.details.damage_at(distance) {
+                    Some(damage) => damage,
+                    None => continue,
+                };
+
+                let blocked = (&entities, &transforms, &bounds, !&velocities).join()
+                    .filter(|(obstacle, _, _, _)| *obstacle != target)
+                    // The object the explosion happened in (e.g. an exploding barrel) does not shelter anyone
+                    .filter(|(_, obstacle_transform, obstacle_bound, _)| check_projectile_collision(&center, Collider { transform: obstacle_transform, bound: obstacle_bound }).is_none())
+                    .any(|(_, obstacle_transform, obstacle_bound, _)| is_segment_blocked(&center, transform, Collider { transform: obstacle_transform, bound: obstacle_bound }));
+
+                if blocked {
+                    log::debug!("{:?} is sheltered from the explosion", target);
+                    continue;
+                }
+
+                log::debug!("{:?} caught in explosion, damage: {}", target, damage);
+                damage_channel.single_write(DamageEvent {
+                    damage: Damage(damage),
+                    target,
+                    attacker: explosion.owner,
+                    weapon: explosion.weapon.clone(),
+                });
+                if let Some(effect) = &explosion.details.status_effect {
+                    status_effect_channel.single_write(StatusEffectEvent {
+                        target,
+                        effect: effect.clone(),
+                        source: explosion.owner,
+                    });
+                }
+            }
+
+            broadcast_explosion(&client_registry, &mut net, &mut replay_channel, ExplosionNotification {
+                position: explosion.position,
+                radius: explosion.details.radius,
+            });
+        }
+    }
+}
+
+fn broadcast_explosion(
+    client_registry: &ClientRegistry,
+    net: &mut TransportResource,
+    replay_channel: &mut EventChannel<ReplayEvent>,
+    notification: ExplosionNotification,
+) {
+    let packet = PacketType::Explosion(notification);
+    let msg = serialize(&packet).expect("ExplosionNotification could not be serialized");
+    client_registry.get_clients().iter().for_each(|&handle| {
+        net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick)
+    });
+    replay_channel.single_write(ReplayEvent(packet));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::Error;
+    use amethyst::core::math::Point2;
+    use amethyst::ecs::Entity;
+    use amethyst::prelude::{World, WorldExt, Builder};
+    use amethyst_test::prelude::*;
+    use westiny_common::explosion::{ExplosionDetails, Falloff};
+
+    fn place(x: f32, y: f32) -> Transform {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(Meter(x).into_pixel(), Meter(y).into_pixel(), 0.0);
+        transform
+    }
+
+    fn create_target(world: &mut World, x: f32, y: f32) -> Entity {
+        world.create_entity()
+            .with(place(x, y))
+            .with(BoundingCircle { radius: Meter(0.5) })
+            .with(Velocity::default())
+            .with(Health(100))
+            .build()
+    }
+
+    #[test]
+    fn explosion_damages_entities_in_range() -> Result<(), Error> {
+        amethyst::start_logger(Default::default());
+        AmethystApplication::blank()
+            .with_setup(|world: &mut World| {
+                world.register::<Transform>();
+                world.register::<BoundingCircle>();
+                world.register::<Health>();
+                world.register::<Velocity>();
+            })
+            .with_resource(EventChannel::<DamageEvent>::new())
+            .with_setup(|world: &mut World| {
+                let reader = world.fetch_mut::<EventChannel<DamageEvent>>().register_reader();
+                world.insert(reader);
+            })
+            .with_resource(ClientRegistry::new(1))
+            .with_resource(TransportResource::new())
+            .with_system_desc(ExplosionSystemDesc::default(), "explosion", &[])
+            .with_effect(|world: &mut World| {
+                let in_range = create_target(world, 1.0, 1.0);
+                // Only the edge of its bounding circle is in range
+                let touched = create_target(world, 2.3, 0.0);
+                create_target(world, 5.0, 0.0);
+
+                // Sheltered by an obstacle
+                create_target(world, 0.0, -2.0);
+                world.create_entity()
+                    .with(place(0.0, -1.0))
+                    .with(BoundingCircle { radius: Meter(0.5) })
+                    .build();
+
+                // The owner is not hurt by its own explosion
+                let owner = create_target(world, -1.0, 0.0);
+
+                world.insert(EffectReturn(vec![in_range, touched]));
+
+                world.fetch_mut::<EventChannel<ExplosionEvent>>().single_write(ExplosionEvent {
+                    position: Point2::new(Meter(0.0), Meter(0.0)),
+                    details: ExplosionDetails { radius: Meter(2.0), damage: 40, falloff: Falloff::Constant, self_damage: false, status_effect: None },
+                    owner: Some(owner),
+                    weapon: None,
+                });
+            })
+            .with_assertion(|world: &mut World| {
+                let expected = world.read_resource::<EffectReturn<Vec<Entity>>>().0.clone();
+
+                let channel = world.fetch::<EventChannel<DamageEvent>>();
+                let mut reader = world.fetch_mut::<ReaderId<DamageEvent>>();
+                let events: Vec<_> = channel.read(&mut reader).collect();
+                assert!(events.iter().all(|event| event.damage.0 == 40));
+                let targets: Vec<_> = events.iter().map(|event| event.target).collect();
+                assert_eq!(targets, expected);
+            })
+            .run()
+    }
+}
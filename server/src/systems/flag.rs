@@ -0,0 +1,343 @@
+use amethyst::core::{Time, Transform};
+use amethyst::core::math::Vector2;
+use amethyst::ecs::{System, ReadStorage, WriteStorage, ReadExpect, Read, Write, WriteExpect, Entities, Entity, Join};
+use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
+use amethyst::shrev::EventChannel;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::components::{Client, Eliminated, Flag, FlagLocation, Input, InputFlags, NetworkId, Player, Team};
+use crate::resources::{ClientRegistry, GameRules, MatchState, ReplayEvent, Scoreboard};
+use westiny_common::metric_dimension::length::Meter;
+use westiny_common::network::{FlagState, FlagStatus, PacketType, PlayerNotification};
+use westiny_common::{serialize, PlayerName};
+
+/// Players closer than this to a flag or a base touch it
+const FLAG_TOUCH_RADIUS: Meter = Meter(1.0);
+/// Players pressing USE reach the flags from this far
+const FLAG_USE_RADIUS: Meter = Meter(2.0);
+
+/// A living player who can interact with the flags
+struct Contender {
+    entity: Entity,
+    position: Vector2<f32>,
+    team: Team,
+    /// Pressing the USE button
+    using: bool,
+}
+
+#[derive(Debug, PartialEq)]
+enum FlagEvent {
+    PickedUp(Entity),
+    Dropped,
+    Returned,
+    Captured(Entity),
+}
+
+/// Runs the capture the flag objective: enemy flags are picked up by touching them or by pressing
+/// USE next to them, dropped when the carrier dies and captured by bringing them to the own base
+/// while the own flag is at home.
+/// A dropped flag is returned when a teammate reaches it the same way or after a while.
+#[derive(Default)]
+pub struct FlagSystem {
+    round: u32,
+    last_status: Vec<FlagStatus>,
+    client_count: usize,
+}
+
+impl<'s> System<'s> for FlagSystem {
+    type SystemData = (
+        Entities<'s>,
+        WriteStorage<'s, Flag>,
+        WriteStorage<'s, Transform>,
+        ReadStorage<'s, NetworkId>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, Client>,
+        ReadStorage<'s, Eliminated>,
+        ReadStorage<'s, Input>,
+        Read<'s, MatchState>,
+        Write<'s, Scoreboard>,
+        ReadExpect<'s, GameRules>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Write<'s, EventChannel<ReplayEvent>>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            mut flags,
+            mut transforms,
+            network_ids,
+            players,
+            teams,
+            clients,
+            eliminates,
+            inputs,
+            match_state,
+            mut scoreboard,
+            rules,
+            client_registry,
+            mut net,
+            mut replay_channel,
+            time,
+        ) = data;
+
+        let now = time.absolute_time();
+
+        if self.round != match_state.round {
+            self.round = match_state.round;
+            for (flag, transform) in (&mut flags, &mut transforms).join() {
+                flag.location = FlagLocation::AtBase;
+                transform.set_translation_x(flag.base.x.into_pixel());
+                transform.set_translation_y(flag.base.y.into_pixel());
+            }
+        }
+
+        if !match_state.is_intermission() {
+            let contenders: Vec<_> = (&entities, &players, &teams, &transforms, (&inputs).maybe(), !&eliminates).join()
+                .map(|(entity, _, team, transform, input, _)| Contender {
+                    entity,
+                    position: transform.translation().xy(),
+                    team: *team,
+                    using: input.map_or(false, |input| input.flags.intersects(InputFlags::USE)),
+                })
+                .collect();
+            let bases: HashMap<_, _> = (&flags).join()
+                .map(|flag| (flag.team, (pixel_position(&flag.base), flag.location == FlagLocation::AtBase)))
+                .collect();
+
+            for (flag, transform) in (&mut flags, &mut transforms).join() {
+                let position = transform.translation().xy();
+                let event = flag_event(flag, &position, &contenders, &bases, now);
+
+                if let Some(event) = &event {
+                    log::debug!("{:?} flag: {:?}", flag.team, event);
+                    let player_name = |entity: Entity| clients.get(entity)
+                        .and_then(|client| client_registry.find_client(client.id))
+                        .map(|handle| handle.player_name.to_string())
+                        .unwrap_or_default();
+                    let message = match *event {
+                        FlagEvent::PickedUp(carrier) => format!("{} took the {:?} flag.", player_name(carrier), flag.team),
+                        FlagEvent::Dropped => format!("The {:?} flag was dropped.", flag.team),
+                        FlagEvent::Returned => format!("The {:?} flag was returned.", flag.team),
+                        FlagEvent::Captured(carrier) => format!("{} captured the {:?} flag!", player_name(carrier), flag.team),
+                    };
+                    broadcast_notification(&client_registry, &mut net, &mut replay_channel, message);
+                }
+
+                match event {
+                    Some(FlagEvent::PickedUp(carrier)) => flag.location = FlagLocation::Carried(carrier),
+                    Some(FlagEvent::Dropped) => flag.location = FlagLocation::Dropped { return_at: now + rules.flag_return_time.into_duration() },
+                    Some(FlagEvent::Returned) => flag.location = FlagLocation::AtBase,
+                    Some(FlagEvent::Captured(carrier)) => {
+                        flag.location = FlagLocation::AtBase;
+                        if match_state.is_live() {
+                            let carrier_team = teams.get(carrier).copied();
+                            if let (Some(client), Some(team)) = (clients.get(carrier), carrier_team) {
+                                scoreboard.add_capture(client.id, team);
+                            }
+                        }
+                    }
+                    None => {}
+                }
+
+                let new_position = match flag.location {
+                    FlagLocation::AtBase => Some(pixel_position(&flag.base)),
+                    FlagLocation::Carried(carrier) => contenders.iter()
+                        .find(|contender| contender.entity == carrier)
+                        .map(|contender| contender.position),
+                    FlagLocation::Dropped { .. } => None,
+                };
+                if let Some(new_position) = new_position {
+                    transform.set_translation_x(new_position.x);
+                    transform.set_translation_y(new_position.y);
+                }
+            }
+        }
+
+        let status = flag_status(&flags, &network_ids, &clients, &client_registry, &scoreboard);
+        if status != self.last_status || client_registry.client_count() != self.client_count {
+            self.client_count = client_registry.client_count();
+            let packet = PacketType::FlagStatus(status.clone());
+            let msg = serialize(&packet).expect("FlagStatus could not be serialized");
+            client_registry.get_clients().iter().for_each(|&handle| {
+                net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick)
+            });
+            replay_channel.single_write(ReplayEvent(packet));
+            self.last_status = status;
+        }
+    }
+}
+
+fn flag_event(
+    flag: &Flag,
+    position: &Vector2<f32>,
+    contenders: &[Contender],
+    bases: &HashMap<Team, (Vector2<f32>, bool)>,
+    now: Duration,
+) -> Option<FlagEvent> {
+    let touching = |contender: &&Contender| reaches(contender, position);
+    match flag.location {
+        FlagLocation::AtBase => contenders.iter()
+            .filter(touching)
+            .find(|contender| contender.team != flag.team)
+            .map(|contender| FlagEvent::PickedUp(contender.entity)),
+        FlagLocation::Carried(carrier) => {
+            let carrier = match contenders.iter().find(|contender| contender.entity == carrier) {
+                Some(carrier) => carrier,
+                // Died or left the game
+                None => return Some(FlagEvent::Dropped),
+            };
+            match bases.get(&carrier.team) {
+                Some((own_base, own_flag_at_home)) if *own_flag_at_home && is_touching(&carrier.position, own_base) =>
+                    Some(FlagEvent::Captured(carrier.entity)),
+                _ => None,
+            }
+        }
+        FlagLocation::Dropped { return_at } => {
+            let toucher = contenders.iter().find(touching);
+            match toucher {
+                Some(contender) if contender.team == flag.team => Some(FlagEvent::Returned),
+                Some(contender) => Some(FlagEvent::PickedUp(contender.entity)),
+                None if return_at <= now => Some(FlagEvent::Returned),
+                None => None,
+            }
+        }
+    }
+}
+
+fn is_touching(a: &Vector2<f32>, b: &Vector2<f32>) -> bool {
+    (a - b).magnitude() <= FLAG_TOUCH_RADIUS.into_pixel()
+}
+
+/// The flag is touched, or is close enough while the contender is pressing USE
+fn reaches(contender: &Contender, flag_position: &Vector2<f32>) -> bool {
+    is_touching(&contender.position, flag_position)
+        || (contender.using && (contender.position - flag_position).magnitude() <= FLAG_USE_RADIUS.into_pixel())
+}
+
+fn pixel_position(position: &Vector2<Meter>) -> Vector2<f32> {
+    Vector2::new(position.x.into_pixel(), position.y.into_pixel())
+}
+
+fn flag_status(
+    flags: &WriteStorage<'_, Flag>,
+    network_ids: &ReadStorage<'_, NetworkId>,
+    clients: &ReadStorage<'_, Client>,
+    client_registry: &ClientRegistry,
+    scoreboard: &Scoreboard,
+) -> Vec<FlagStatus> {
+    (flags, network_ids).join()
+        .map(|(flag, network_id)| FlagStatus {
+            network_id: *network_id,
+            team: flag.team,
+            state: match flag.location {
+                FlagLocation::AtBase => FlagState::AtBase,
+                FlagLocation::Carried(carrier) => FlagState::Carried {
+                    carrier: clients.get(carrier)
+                        .and_then(|client| client_registry.find_client(client.id))
+                        .map(|handle| handle.player_name.clone())
+                        .unwrap_or_else(|| PlayerName(String::new())),
+                },
+                FlagLocation::Dropped { .. } => FlagState::Dropped,
+            },
+            // Score of the team owning the flag
+            captures: scoreboard.team_captures(flag.team),
+        })
+        .collect()
+}
+
+fn broadcast_notification(
+    client_registry: &ClientRegistry,
+    net: &mut TransportResource,
+    replay_channel: &mut EventChannel<ReplayEvent>,
+    message: String,
+) {
+    let packet = PacketType::Notification(PlayerNotification { message });
+    let msg = serialize(&packet).expect("PlayerNotification could not be serialized");
+    client_registry.get_clients().iter().for_each(|&handle| {
+        net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick)
+    });
+    replay_channel.single_write(ReplayEvent(packet));
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::ecs::{World, WorldExt, Builder};
+
+    fn red_flag(location: FlagLocation) -> Flag {
+        Flag { team: Team::Red, base: Vector2::new(Meter(0.0), Meter(0.0)), location }
+    }
+
+    fn bases(blue_flag_at_home: bool) -> HashMap<Team, (Vector2<f32>, bool)> {
+        let mut bases = HashMap::new();
+        bases.insert(Team::Red, (Vector2::new(0.0, 0.0), true));
+        bases.insert(Team::Blue, (Vector2::new(1000.0, 0.0), blue_flag_at_home));
+        bases
+    }
+
+    #[test]
+    fn flag_at_base_is_taken_by_enemy_only() {
+        let mut world = World::new();
+        let red = world.create_entity().build();
+        let blue = world.create_entity().build();
+
+        let defender = [Contender { entity: red, position: Vector2::new(0.0, 0.0), team: Team::Red, using: false }];
+        assert_eq!(flag_event(&red_flag(FlagLocation::AtBase), &Vector2::new(0.0, 0.0), &defender, &bases(true), Duration::from_secs(0)), None);
+
+        let attacker = [Contender { entity: blue, position: Vector2::new(10.0, 0.0), team: Team::Blue, using: false }];
+        assert_eq!(flag_event(&red_flag(FlagLocation::AtBase), &Vector2::new(0.0, 0.0), &attacker, &bases(true), Duration::from_secs(0)),
+                   Some(FlagEvent::PickedUp(blue)));
+    }
+
+    #[test]
+    fn flag_is_reached_from_further_by_pressing_use() {
+        let mut world = World::new();
+        let blue = world.create_entity().build();
+        let position = Vector2::new(Meter(1.5).into_pixel(), 0.0);
+
+        let walker = [Contender { entity: blue, position, team: Team::Blue, using: false }];
+        assert_eq!(flag_event(&red_flag(FlagLocation::AtBase), &Vector2::new(0.0, 0.0), &walker, &bases(true), Duration::from_secs(0)), None);
+
+        let user = [Contender { entity: blue, position, team: Team::Blue, using: true }];
+        assert_eq!(flag_event(&red_flag(FlagLocation::AtBase), &Vector2::new(0.0, 0.0), &user, &bases(true), Duration::from_secs(0)),
+                   Some(FlagEvent::PickedUp(blue)));
+    }
+
+    #[test]
+    fn flag_is_dropped_when_carrier_is_gone() {
+        let mut world = World::new();
+        let blue = world.create_entity().build();
+        assert_eq!(flag_event(&red_flag(FlagLocation::Carried(blue)), &Vector2::new(500.0, 0.0), &[], &bases(true), Duration::from_secs(0)),
+                   Some(FlagEvent::Dropped));
+    }
+
+    #[test]
+    fn flag_is_captured_only_while_own_flag_is_at_home() {
+        let mut world = World::new();
+        let blue = world.create_entity().build();
+        let carrier = [Contender { entity: blue, position: Vector2::new(1000.0, 0.0), team: Team::Blue, using: false }];
+        let flag = red_flag(FlagLocation::Carried(blue));
+
+        assert_eq!(flag_event(&flag, &Vector2::new(1000.0, 0.0), &carrier, &bases(true), Duration::from_secs(0)),
+                   Some(FlagEvent::Captured(blue)));
+        assert_eq!(flag_event(&flag, &Vector2::new(1000.0, 0.0), &carrier, &bases(false), Duration::from_secs(0)), None);
+    }
+
+    #[test]
+    fn dropped_flag_is_returned_by_teammate_or_in_time() {
+        let mut world = World::new();
+        let red = world.create_entity().build();
+        let flag = red_flag(FlagLocation::Dropped { return_at: Duration::from_secs(30) });
+        let position = Vector2::new(500.0, 0.0);
+
+        let teammate = [Contender { entity: red, position, team: Team::Red, using: false }];
+        assert_eq!(flag_event(&flag, &position, &teammate, &bases(true), Duration::from_secs(10)), Some(FlagEvent::Returned));
+        assert_eq!(flag_event(&flag, &position, &[], &bases(true), Duration::from_secs(10)), None);
+        assert_eq!(flag_event(&flag, &position, &[], &bases(true), Duration::from_secs(30)), Some(FlagEvent::Returned));
+    }
+}
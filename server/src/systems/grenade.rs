@@ -0,0 +1,116 @@
+use amethyst::core::{Transform, Time};
+use amethyst::core::math::{Point2, Vector2};
+use amethyst::ecs::{System, ReadStorage, WriteStorage, ReadExpect, Read, Write, Entities, Join};
+use amethyst::shrev::EventChannel;
+
+use crate::components::{Grenade, Owner, Velocity, WeaponName};
+use westiny_common::events::{EntityDelete, ExplosionEvent};
+use westiny_common::metric_dimension::{MeterPerSec, to_meter_vec};
+use westiny_common::metric_dimension::length::normalize;
+use westiny_common::resources::collision::Collisions;
+
+/// Moves the thrown grenades: they bounce off the bodies they collide with, slow down with
+/// friction and explode when their fuse burns down.
+pub struct GrenadeSystem;
+
+impl<'s> System<'s> for GrenadeSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Grenade>,
+        WriteStorage<'s, Velocity>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Owner>,
+        ReadStorage<'s, WeaponName>,
+        ReadExpect<'s, Collisions>,
+        Write<'s, EventChannel<EntityDelete>>,
+        Write<'s, EventChannel<ExplosionEvent>>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            grenades,
+            mut velocities,
+            transforms,
+            owners,
+            weapon_names,
+            collisions,
+            mut entity_delete_channel,
+            mut explosion_channel,
+            time,
+        ) = data;
+
+        for collision in &collisions.0 {
+            if let (Some(grenade), Some(velocity)) = (grenades.get(collision.collider), velocities.get_mut(collision.collider)) {
+                let normal = normalize(collision.vector);
+                velocity.0 = bounce(&velocity.0, &normal, grenade.details.bounciness);
+            }
+        }
+
+        for (entity, grenade, velocity, transform) in (&entities, &grenades, &mut velocities, &transforms).join() {
+            velocity.0 = apply_friction(&velocity.0, grenade.details.friction, time.delta_seconds());
+
+            if time.absolute_time() >= grenade.detonates_at {
+                explosion_channel.single_write(ExplosionEvent {
+                    position: Point2 { coords: to_meter_vec(transform.translation().xy()) },
+                    details: grenade.explosion.clone(),
+                    owner: owners.get(entity).map(|owner| owner.0),
+                    weapon: weapon_names.get(entity).map(|name| name.0.clone()),
+                });
+                entity_delete_channel.single_write(EntityDelete { entity_id: entity });
+            }
+        }
+    }
+}
+
+/// Reflects the velocity from a surface. `normal` is the unit vector pointing toward the surface.
+fn bounce(velocity: &Vector2<MeterPerSec>, normal: &Vector2<f32>, bounciness: f32) -> Vector2<MeterPerSec> {
+    let velocity_f32 = Vector2::new(velocity.x.0, velocity.y.0);
+    let approach_speed = velocity_f32.dot(normal);
+    // Already moving away, e.g. still overlapping after the previous bounce
+    if approach_speed <= 0.0 {
+        return *velocity;
+    }
+
+    let reflected = velocity_f32 - (1.0 + bounciness) * approach_speed * normal;
+    Vector2::new(MeterPerSec(reflected.x), MeterPerSec(reflected.y))
+}
+
+/// Slows down the velocity by `deceleration` [m/s^2] without turning it around
+fn apply_friction(velocity: &Vector2<MeterPerSec>, deceleration: f32, delta_seconds: f32) -> Vector2<MeterPerSec> {
+    let velocity_f32 = Vector2::new(velocity.x.0, velocity.y.0);
+    let speed = velocity_f32.norm();
+    if speed <= 0.0 {
+        return *velocity;
+    }
+
+    let new_speed = (speed - deceleration * delta_seconds).max(0.0);
+    let slowed = velocity_f32 * (new_speed / speed);
+    Vector2::new(MeterPerSec(slowed.x), MeterPerSec(slowed.y))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn grenade_bounces_back_from_wall() {
+        let velocity = Vector2::new(MeterPerSec(4.0), MeterPerSec(-2.0));
+        let wall_on_the_right = Vector2::new(1.0, 0.0);
+
+        assert_eq!(bounce(&velocity, &wall_on_the_right, 1.0), Vector2::new(MeterPerSec(-4.0), MeterPerSec(-2.0)));
+        assert_eq!(bounce(&velocity, &wall_on_the_right, 0.5), Vector2::new(MeterPerSec(-2.0), MeterPerSec(-2.0)));
+
+        let wall_on_the_left = Vector2::new(-1.0, 0.0);
+        assert_eq!(bounce(&velocity, &wall_on_the_left, 1.0), velocity, "Moving away is not affected");
+    }
+
+    #[test]
+    fn friction_stops_grenade() {
+        let velocity = Vector2::new(MeterPerSec(0.0), MeterPerSec(4.0));
+
+        assert_eq!(apply_friction(&velocity, 2.0, 0.5), Vector2::new(MeterPerSec(0.0), MeterPerSec(3.0)));
+        assert_eq!(apply_friction(&velocity, 20.0, 0.5), Vector2::new(MeterPerSec(0.0), MeterPerSec(0.0)));
+    }
+}
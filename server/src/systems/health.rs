@@ -1,13 +1,13 @@
 use amethyst::{
     derive::SystemDesc,
-    ecs::{System, SystemData, Read, ReadStorage, WriteStorage},
+    ecs::{System, SystemData, Read, Write, ReadStorage, WriteStorage, Entities, Entity, Join},
     shrev::{ReaderId, EventChannel},
 };
 
 use derive_new::new;
-use westiny_common::components::Health;
-use westiny_common::network::PacketType;
-use crate::resources::{ClientRegistry, StreamId, ClientID};
+use westiny_common::components::{Armor, Health, MaxHealth, Player, Team};
+use westiny_common::network::{HealthState, PacketType};
+use crate::resources::{ClientRegistry, StreamId, ClientID, GameRules, KillEvent, MatchState};
 use amethyst::core::ecs::{ReadExpect, WriteExpect};
 use crate::components::{Client, Eliminated};
 use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
@@ -17,50 +17,157 @@ use anyhow;
 use amethyst::core::Time;
 use westiny_common::events::DamageEvent;
 use westiny_common::network::PlayerUpdate;
+use std::collections::HashMap;
+
+/// Attackers hurting the victim within this time before the kill get an assist
+const ASSIST_WINDOW_SEC: f64 = 10.0;
 
 #[derive(SystemDesc, new)]
 #[system_desc(name(HealthSystemDesc))]
 pub struct HealthSystem {
     #[system_desc(event_channel_reader)]
     reader: ReaderId<DamageEvent>,
+
+    /// Who damaged whom and when, kept until the victim dies
+    #[system_desc(skip)]
+    #[new(default)]
+    damage_history: HashMap<Entity, Vec<(Entity, f64)>>,
+
+    /// When the players were hurt last time, regeneration waits for it
+    #[system_desc(skip)]
+    #[new(default)]
+    last_damaged: HashMap<Entity, f64>,
+
+    /// Regenerated health not added yet, as health is counted in whole points
+    #[system_desc(skip)]
+    #[new(default)]
+    regenerated: HashMap<Entity, f32>,
 }
 
 impl<'s> System<'s> for HealthSystem {
     type SystemData = (
         Read<'s, EventChannel<DamageEvent>>,
+        Entities<'s>,
         WriteStorage<'s, Health>,
+        ReadStorage<'s, MaxHealth>,
+        WriteStorage<'s, Armor>,
+        ReadStorage<'s, Player>,
         ReadStorage<'s, Client>,
         WriteStorage<'s, Eliminated>,
         ReadExpect<'s, ClientRegistry>,
         WriteExpect<'s, TransportResource>,
         ReadExpect<'s, Time>,
+        ReadStorage<'s, Team>,
+        ReadExpect<'s, GameRules>,
+        Read<'s, MatchState>,
+        Write<'s, EventChannel<KillEvent>>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
         let (
             damage_event_channel,
+            entities,
             mut healths,
+            max_healths,
+            mut armors,
+            players,
             clients,
             mut eliminates,
             client_registry,
             mut transport,
             time,
+            teams,
+            rules,
+            match_state,
+            mut kill_event_channel,
         ) = data;
 
+        let now = time.absolute_time_seconds();
         for damage_event in damage_event_channel.read(&mut self.reader) {
+            if match_state.is_intermission() {
+                continue;
+            }
+
+            let is_teammate = match damage_event.attacker {
+                Some(attacker) if attacker != damage_event.target => {
+                    is_same_team(teams.get(attacker), teams.get(damage_event.target))
+                }
+                _ => false,
+            };
+            if is_teammate && !rules.friendly_fire {
+                continue;
+            }
+
             if let Some(health) = healths.get_mut(damage_event.target) {
-                let health_drained = health.0 <= damage_event.damage.0;
+                if let Some(attacker) = damage_event.attacker.filter(|&attacker| attacker != damage_event.target) {
+                    let history = self.damage_history.entry(damage_event.target).or_default();
+                    history.retain(|&(_, damaged_at)| now - damaged_at <= ASSIST_WINDOW_SEC);
+                    history.push((attacker, now));
+                }
+                self.last_damaged.insert(damage_event.target, now);
+                self.regenerated.remove(&damage_event.target);
+
+                let damage = match armors.get_mut(damage_event.target) {
+                    Some(armor) => armor.absorb(damage_event.damage, rules.armor_absorption),
+                    None => damage_event.damage,
+                };
+
+                let health_drained = health.0 <= damage.0;
+                if health_drained && health.0 > 0 {
+                    let history = self.damage_history.remove(&damage_event.target).unwrap_or_default();
+                    kill_event_channel.single_write(KillEvent {
+                        victim: damage_event.target,
+                        killer: damage_event.attacker,
+                        weapon: damage_event.weapon.clone(),
+                        assists: assisting_attackers(&history, damage_event.attacker, now),
+                    });
+                }
                 if health_drained {
                     health.0 = 0;
                     if let Err(err) = eliminates.insert(damage_event.target, Eliminated { elimination_time_sec: time.absolute_time_seconds() }) {
                         log::error!("Component 'Eliminated' could not be inserted to entity. error: {:?}", err);
                     }
                 } else {
-                    *health -= damage_event.damage;
+                    *health -= damage;
                 }
                 if let Some(client) = clients.get(damage_event.target) {
-                    log::debug!("Client [id: {:?}] took {} damage", client.id, damage_event.damage.0);
-                    if let Err(err) = HealthSystem::notify_client(&client_registry, &mut transport, health.clone(), &client.id) {
+                    log::debug!("Client [id: {:?}] took {} damage", client.id, damage.0);
+                    let state = health_state(*health, max_healths.get(damage_event.target), armors.get(damage_event.target));
+                    if let Err(err) = send_health_update(&client.id, &client_registry, &mut transport, state) {
+                        log::error!("Error while sending Health update to client: {}", err);
+                    }
+                }
+            }
+        }
+
+        self.last_damaged.retain(|&entity, _| entities.is_alive(entity));
+        self.regenerated.retain(|&entity, _| entities.is_alive(entity));
+
+        let regen = match &rules.health_regen {
+            Some(regen) if !match_state.is_intermission() => regen,
+            _ => return,
+        };
+        for (entity, health, max_health, _, _, client) in
+                (&entities, &mut healths, &max_healths, &players, !&eliminates, (&clients).maybe()).join() {
+            let out_of_combat = self.last_damaged.get(&entity)
+                .map_or(true, |&damaged_at| now - damaged_at >= regen.delay.0 as f64);
+            if !out_of_combat || health.0 >= max_health.0 {
+                self.regenerated.remove(&entity);
+                continue;
+            }
+
+            let regenerated = self.regenerated.entry(entity).or_default();
+            *regenerated += regen.health_per_sec * time.delta_seconds();
+            let whole_points = regenerated.floor();
+            if whole_points < 1.0 {
+                continue;
+            }
+            *regenerated -= whole_points;
+
+            if health.heal(whole_points as u16, *max_health) {
+                if let Some(client) = client {
+                    let state = health_state(*health, Some(max_health), armors.get(entity));
+                    if let Err(err) = send_health_update(&client.id, &client_registry, &mut transport, state) {
                         log::error!("Error while sending Health update to client: {}", err);
                     }
                 }
@@ -69,26 +176,83 @@ impl<'s> System<'s> for HealthSystem {
     }
 }
 
-impl HealthSystem {
-    fn notify_client(client_registry: &ClientRegistry,
-                     transport: &mut TransportResource,
-                     new_health: Health,
-                     client: &ClientID) -> anyhow::Result<()> {
-        let client_handle = {
-            client_registry.find_client(*client)
-                .ok_or(anyhow::anyhow!("Client [id: {:?}] not found in registry", client))?
-        };
+pub(super) fn is_same_team(attacker: Option<&Team>, target: Option<&Team>) -> bool {
+    match (attacker, target) {
+        (Some(attacker), Some(target)) => attacker == target,
+        _ => false,
+    }
+}
+
+/// Every attacker hurting the victim recently, except the killer
+fn assisting_attackers(history: &[(Entity, f64)], killer: Option<Entity>, now: f64) -> Vec<Entity> {
+    let mut assists = Vec::new();
+    for &(attacker, damaged_at) in history {
+        if now - damaged_at <= ASSIST_WINDOW_SEC && Some(attacker) != killer && !assists.contains(&attacker) {
+            assists.push(attacker);
+        }
+    }
+    assists
+}
+
+pub(super) fn health_state(health: Health, max_health: Option<&MaxHealth>, armor: Option<&Armor>) -> HealthState {
+    HealthState {
+        health,
+        max_health: max_health.copied().unwrap_or(MaxHealth(health.0)),
+        armor: armor.copied().unwrap_or_default(),
+    }
+}
+
+/// Sends the health and armor of the player to its client
+pub(super) fn send_health_update(client: &ClientID,
+                                 client_registry: &ClientRegistry,
+                                 transport: &mut TransportResource,
+                                 state: HealthState) -> anyhow::Result<()> {
+    let client_handle = {
+        client_registry.find_client(*client)
+            .ok_or(anyhow::anyhow!("Client [id: {:?}] not found in registry", client))?
+    };
+
+    let payload = serialize(&PacketType::PlayerUpdate(PlayerUpdate::HealthUpdate(state)))
+        .map_err(|err| anyhow::Error::new(err))?;
+
+    transport.send_with_requirements(
+        client_handle.addr,
+        &payload,
+        DeliveryRequirement::ReliableSequenced(StreamId::HealthUpdate.into()),
+        UrgencyRequirement::OnTick
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn teammates_are_recognized() {
+        assert!(is_same_team(Some(&Team::Red), Some(&Team::Red)));
+        assert!(!is_same_team(Some(&Team::Red), Some(&Team::Blue)));
+        assert!(!is_same_team(None, Some(&Team::Blue)), "Damage from environment");
+        assert!(!is_same_team(None, None), "No teams in deathmatch");
+    }
 
-        let payload = serialize(&PacketType::PlayerUpdate(PlayerUpdate::HealthUpdate(new_health)))
-            .map_err(|err| anyhow::Error::new(err))?;
+    #[test]
+    fn recent_attackers_get_assist() {
+        use amethyst::ecs::{World, WorldExt, Builder};
 
-        transport.send_with_requirements(
-            client_handle.addr,
-            &payload,
-            DeliveryRequirement::ReliableSequenced(StreamId::HealthUpdate.into()),
-            UrgencyRequirement::OnTick
-        );
+        let mut world = World::new();
+        let killer = world.create_entity().build();
+        let helper = world.create_entity().build();
+        let forgotten = world.create_entity().build();
 
-        Ok(())
+        let history = vec![
+            (forgotten, 1.0),
+            (helper, 12.0),
+            (killer, 13.0),
+            (helper, 14.0),
+            (killer, 15.0),
+        ];
+        assert_eq!(assisting_attackers(&history, Some(killer), 15.0), vec![helper]);
     }
 }
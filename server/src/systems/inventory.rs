@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+use amethyst::core::{Transform, Time};
+use amethyst::core::math::Vector2;
+use amethyst::ecs::{System, ReadStorage, WriteStorage, ReadExpect, WriteExpect, Read, Write, Entities, Entity, Join, LazyUpdate};
+use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
+use amethyst::shrev::EventChannel;
+
+use crate::components::{Armor, Client, Health, Input, InputFlags, MaxHealth, Pickup, Player, EntityType, MAX_ARMOR, weapon::{Holster, Weapon}};
+use crate::resources::{ClientID, ClientRegistry, NetworkIdSupplier, StreamId};
+use super::health::{health_state, send_health_update};
+use westiny_common::entities::spawn_pickup;
+use westiny_common::events::{EntityDelete, StatusEffectEvent};
+use westiny_common::metric_dimension::length::{Meter, magnitude};
+use westiny_common::metric_dimension::to_meter_vec;
+use westiny_common::network::{PacketType, PlayerUpdate};
+use westiny_common::serialize;
+
+/// Players can collect pickups closer than this
+const PICKUP_RADIUS: Meter = Meter(1.0);
+
+/// Lets players collect the items lying on the ground by pressing USE.
+/// When the inventory is full the active weapon is swapped with the picked up one.
+/// Medkits and armor are left on the ground while they would be of no use.
+/// Status effect pickups are applied on the player.
+#[derive(Default)]
+pub struct PickupSystem {
+    /// Players who held the USE button in the last frame. One press picks up one item.
+    use_held: HashSet<Entity>,
+}
+
+impl<'s> System<'s> for PickupSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Input>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Client>,
+        WriteStorage<'s, Holster>,
+        WriteStorage<'s, Health>,
+        ReadStorage<'s, MaxHealth>,
+        WriteStorage<'s, Armor>,
+        WriteStorage<'s, Pickup>,
+        Write<'s, EventChannel<EntityDelete>>,
+        Write<'s, EventChannel<StatusEffectEvent>>,
+        WriteExpect<'s, NetworkIdSupplier>,
+        ReadExpect<'s, LazyUpdate>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            players,
+            inputs,
+            transforms,
+            clients,
+            mut holsters,
+            mut healths,
+            max_healths,
+            mut armors,
+            mut pickups,
+            mut entity_delete_channel,
+            mut status_effect_channel,
+            mut net_id_supplier,
+            lazy,
+            client_registry,
+            mut net,
+            time,
+        ) = data;
+
+        for (player_entity, _player, input, player_transform, holster, health, max_health, armor, client) in
+                (&entities, &players, &inputs, &transforms, &mut holsters, &mut healths, &max_healths, &mut armors, (&clients).maybe()).join() {
+            if !input.flags.intersects(InputFlags::USE) {
+                self.use_held.remove(&player_entity);
+                continue;
+            }
+
+            if !self.use_held.insert(player_entity) {
+                // USE is held since the last frame
+                continue;
+            }
+
+            let nearest_pickup = (&entities, &pickups, &transforms).join()
+                .filter(|(_, pickup, _)| is_useful(pickup, health, max_health, armor))
+                .map(|(entity, _, transform)| (entity, distance(player_transform, transform)))
+                .filter(|(_, distance)| *distance <= PICKUP_RADIUS)
+                .min_by(|(_, d1), (_, d2)| d1.partial_cmp(d2).unwrap_or(std::cmp::Ordering::Equal))
+                .map(|(entity, _)| entity);
+
+            // Removing the component prevents others from collecting the same item in this frame
+            let pickup = match nearest_pickup.and_then(|entity| pickups.remove(entity).map(|pickup| (entity, pickup))) {
+                Some((pickup_entity, pickup)) => {
+                    entity_delete_channel.single_write(EntityDelete { entity_id: pickup_entity });
+                    pickup
+                }
+                None => continue,
+            };
+
+            let is_weapon = matches!(pickup, Pickup::Weapon(_));
+            let is_status_effect = matches!(pickup, Pickup::StatusEffect(_));
+            match pickup {
+                Pickup::Weapon(weapon) => {
+                    log::debug!("{:?} picked up {}", player_entity, weapon.details.name);
+                    if let Err(weapon) = holster.add(weapon) {
+                        if let Some(dropped) = holster.replace_active(weapon) {
+                            drop_weapon(dropped, player_transform, &entities, &lazy, &mut net_id_supplier, &time);
+                        }
+                    }
+                }
+                Pickup::Medkit(amount) => {
+                    health.heal(amount, *max_health);
+                    log::debug!("{:?} picked up a medkit, health: {}", player_entity, health.0);
+                }
+                Pickup::Armor(amount) => {
+                    armor.repair(amount);
+                    log::debug!("{:?} picked up armor, armor: {}", player_entity, armor.0);
+                }
+                Pickup::StatusEffect(effect) => {
+                    log::debug!("{:?} picked up {:?}", player_entity, effect.kind);
+                    // The client is informed by the status effect system
+                    status_effect_channel.single_write(StatusEffectEvent { target: player_entity, effect, source: None });
+                }
+            }
+
+            if let Some(client) = client.filter(|_| !is_status_effect) {
+                let result = if is_weapon {
+                    send_inventory_update(&client.id, &client_registry, &mut net, holster)
+                } else {
+                    send_health_update(&client.id, &client_registry, &mut net, health_state(*health, Some(max_health), Some(&*armor)))
+                };
+                if let Err(err) = result {
+                    log::error!("Failed to send pickup result to client {:?}. Error: {}", client.id, err);
+                }
+            }
+        }
+
+        // Forget players who do not exist anymore
+        self.use_held.retain(|&entity| entities.is_alive(entity));
+    }
+}
+
+/// Players do not pick up healing they do not need
+fn is_useful(pickup: &Pickup, health: &Health, max_health: &MaxHealth, armor: &Armor) -> bool {
+    match pickup {
+        Pickup::Weapon(_) => true,
+        Pickup::Medkit(_) => health.0 < max_health.0,
+        Pickup::Armor(_) => armor.0 < MAX_ARMOR,
+        Pickup::StatusEffect(_) => true,
+    }
+}
+
+fn distance(t1: &Transform, t2: &Transform) -> Meter {
+    let disposition: Vector2<f32> = (t1.translation() - t2.translation()).xy();
+    magnitude(to_meter_vec(disposition))
+}
+
+/// Places the weapon on the ground as a pickup entity
+pub fn drop_weapon(
+    weapon: Weapon,
+    position: &Transform,
+    entities: &Entities<'_>,
+    lazy: &LazyUpdate,
+    net_id_supplier: &mut NetworkIdSupplier,
+    time: &Time,
+) {
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(position.translation().x, position.translation().y, 0.0);
+
+    spawn_pickup(transform,
+                 Pickup::Weapon(weapon),
+                 net_id_supplier.next(EntityType::WeaponPickup),
+                 time.absolute_time(),
+                 lazy.create_entity(entities));
+}
+
+/// Sends the full inventory of the player to its client
+pub fn send_inventory_update(
+    client_id: &ClientID,
+    client_registry: &ClientRegistry,
+    net: &mut TransportResource,
+    holster: &Holster,
+) -> anyhow::Result<()> {
+    let payload = serialize(&PacketType::PlayerUpdate(PlayerUpdate::InventoryUpdate(holster.inventory_state())))
+        .map_err(|err| anyhow::anyhow!("Failed to serialize InventoryUpdate: {}", err))?;
+    let address = client_registry.find_client(*client_id).map(|handle| handle.addr)
+        .ok_or(anyhow::anyhow!("Client with id {:?} not found in registry", client_id))?;
+    net.send_with_requirements(address,
+                               &payload,
+                               DeliveryRequirement::ReliableSequenced(StreamId::InventoryUpdate.into()),
+                               UrgencyRequirement::OnTick);
+    Ok(())
+}
@@ -0,0 +1,72 @@
+use amethyst::core::{Transform, Time};
+use amethyst::ecs::{System, ReadStorage, ReadExpect, WriteExpect, Read, Entities, Entity, LazyUpdate};
+use std::time::Duration;
+
+use crate::components::{EntityType, Pickup};
+use crate::resources::{MapRotation, NetworkIdSupplier};
+use westiny_common::entities::place_item;
+use westiny_common::resources::map::{Item, ItemSpawn, MapMetadata};
+
+enum SpawnPointState {
+    Placed(Entity),
+    RespawnAt(Duration),
+}
+
+/// Places the medkits, armor and status effect pickups listed in the map metadata. Picked up items are placed back
+/// after the respawn time of their spawn point.
+#[derive(Default)]
+pub struct ItemSpawnSystem {
+    /// State of every spawn point in the order of the map metadata
+    spawn_points: Vec<SpawnPointState>,
+    /// The pickups of the previous map are deleted with it, every item is placed again on a new map
+    map_loads: u32,
+}
+
+impl<'s> System<'s> for ItemSpawnSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Pickup>,
+        ReadExpect<'s, MapMetadata>,
+        ReadExpect<'s, MapRotation>,
+        WriteExpect<'s, NetworkIdSupplier>,
+        ReadExpect<'s, LazyUpdate>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, pickups, map_metadata, map_rotation, mut net_id_supplier, lazy, time): Self::SystemData) {
+        let now = time.absolute_time();
+        if self.spawn_points.len() != map_metadata.item_spawns.len() || self.map_loads != map_rotation.loads() {
+            self.map_loads = map_rotation.loads();
+            self.spawn_points = map_metadata.item_spawns.iter()
+                .map(|_| SpawnPointState::RespawnAt(now))
+                .collect();
+        }
+
+        for (state, spawn) in self.spawn_points.iter_mut().zip(map_metadata.item_spawns.iter()) {
+            match *state {
+                SpawnPointState::Placed(entity) => {
+                    // The pickup component is removed right when the item is picked up
+                    if !entities.is_alive(entity) || !pickups.contains(entity) {
+                        *state = SpawnPointState::RespawnAt(now + spawn.respawn_time.into_duration());
+                    }
+                }
+                SpawnPointState::RespawnAt(respawn_at) if respawn_at <= now => {
+                    *state = SpawnPointState::Placed(place(spawn, &entities, &lazy, &mut net_id_supplier));
+                }
+                SpawnPointState::RespawnAt(_) => {}
+            }
+        }
+    }
+}
+
+fn place(spawn: &ItemSpawn, entities: &Entities<'_>, lazy: &LazyUpdate, net_id_supplier: &mut NetworkIdSupplier) -> Entity {
+    let mut transform = Transform::default();
+    transform.set_translation_xyz(spawn.x.into_pixel(), spawn.y.into_pixel(), 0.0);
+
+    let (pickup, entity_type) = match &spawn.item {
+        Item::Medkit(amount) => (Pickup::Medkit(*amount), EntityType::Medkit),
+        Item::Armor(amount) => (Pickup::Armor(*amount), EntityType::ArmorPickup),
+        Item::StatusEffect(effect) => (Pickup::StatusEffect(effect.clone()), EntityType::EffectPickup),
+    };
+    place_item(transform, pickup, net_id_supplier.next(entity_type), lazy.create_entity(entities))
+}
@@ -0,0 +1,275 @@
+use amethyst::core::{Time, Transform};
+use amethyst::ecs::{System, ReadStorage, ReadExpect, Read, Write, WriteExpect, Entities, Join};
+use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
+use amethyst::shrev::EventChannel;
+use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::components::{Client, Eliminated, NetworkId, Player, Respawn, Team};
+use crate::resources::{ClientRegistry, GameMode, GameRules, MapRotation, MatchState, Phase, ReplayEvent, Scoreboard, StreamId};
+use crate::systems::SpawnPlayerEvent;
+use super::score::collect_results;
+use super::spawn::is_respawn_due;
+use westiny_common::events::EntityDelete;
+use westiny_common::metric_dimension::Second;
+use westiny_common::network::{MatchPhase, MatchUpdate, PacketType, PlayerScore};
+use westiny_common::serialize;
+
+/// The players of a last man standing round are spawned during this time, the round cannot be
+/// won before it
+const SINGLE_LIFE_ROUND_GRACE: Duration = Duration::from_secs(3);
+
+/// Clients are reminded of the match state this often, so their countdowns stay in sync
+const UPDATE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Drives the match lifecycle: warmup until enough players join, live rounds with time and score
+/// limit, intermission showing the results, then a fresh map for the next round.
+#[derive(Default)]
+pub struct MatchSystem {
+    next_update_at: Duration,
+    /// The map loads seen so far, a new round starts on every newly loaded map
+    map_loads: u32,
+}
+
+impl<'s> System<'s> for MatchSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Client>,
+        ReadStorage<'s, NetworkId>,
+        ReadStorage<'s, Team>,
+        ReadStorage<'s, Eliminated>,
+        ReadStorage<'s, Respawn>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Player>,
+        Write<'s, MatchState>,
+        Write<'s, Scoreboard>,
+        WriteExpect<'s, MapRotation>,
+        ReadExpect<'s, GameRules>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Write<'s, EventChannel<EntityDelete>>,
+        Write<'s, EventChannel<SpawnPlayerEvent>>,
+        Write<'s, EventChannel<ReplayEvent>>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            clients,
+            network_ids,
+            teams,
+            eliminates,
+            respawns,
+            transforms,
+            players,
+            mut match_state,
+            mut scoreboard,
+            mut map_rotation,
+            rules,
+            client_registry,
+            mut net,
+            mut entity_delete_channel,
+            mut spawn_player_channel,
+            mut replay_channel,
+            time,
+        ) = data;
+
+        let now = time.absolute_time();
+        let has_enough_players = client_registry.player_count() >= rules.min_players;
+        // The respawn system is paused during the intermission only
+        let respawns_running = !match_state.is_intermission();
+        let map_loaded = map_rotation.loads() != self.map_loads;
+        self.map_loads = map_rotation.loads();
+
+        let next_phase = match &match_state.phase {
+            _ if map_loaded => {
+                if has_enough_players {
+                    Some(Phase::Live { ends_at: now + rules.round_time.into_duration() })
+                } else {
+                    Some(Phase::Warmup)
+                }
+            }
+            Phase::Warmup if has_enough_players => Some(Phase::Live { ends_at: now + rules.round_time.into_duration() }),
+            Phase::Live { ends_at } => {
+                let results = collect_results(&client_registry, &scoreboard, &clients, &teams);
+                let score_limit_reached = rules.score_limit
+                    .map_or(false, |limit| leading_score(&results, &scoreboard, rules.mode) >= limit);
+
+                let started_at = ends_at.checked_sub(rules.round_time.into_duration()).unwrap_or_default();
+                let survivors: Vec<_> = (&players, &clients, !&eliminates).join()
+                    .map(|(_, client, _)| client.id)
+                    .collect();
+                let last_man_standing = rules.has_single_life()
+                    && now >= started_at + SINGLE_LIFE_ROUND_GRACE
+                    && survivors.len() <= 1;
+
+                if *ends_at <= now || score_limit_reached || last_man_standing {
+                    let winner = if last_man_standing {
+                        survivors.first()
+                            .and_then(|&client_id| client_registry.find_client(client_id))
+                            .map(|handle| handle.player_name.clone())
+                    } else {
+                        None
+                    };
+                    Some(Phase::Intermission { ends_at: now + rules.intermission_time.into_duration(), results, winner })
+                } else {
+                    None
+                }
+            }
+            Phase::Intermission { ends_at, .. } if *ends_at <= now => {
+                // The players are spawned on the next map, so the round starts a frame after loading it
+                if !map_rotation.is_requested() {
+                    map_rotation.request_next();
+                }
+                None
+            }
+            _ => None,
+        };
+
+        if let Some(phase) = next_phase {
+            let round_started = matches!(phase, Phase::Live { .. });
+            if round_started {
+                match_state.round += 1;
+                log::info!("Round {} started", match_state.round);
+                scoreboard.reset();
+            }
+
+            if round_started || map_loaded {
+                // Everyone starts the round or the warmup on the new map at full health with the default loadout
+                for (entity, client, network_id, team, eliminated, respawn, transform)
+                        in (&entities, &clients, &network_ids, (&teams).maybe(), (&eliminates).maybe(), (&respawns).maybe(), (&transforms).maybe()).join() {
+                    if let Some(eliminated) = eliminated {
+                        if transform.is_some() {
+                            // The corpse is about to be replaced by a respawn placeholder, which is
+                            // restarted in the next round or respawns shortly anyway
+                            continue;
+                        }
+                        if respawns_running && respawn.map_or(false, |respawn| is_respawn_due(respawn, eliminated, &time)) {
+                            // The respawn system has already deleted and respawned the placeholder in this frame
+                            continue;
+                        }
+                    }
+                    entity_delete_channel.single_write(EntityDelete { entity_id: entity });
+                    spawn_player_channel.single_write(SpawnPlayerEvent {
+                        client: *client,
+                        network_id: *network_id,
+                        team: team.copied(),
+                    });
+                }
+            }
+            match_state.phase = phase;
+            self.next_update_at = now;
+        }
+
+        if self.next_update_at <= now {
+            self.next_update_at = now + UPDATE_INTERVAL;
+            let update = match_update(&match_state.phase, now, rules.min_players, client_registry.player_count());
+            let packet = PacketType::MatchUpdate(update);
+            let msg = serialize(&packet).expect("MatchUpdate could not be serialized");
+            client_registry.get_clients().iter().for_each(|&handle| {
+                net.send_with_requirements(
+                    handle.addr,
+                    &msg,
+                    DeliveryRequirement::ReliableSequenced(StreamId::MatchUpdate.into()),
+                    UrgencyRequirement::OnTick,
+                )
+            });
+            replay_channel.single_write(ReplayEvent(packet));
+        }
+    }
+}
+
+/// The best individual score, or the best team total in team modes.
+/// Flag captures count in capture the flag, kills otherwise.
+fn leading_score(results: &[PlayerScore], scoreboard: &Scoreboard, mode: GameMode) -> u32 {
+    match mode {
+        GameMode::Deathmatch => results.iter().map(|score| score.kills).max().unwrap_or(0),
+        // The captures of the players who have left count too
+        GameMode::CaptureTheFlag => Team::ALL.iter().map(|team| scoreboard.team_captures(*team)).max().unwrap_or(0),
+        _ => {
+            let mut team_scores = HashMap::new();
+            for score in results {
+                if let Some(team) = score.team {
+                    *team_scores.entry(team).or_insert(0) += score.kills;
+                }
+            }
+            team_scores.values().copied().max().unwrap_or(0)
+        }
+    }
+}
+
+fn match_update(phase: &Phase, now: Duration, min_players: usize, client_count: usize) -> MatchUpdate {
+    let time_left = |ends_at: &Duration| Some(Second::from(ends_at.checked_sub(now).unwrap_or_default()));
+    match phase {
+        Phase::Warmup => MatchUpdate {
+            phase: MatchPhase::Warmup { players_needed: min_players.saturating_sub(client_count) as u32 },
+            time_left: None,
+        },
+        Phase::Live { ends_at } => MatchUpdate {
+            phase: MatchPhase::Live,
+            time_left: time_left(ends_at),
+        },
+        Phase::Intermission { ends_at, results, winner } => MatchUpdate {
+            phase: MatchPhase::Intermission { results: results.clone(), winner: winner.clone() },
+            time_left: time_left(ends_at),
+        },
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::resources::ClientID;
+    use westiny_common::PlayerName;
+
+    fn score(name: &str, team: Option<Team>, kills: u32) -> PlayerScore {
+        PlayerScore { player_name: PlayerName(name.to_string()), team, kills, deaths: 0, assists: 0, captures: 0 }
+    }
+
+    #[test]
+    fn leading_score_is_the_best_player_without_teams() {
+        let results = vec![score("a", None, 3), score("b", None, 5), score("c", None, 4)];
+        assert_eq!(leading_score(&results, &Scoreboard::default(), GameMode::Deathmatch), 5);
+        assert_eq!(leading_score(&[], &Scoreboard::default(), GameMode::Deathmatch), 0);
+    }
+
+    #[test]
+    fn leading_score_is_the_best_team_total_in_team_modes() {
+        let results = vec![
+            score("a", Some(Team::Red), 3),
+            score("b", Some(Team::Blue), 5),
+            score("c", Some(Team::Red), 4),
+        ];
+        assert_eq!(leading_score(&results, &Scoreboard::default(), GameMode::TeamDeathmatch), 7);
+    }
+
+    #[test]
+    fn leading_score_is_the_most_team_captures_in_capture_the_flag() {
+        let results = vec![score("a", Some(Team::Red), 9), score("b", Some(Team::Blue), 1)];
+        // The capturers are not connected anymore
+        let mut scoreboard = Scoreboard::default();
+        scoreboard.add_capture(ClientID(1), Team::Blue);
+        scoreboard.add_capture(ClientID(2), Team::Blue);
+        scoreboard.add_capture(ClientID(3), Team::Blue);
+        scoreboard.add_capture(ClientID(4), Team::Red);
+        assert_eq!(leading_score(&results, &scoreboard, GameMode::CaptureTheFlag), 3);
+    }
+
+    #[test]
+    fn warmup_update_tells_the_missing_players() {
+        let update = match_update(&Phase::Warmup, Duration::from_secs(10), 4, 1);
+        match update.phase {
+            MatchPhase::Warmup { players_needed } => assert_eq!(players_needed, 3),
+            other => panic!("Unexpected phase: {:?}", other),
+        }
+        assert_eq!(update.time_left, None);
+    }
+
+    #[test]
+    fn live_update_tells_the_remaining_time() {
+        let update = match_update(&Phase::Live { ends_at: Duration::from_secs(70) }, Duration::from_secs(10), 2, 2);
+        assert!(matches!(update.phase, MatchPhase::Live));
+        assert_eq!(update.time_left, Some(Second(60.0)));
+    }
+}
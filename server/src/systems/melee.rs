@@ -0,0 +1,193 @@
+use amethyst::core::{Transform, Time};
+use amethyst::core::math::{Vector2, Vector3};
+use amethyst::ecs::{System, ReadStorage, WriteStorage, ReadExpect, Read, Write, Entities, Join};
+use amethyst::shrev::EventChannel;
+
+use crate::components::{BoundingCircle, Damage, Health, Input, InputFlags, Knockback, Melee};
+use westiny_common::collision::{Collider, check_arc_collision};
+use westiny_common::events::DamageEvent;
+use westiny_common::metric_dimension::length::normalize;
+use westiny_common::resources::MeleeDetails;
+
+/// Displayed in the kill feed for melee kills
+const MELEE_WEAPON_NAME: &str = "Melee";
+
+/// Performs melee attacks: entities with `Health` in the arc in front of the attacker
+/// take damage and are pushed away.
+pub struct MeleeSystem;
+
+impl<'s> System<'s> for MeleeSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Input>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, BoundingCircle>,
+        ReadStorage<'s, Health>,
+        WriteStorage<'s, Melee>,
+        WriteStorage<'s, Knockback>,
+        Write<'s, EventChannel<DamageEvent>>,
+        ReadExpect<'s, MeleeDetails>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, data: Self::SystemData) {
+        let (
+            entities,
+            inputs,
+            transforms,
+            bounds,
+            healths,
+            mut melees,
+            mut knockbacks,
+            mut damage_event_channel,
+            details,
+            time,
+        ) = data;
+
+        let half_angle = details.arc.to_radians() / 2.0;
+
+        for (attacker, input, attacker_transform, melee) in (&entities, &inputs, &transforms, &mut melees).join() {
+            if !input.flags.intersects(InputFlags::MELEE) {
+                melee.input_lifted = true;
+                continue;
+            }
+
+            if !melee.is_allowed_to_attack(time.absolute_time_seconds(), details.cooldown.0 as f64) {
+                continue;
+            }
+            melee.last_attack_time = time.absolute_time_seconds();
+            melee.input_lifted = false;
+
+            let direction = facing_direction(attacker_transform);
+
+            for (target, target_transform, target_bound, _) in (&entities, &transforms, &bounds, &healths).join() {
+                if target == attacker {
+                    continue;
+                }
+
+                if let Some(disposition) = check_arc_collision(
+                    attacker_transform,
+                    &direction,
+                    details.reach,
+                    half_angle,
+                    Collider { transform: target_transform, bound: target_bound })
+                {
+                    log::debug!("{:?} hit {:?} in melee", attacker, target);
+                    damage_event_channel.single_write(DamageEvent {
+                        damage: Damage(details.damage),
+                        target,
+                        attacker: Some(attacker),
+                        weapon: Some(MELEE_WEAPON_NAME.to_string()),
+                    });
+
+                    // A target standing in the attacker is pushed forward
+                    let push_direction = if disposition.x.0 == 0.0 && disposition.y.0 == 0.0 {
+                        direction
+                    } else {
+                        normalize(disposition)
+                    };
+                    let knockback = Knockback::new(details.knockback_speed * push_direction, details.knockback_duration, time.absolute_time());
+                    if let Err(err) = knockbacks.insert(target, knockback) {
+                        log::error!("Component 'Knockback' could not be inserted to entity. error: {:?}", err);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// The unit vector the entity is looking at
+fn facing_direction(transform: &Transform) -> Vector2<f32> {
+    let direction3d = (transform.rotation() * Vector3::y()).normalize();
+    Vector2::new(-direction3d.x, -direction3d.y)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::Error;
+    use amethyst::prelude::{World, WorldExt, Builder};
+    use amethyst::shrev::ReaderId;
+    use amethyst_test::prelude::*;
+    use westiny_common::metric_dimension::{MeterPerSec, Second};
+    use westiny_common::metric_dimension::length::Meter;
+
+    fn melee_details() -> MeleeDetails {
+        MeleeDetails {
+            damage: 20,
+            reach: Meter(1.0),
+            arc: 90.0,
+            cooldown: Second(0.0),
+            knockback_speed: MeterPerSec(8.0),
+            knockback_duration: Second(0.2),
+        }
+    }
+
+    fn place(x: f32, y: f32) -> Transform {
+        let mut transform = Transform::default();
+        transform.set_translation_xyz(Meter(x).into_pixel(), Meter(y).into_pixel(), 0.0);
+        transform
+    }
+
+    #[test]
+    fn melee_hits_only_targets_in_front() -> Result<(), Error> {
+        amethyst::start_logger(Default::default());
+        AmethystApplication::blank()
+            .with_setup(|world: &mut World| {
+                world.register::<Input>();
+                world.register::<Transform>();
+                world.register::<BoundingCircle>();
+                world.register::<Health>();
+                world.register::<Melee>();
+                world.register::<Knockback>();
+            })
+            .with_resource(EventChannel::<DamageEvent>::new())
+            .with_setup(|world: &mut World| {
+                let reader = world.fetch_mut::<EventChannel<DamageEvent>>().register_reader();
+                world.insert(reader);
+            })
+            .with_resource(melee_details())
+            .with_effect(|world: &mut World| {
+                let mut input = Input::default();
+                input.flags = InputFlags::MELEE;
+
+                // Default rotation faces toward -y
+                world.create_entity()
+                    .with(input)
+                    .with(place(0.0, 0.0))
+                    .with(BoundingCircle { radius: Meter(0.5) })
+                    .with(Health(100))
+                    .with(Melee::default())
+                    .build();
+
+                let in_front = world.create_entity()
+                    .with(place(0.0, -0.8))
+                    .with(BoundingCircle { radius: Meter(0.5) })
+                    .with(Health(100))
+                    .build();
+
+                world.create_entity()
+                    .with(place(0.0, 0.8))
+                    .with(BoundingCircle { radius: Meter(0.5) })
+                    .with(Health(100))
+                    .build();
+
+                world.insert(EffectReturn(in_front));
+            })
+            .with_system(MeleeSystem, "melee", &[])
+            .with_assertion(|world: &mut World| {
+                let in_front = world.read_resource::<EffectReturn<amethyst::ecs::Entity>>().0;
+
+                let channel = world.fetch::<EventChannel<DamageEvent>>();
+                let mut reader = world.fetch_mut::<ReaderId<DamageEvent>>();
+                let targets: Vec<_> = channel.read(&mut reader).map(|event| event.target).collect();
+                assert_eq!(targets, vec![in_front]);
+
+                let knockbacks = world.read_storage::<Knockback>();
+                let knockback = knockbacks.get(in_front).expect("Target is not knocked back");
+                assert!(knockback.velocity.y.0 < 0.0, "Target should be pushed away from the attacker");
+                assert_eq!((&knockbacks).join().count(), 1);
+            })
+            .run()
+    }
+}
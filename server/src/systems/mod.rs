@@ -1,22 +1,58 @@
+pub use admin_command::AdminCommandSystemDesc;
+pub use admin_console::AdminConsoleSystem;
+pub use anti_cheat::AntiCheatSystemDesc;
+pub use chat::ChatSystemDesc;
 pub use client_introduction::ClientIntroductionSystemDesc;
 pub use command_transformer::CommandTransformerSystemDesc;
 pub use entity_delete_broadcaster::EntityDeleteBroadcasterSystemDesc;
+pub use discovery::DiscoverySystem;
 pub use entity_state_broadcaster::EntityStateBroadcasterSystem;
 pub use health::HealthSystemDesc;
+pub use inventory::PickupSystem;
+pub use item_spawn::ItemSpawnSystem;
+pub use melee::MeleeSystem;
 pub use network_messenger::NetworkMessageReceiverSystemDesc;
 pub use player_movement::PlayerMovementSystem;
+pub use replay_recorder::ReplayRecorderSystemDesc;
 pub use shooter::ShooterSystem;
 pub use spawn::{SpawnPlayerEvent, SpawnSystemDesc, RespawnSystem};
-pub use death::DeathSystem;
+pub use death::DeathSystemDesc;
+pub use destruction::{DestructionSystem, ObjectRespawnSystem};
+pub use explosion::ExplosionSystemDesc;
+pub use grenade::GrenadeSystem;
+pub use flag::FlagSystem;
+pub use score::ScoreSystemDesc;
+pub use match_control::MatchSystem;
+pub use safe_zone::SafeZoneSystem;
+pub use status_effect::StatusEffectSystemDesc;
+pub use stamina::StaminaSystem;
 pub use westiny_common::systems::*;
 
 mod network_messenger;
+mod admin_command;
+mod admin_console;
+mod anti_cheat;
+mod chat;
 mod client_introduction;
 mod command_transformer;
+mod discovery;
 mod entity_delete_broadcaster;
 mod entity_state_broadcaster;
 mod shooter;
 mod player_movement;
+mod replay_recorder;
 mod health;
 mod spawn;
 mod death;
+mod inventory;
+mod item_spawn;
+mod melee;
+mod destruction;
+mod explosion;
+mod grenade;
+mod flag;
+mod score;
+mod match_control;
+mod safe_zone;
+mod status_effect;
+mod stamina;
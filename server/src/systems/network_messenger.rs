@@ -1,20 +1,38 @@
 use amethyst::{
+    core::Time,
     derive::SystemDesc,
     ecs::{System, SystemData, Read, Write, WriteExpect},
-    network::simulation::NetworkSimulationEvent,
+    network::simulation::{NetworkSimulationEvent, TransportResource, DeliveryRequirement, UrgencyRequirement},
     shrev::{ReaderId, EventChannel},
 };
 
 use anyhow::Result;
 use std::net::SocketAddr;
 use derive_new::new;
+use thiserror::Error;
 
 use westiny_common::{
-    network::{PacketType},
+    network::{self, ErrorKind, PacketType},
+    serialization::DecodeError,
     deserialize,
+    serialize,
 };
 
-use crate::resources::{ClientRegistry, ClientNetworkEvent, NetworkCommand};
+use crate::resources::{AccessPolicy, AddError, BanList, ClientRegistry, ClientNetworkEvent, NetworkCommand, PacketGuard, Verdict};
+
+/// Only this much of an invalid payload is logged
+const LOGGED_PAYLOAD_LENGTH: usize = 32;
+
+/// Packets counted as violations by the packet guard
+#[derive(Error, Debug)]
+enum Offense {
+    #[error("{0}")]
+    Malformed(DecodeError),
+    #[error("Packet type is not sent by clients")]
+    Unexpected,
+    #[error("Invalid input: {0:?}")]
+    InvalidInput(westiny_common::components::Input),
+}
 
 
 #[derive(SystemDesc, new)]
@@ -30,9 +48,17 @@ impl<'s> System<'s> for NetworkMessageReceiverSystem {
         Read<'s, EventChannel<NetworkSimulationEvent>>,
         Write<'s, EventChannel<ClientNetworkEvent>>,
         Write<'s, EventChannel<NetworkCommand>>,
+        Write<'s, TransportResource>,
+        Read<'s, BanList>,
+        Read<'s, AccessPolicy>,
+        Write<'s, PacketGuard>,
+        Read<'s, Time>,
     );
 
-    fn run(&mut self, (mut client_registry, net_event_ch, mut client_net_ec, mut command_channel): Self::SystemData) {
+    fn run(&mut self, (mut client_registry, net_event_ch, mut client_net_ec, mut command_channel, mut net, ban_list, access_policy, mut packet_guard, time): Self::SystemData) {
+        let now = time.absolute_time();
+        packet_guard.forget_idle(now);
+
         for event in net_event_ch.read(&mut self.reader) {
             match event {
                 NetworkSimulationEvent::Connect(addr) => log::info!(
@@ -45,11 +71,28 @@ impl<'s> System<'s> for NetworkMessageReceiverSystem {
                     }
                 }
                 NetworkSimulationEvent::Message(addr, payload) => {
-                    match self.process_payload(addr, payload, &mut client_registry, &mut client_net_ec, &mut command_channel) {
-                        Ok(_) => log::debug!("Message from {} processed successfully.", addr),
-                        Err(e) => {
-                            log::error!("Could not process message! {}, payload: {:?}", e, payload)
+                    let verdict = match packet_guard.admit(addr, payload.len(), now) {
+                        Verdict::Accept => match inspect(payload) {
+                            Ok(packet) => {
+                                match self.process_packet(addr, packet, &mut client_registry, &mut client_net_ec, &mut command_channel, &mut net, (&ban_list, &access_policy)) {
+                                    Ok(_) => log::debug!("Message from {} processed successfully.", addr),
+                                    Err(e) => log::error!("Could not process message from {}! {}", addr, e),
+                                }
+                                Verdict::Accept
+                            }
+                            Err(offense) => {
+                                log::warn!("Invalid packet from {}: {}, payload: {}", addr, offense, payload_preview(payload));
+                                packet_guard.report(addr, now)
+                            }
+                        },
+                        verdict => {
+                            log::debug!("Packet of {} bytes from {} is dropped", payload.len(), addr);
+                            verdict
                         }
+                    };
+
+                    if verdict == Verdict::Disconnect {
+                        self.disconnect_offender(addr, &mut client_registry, &mut client_net_ec, &mut net);
                     }
                 }
                 _ => log::error!("Network error: {:?}", event),
@@ -73,24 +116,60 @@ impl NetworkMessageReceiverSystem {
         Ok(())
     }
 
-    fn process_payload(
+    /// Repeat offenders are kicked as if they disconnected, their packets are ignored for a while
+    fn disconnect_offender(
+        &self,
+        addr: &SocketAddr,
+        registry: &mut ClientRegistry,
+        client_event_channel: &mut EventChannel<ClientNetworkEvent>,
+        net: &mut TransportResource,
+    ) {
+        log::warn!("Too many invalid packets from {}, ignoring it for a while", addr);
+        if registry.find_by_addr(addr).is_none() {
+            return;
+        }
+        match serialize(&PacketType::Kicked { reason: "Too many invalid packets".to_string() }) {
+            Ok(msg) => net.send_with_requirements(*addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::Immediate),
+            Err(err) => log::error!("Kicked message could not be serialized: {}", err),
+        }
+        if let Err(e) = self.disconnect_client(addr, registry, client_event_channel) {
+            log::error!("Error during disconnect_client: {}", e);
+        }
+    }
+
+    fn process_packet(
         &self,
         addr: &SocketAddr,
-        payload: &[u8],
+        packet: PacketType,
         registry: &mut ClientRegistry,
         client_net_event_channel: &mut EventChannel<ClientNetworkEvent>,
         command_channel: &mut EventChannel<NetworkCommand>,
+        net: &mut TransportResource,
+        (ban_list, access_policy): (&BanList, &AccessPolicy),
     ) -> Result<()> {
-
-        log::debug!("Message: {:02x?}", payload);
-        match deserialize(payload)? {
-            PacketType::ConnectionRequest { player_name } => {
+        match packet {
+            PacketType::ConnectionRequest { player_name, observer, password } => {
                 log::debug!("Connection request received: {}, {}", addr, player_name);
-                // TODO response errors from registry
-                let client_id = registry.add(addr, player_name.as_str())?;
+                if let Err(error_kind) = access_policy.admit(ban_list, &addr.ip(), &player_name, password.as_deref()) {
+                    refuse_connection(net, addr, error_kind)?;
+                    return Err(anyhow::anyhow!("Client {} from {} is not let in: {}", player_name, addr, error_kind));
+                }
+                let registration = if observer {
+                    registry.add_observer(addr, player_name.as_str())
+                } else {
+                    registry.add(addr, player_name.as_str())
+                };
+                let client_id = match registration {
+                    Ok(client_id) => client_id,
+                    Err(err) => {
+                        refuse_connection(net, addr, error_kind_of(&err))?;
+                        return Err(err.into());
+                    }
+                };
                 log::info!(
-                    "Client from {} as player {} connection request accepted. ClientID={:?}",
+                    "Client from {} as {} {} connection request accepted. ClientID={:?}",
                     addr,
+                    if observer { "observer" } else { "player" },
                     player_name,
                     client_id
                 );
@@ -104,15 +183,52 @@ impl NetworkMessageReceiverSystem {
                     .map(|handle| command_channel.single_write(NetworkCommand::Input { id: handle.id, input }))
                     .ok_or(anyhow::anyhow!("Valid input command from unregistered client! Address: {:?}", addr))
             },
-            _ => Err(anyhow::anyhow!(
-                "Unexpected message from {}, payload={:02x?}",
-                addr,
-                payload
-            )),
+            PacketType::ChatRequest { text, team_only } => {
+                registry
+                    .find_by_addr(addr)
+                    .map(|handle| command_channel.single_write(NetworkCommand::Chat { id: handle.id, text, team_only }))
+                    .ok_or(anyhow::anyhow!("Chat message from unregistered client! Address: {:?}", addr))
+            },
+            _ => Err(anyhow::anyhow!("Unexpected message from {}", addr)),
         }
     }
 }
 
+/// Deserializes the payload and checks it before anything is done by it
+fn inspect(payload: &[u8]) -> std::result::Result<PacketType, Offense> {
+    log::debug!("Message: {:02x?}", payload);
+    match deserialize(payload).map_err(Offense::Malformed)? {
+        PacketType::InputState { input } if !input.is_valid() => Err(Offense::InvalidInput(input)),
+        packet @ PacketType::ConnectionRequest { .. }
+        | packet @ PacketType::InputState { .. }
+        | packet @ PacketType::ChatRequest { .. } => Ok(packet),
+        _ => Err(Offense::Unexpected),
+    }
+}
+
+fn payload_preview(payload: &[u8]) -> String {
+    if payload.len() > LOGGED_PAYLOAD_LENGTH {
+        format!("{:02x?}... ({} bytes)", &payload[..LOGGED_PAYLOAD_LENGTH], payload.len())
+    } else {
+        format!("{:02x?}", payload)
+    }
+}
+
+fn error_kind_of(err: &AddError) -> ErrorKind {
+    match err {
+        AddError::Unauthorized => ErrorKind::AlreadyConnected,
+        AddError::ServerIsFull => ErrorKind::ServerIsFull,
+        AddError::InvalidName(error_kind) => *error_kind,
+    }
+}
+
+/// Tells the client why it could not join
+fn refuse_connection(net: &mut TransportResource, addr: &SocketAddr, error_kind: ErrorKind) -> Result<()> {
+    let response = PacketType::ConnectionResponse(Err(network::Error::new(error_kind)));
+    net.send_with_requirements(*addr, &serialize(&response)?, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick);
+    Ok(())
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -177,6 +293,70 @@ mod test {
             .run()
     }
 
+    #[test]
+    fn connection_request_with_invalid_name_is_refused() -> Result<(), Error> {
+        amethyst::start_logger(Default::default());
+        AmethystApplication::blank()
+            .with_resource(EventChannel::<ClientNetworkEvent>::new())
+            .with_resource(EventChannel::<NetworkCommand>::new())
+            .with_setup(move |world: &mut World| {
+                let client_net_channel = world.fetch_mut::<EventChannel<ClientNetworkEvent>>().register_reader();
+                world.insert(client_net_channel);
+            })
+            .with_resource(ClientRegistry::new(1))
+            .with_effect(|world| {
+                let mut network_event_channel = world.fetch_mut::<EventChannel<NetworkSimulationEvent>>();
+                let req = network::PacketType::ConnectionRequest { player_name: "Clint\tWestwood".to_string(), observer: false, password: None };
+                network_event_channel.single_write(
+                    NetworkSimulationEvent::Message(
+                        socket_addr(),
+                        serialize(&req).unwrap().into()
+                    )
+                );
+            })
+            .with_system_desc(NetworkMessageReceiverSystemDesc::default(), "receiver", &[])
+            .with_assertion(|world: &mut World| {
+                let client_net_ec = world.fetch_mut::<EventChannel<ClientNetworkEvent>>();
+                let mut reader_id = world.write_resource::<ReaderId<ClientNetworkEvent>>();
+
+                assert_eq!(0, client_net_ec.read(&mut reader_id).count(), "Client with invalid name should not connect");
+                assert_eq!(0, world.read_resource::<ClientRegistry>().client_count());
+            })
+            .run()
+    }
+
+    #[test]
+    fn connection_request_without_password_is_refused_on_private_server() -> Result<(), Error> {
+        amethyst::start_logger(Default::default());
+        AmethystApplication::blank()
+            .with_resource(EventChannel::<ClientNetworkEvent>::new())
+            .with_resource(EventChannel::<NetworkCommand>::new())
+            .with_setup(move |world: &mut World| {
+                let client_net_channel = world.fetch_mut::<EventChannel<ClientNetworkEvent>>().register_reader();
+                world.insert(client_net_channel);
+            })
+            .with_resource(ClientRegistry::new(1))
+            .with_resource(AccessPolicy::new(Some("secret".to_string()), None))
+            .with_effect(|world| {
+                let mut network_event_channel = world.fetch_mut::<EventChannel<NetworkSimulationEvent>>();
+                network_event_channel.single_write(
+                    NetworkSimulationEvent::Message(
+                        socket_addr(),
+                        serialize(&connection_request()).unwrap().into()
+                    )
+                );
+            })
+            .with_system_desc(NetworkMessageReceiverSystemDesc::default(), "receiver", &[])
+            .with_assertion(|world: &mut World| {
+                let client_net_ec = world.fetch_mut::<EventChannel<ClientNetworkEvent>>();
+                let mut reader_id = world.write_resource::<ReaderId<ClientNetworkEvent>>();
+
+                assert_eq!(0, client_net_ec.read(&mut reader_id).count(), "Client without password should not connect");
+                assert_eq!(0, world.read_resource::<ClientRegistry>().client_count());
+            })
+            .run()
+    }
+
     fn make_input() -> Input {
         let mut inp = Input::default();
         inp.flags |= InputFlags::FORWARD;
@@ -232,6 +412,44 @@ mod test {
             .run()
     }
 
+    #[test]
+    fn invalid_packets_are_recognized() {
+        assert!(matches!(inspect(&[0xc1, 0x00, 0xff]), Err(Offense::Malformed(_))));
+        assert!(matches!(inspect(&serialize(&PacketType::EntityStateUpdate(vec![])).unwrap()), Err(Offense::Unexpected)));
+
+        let mut input = make_input();
+        input.cursor.x = Meter(f32::NAN);
+        assert!(matches!(inspect(&serialize(&PacketType::InputState { input }).unwrap()), Err(Offense::InvalidInput(_))));
+        input.cursor.x = Meter(f32::INFINITY);
+        assert!(matches!(inspect(&serialize(&PacketType::InputState { input }).unwrap()), Err(Offense::InvalidInput(_))));
+
+        assert!(inspect(&serialize(&connection_request()).unwrap()).is_ok());
+        assert!(inspect(&serialize(&PacketType::InputState { input: make_input() }).unwrap()).is_ok());
+    }
+
+    #[test]
+    fn client_sending_garbage_is_disconnected() -> Result<(), Error> {
+        create_testapp()
+            .with_effect(|world| {
+                let mut network_event_channel = world.fetch_mut::<EventChannel<NetworkSimulationEvent>>();
+                for _ in 0..10 {
+                    network_event_channel.single_write(
+                        NetworkSimulationEvent::Message(socket_addr(), vec![0xc1, 0xc1, 0xc1].into())
+                    );
+                }
+            })
+            .with_assertion(|world| {
+                let client_net_ec = world.fetch_mut::<EventChannel<ClientNetworkEvent>>();
+                let mut reader_id = world.write_resource::<ReaderId<ClientNetworkEvent>>();
+
+                let events: Vec<&ClientNetworkEvent> = client_net_ec.read(&mut reader_id).collect();
+                assert_eq!(1, events.len(), "There should be exactly 1 ClientNetworkEvent on channel");
+                assert!(matches!(events[0], ClientNetworkEvent::ClientDisconnected(_, _)));
+                assert_eq!(0, world.read_resource::<ClientRegistry>().client_count());
+            })
+            .run()
+    }
+
     #[inline]
     fn socket_addr() -> SocketAddr {
         SocketAddr::from(([127, 0, 0, 1], 9999))
@@ -239,6 +457,6 @@ mod test {
 
     #[inline]
     fn connection_request() -> network::PacketType {
-        network::PacketType::ConnectionRequest { player_name: "Clint Westwood".to_string() }
+        network::PacketType::ConnectionRequest { player_name: "Clint Westwood".to_string(), observer: false, password: None }
     }
 }
@@ -1,13 +1,14 @@
 use amethyst::derive::SystemDesc;
-use amethyst::ecs::{System, SystemData, ReadStorage, WriteStorage, Join};
-use amethyst::core::Transform;
+use amethyst::ecs::{System, SystemData, ReadStorage, WriteStorage, Read, ReadExpect, Entities, Join};
+use amethyst::core::{Transform, Time};
 use amethyst::core::math::{Vector2, Rotation2, Point2};
 
 use westiny_common::MoveDirection;
-use westiny_common::components::{Player, Velocity};
+use westiny_common::components::{Player, Velocity, Knockback, Stamina, StatusEffects};
 use westiny_common::components::{InputFlags, Input};
 use westiny_common::metric_dimension::{MeterPerSec, rotate};
 use amethyst::core::num::Zero;
+use crate::resources::GameRules;
 
 #[derive(SystemDesc)]
 pub struct PlayerMovementSystem;
@@ -18,20 +19,43 @@ impl<'s> System<'s> for PlayerMovementSystem {
         WriteStorage<'s, Velocity>,
         ReadStorage<'s, Player>,
         ReadStorage<'s, Input>,
+        WriteStorage<'s, Knockback>,
+        ReadStorage<'s, StatusEffects>,
+        ReadStorage<'s, Stamina>,
+        Read<'s, Time>,
+        Entities<'s>,
+        ReadExpect<'s, GameRules>,
     );
 
-    fn run(&mut self, (mut transforms, mut velocities, players, inputs): Self::SystemData) {
-        for (_player, input, mut velocity, transform) in (&players, &inputs, &mut velocities, &mut transforms).join() {
+    fn run(&mut self, (mut transforms, mut velocities, players, inputs, mut knockbacks, status_effects, staminas, time, entities, rules): Self::SystemData) {
+        for (entity, _player, input, mut velocity, transform) in (&entities, &players, &inputs, &mut velocities, &mut transforms).join() {
             rotate_toward_point(transform, &Point2::new(input.cursor.x.into_pixel(), input.cursor.y.into_pixel()));
 
             let move_inputs = move_directions_from_input(&input);
             log::debug!("{:?} {}", input, move_inputs.len());
 
-            update_velocity(&transform, &move_inputs, &mut velocity);
+            let sprinting = staminas.get(entity).map_or(false, |stamina| stamina.is_sprinting(input));
+            update_velocity(&transform, &move_inputs, sprinting, rules.walk_speed, &mut velocity);
+            if let Some(effects) = status_effects.get(entity) {
+                let multiplier = effects.speed_multiplier();
+                velocity.0 = velocity.0.map(|speed| MeterPerSec(speed.0 * multiplier));
+            }
+
+            if let Some(knockback) = knockbacks.get(entity) {
+                if time.absolute_time() < knockback.until {
+                    apply_knockback(&mut velocity, knockback);
+                } else {
+                    knockbacks.remove(entity);
+                }
+            }
         }
     }
 }
 
+fn apply_knockback(velocity: &mut Velocity, knockback: &Knockback) {
+    velocity.0 = Vector2::new(velocity.0.x + knockback.velocity.x, velocity.0.y + knockback.velocity.y);
+}
+
 pub fn move_directions_from_input(input: &Input) -> Vec<MoveDirection>
 {
     let mut directions = Vec::new();
@@ -66,20 +90,23 @@ pub fn rotate_toward_point(
     set_rotation_toward_vector(transform, &direction);
 }
 
-const PLAYER_MAX_WALK_SPEED: MeterPerSec = MeterPerSec(4.0);
+/// Forward speed is multiplied by this while sprinting
+const SPRINT_MULTIPLIER: f32 = 1.75;
 
 // TODO It would be better to use a more generic IntoIterator instead of the specific vector type.
 // I did not manage to call into_iter on <T: IntoIterator<Item=MoveDirection>> type
 fn update_velocity(
     transform: &Transform,
     move_inputs: &Vec<MoveDirection>,
+    sprinting: bool,
+    walk_speed: MeterPerSec,
     velocity: &mut Velocity
 ) {
     *velocity = if move_inputs.is_empty() {
         Velocity::default()
     } else {
         let velocities: Vec<Vector2<MeterPerSec>> = move_inputs.into_iter()
-            .map(|dir| as_vector2(*dir))
+            .map(|dir| as_vector2(*dir, sprinting, walk_speed))
             .collect();
 
         let angle = transform.rotation().axis().map(|vec| vec.z).unwrap_or(1.0) * transform.rotation().angle();
@@ -106,14 +133,19 @@ fn vector_avg<'a, I>(velocities: I) -> Vector2<MeterPerSec>
 
 // TODO I couldn't manage to create valid rustdoc links :(
 /// Gives the corresponding `Vector2` to the given `MoveDirection` element.
-/// In te case of `Forward` the length of the returned vector will be the max walk speed
-/// and the half of that in any other cases
-fn as_vector2(move_dir: MoveDirection) -> Vector2<MeterPerSec> {
+/// In te case of `Forward` the length of the returned vector will be the walk speed
+/// and the half of that in any other cases. Sprinting makes only the forward movement faster.
+fn as_vector2(move_dir: MoveDirection, sprinting: bool, walk_speed: MeterPerSec) -> Vector2<MeterPerSec> {
+    let forward_speed = if sprinting {
+        MeterPerSec(walk_speed.0 * SPRINT_MULTIPLIER)
+    } else {
+        walk_speed
+    };
     match move_dir {
-        MoveDirection::Forward => Vector2::new(MeterPerSec::zero(), -PLAYER_MAX_WALK_SPEED),
-        MoveDirection::Backward => Vector2::new(MeterPerSec::zero(), PLAYER_MAX_WALK_SPEED / 2.0),
-        MoveDirection::StrafeLeft => Vector2::new(PLAYER_MAX_WALK_SPEED / 2.0, MeterPerSec::zero()),
-        MoveDirection::StrafeRight => Vector2::new(-PLAYER_MAX_WALK_SPEED / 2.0, MeterPerSec::zero())
+        MoveDirection::Forward => Vector2::new(MeterPerSec::zero(), -forward_speed),
+        MoveDirection::Backward => Vector2::new(MeterPerSec::zero(), walk_speed / 2.0),
+        MoveDirection::StrafeLeft => Vector2::new(walk_speed / 2.0, MeterPerSec::zero()),
+        MoveDirection::StrafeRight => Vector2::new(-walk_speed / 2.0, MeterPerSec::zero())
     }
 }
 
@@ -128,6 +160,7 @@ mod test {
     const FACING_DOWN: f32 = 0.0;
     const FACING_LEFT: f32 = -PI/2.0;
     const FACING_RIGHT: f32 = PI/2.0;
+    const WALK_SPEED: MeterPerSec = MeterPerSec(4.0);
 
     mod test_rotate_toward_mouse {
         use super::*;
@@ -164,6 +197,19 @@ mod test {
         }
     }
 
+    #[test]
+    fn knockback_is_added_to_movement() {
+        use westiny_common::metric_dimension::Second;
+        use std::time::Duration;
+
+        let mut velocity = Velocity(Vector2::new(MeterPerSec(1.0), MeterPerSec(0.0)));
+        let knockback = Knockback::new(Vector2::new(MeterPerSec(0.0), MeterPerSec(-8.0)), Second(1.0), Duration::from_secs(0));
+        apply_knockback(&mut velocity, &knockback);
+
+        assert!(f32_eq(velocity.0.x.0, 1.0));
+        assert!(f32_eq(velocity.0.y.0, -8.0));
+    }
+
     mod test_update_velocity {
         use super::*;
         use westiny_test::f32_eq;
@@ -179,7 +225,7 @@ mod test {
                     let (exp_x, exp_y) = $expected;
 
                     let mut velocity = Velocity::default();
-                    update_velocity(&transform, &inputs, &mut velocity);
+                    update_velocity(&transform, &inputs, false, WALK_SPEED, &mut velocity);
 
                     assert!(f32_eq(exp_x.0, velocity.0.x.0), "velocity x -> Expected: {}, Actual: {}", exp_x, velocity.0.x);
                     assert!(f32_eq(exp_y.0, velocity.0.y.0), "velocity y -> Expected: {}, Actual: {}", exp_y, velocity.0.y);
@@ -191,11 +237,19 @@ mod test {
 
         test_update_velocity! {
             // forward
-            fwd_up: FACING_UP, vec!{Forward}, (MeterPerSec(0.0), PLAYER_MAX_WALK_SPEED),
-            fwd_down: FACING_DOWN, vec!{Forward}, (MeterPerSec(0.0), -PLAYER_MAX_WALK_SPEED),
-            fwd_left: FACING_LEFT, vec!{Forward}, (-PLAYER_MAX_WALK_SPEED, MeterPerSec(0.0)),
-            fwd_right: FACING_RIGHT, vec!{Forward}, (PLAYER_MAX_WALK_SPEED, MeterPerSec(0.0)),
+            fwd_up: FACING_UP, vec!{Forward}, (MeterPerSec(0.0), WALK_SPEED),
+            fwd_down: FACING_DOWN, vec!{Forward}, (MeterPerSec(0.0), -WALK_SPEED),
+            fwd_left: FACING_LEFT, vec!{Forward}, (-WALK_SPEED, MeterPerSec(0.0)),
+            fwd_right: FACING_RIGHT, vec!{Forward}, (WALK_SPEED, MeterPerSec(0.0)),
         }
     }
 
+    #[test]
+    fn sprinting_speeds_up_only_the_forward_movement() {
+        let forward = as_vector2(MoveDirection::Forward, true, WALK_SPEED);
+        assert!(f32_eq(forward.y.0, -WALK_SPEED.0 * SPRINT_MULTIPLIER));
+
+        let strafe = as_vector2(MoveDirection::StrafeLeft, true, WALK_SPEED);
+        assert!(f32_eq(strafe.x.0, WALK_SPEED.0 / 2.0));
+    }
 }
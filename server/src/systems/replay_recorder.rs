@@ -0,0 +1,221 @@
+use amethyst::core::{SystemDesc, Time, Transform};
+use amethyst::core::ecs::{System, SystemData, World, Read, ReadStorage, ReadExpect, Join};
+use amethyst::shrev::{EventChannel, ReaderId};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use westiny_common::network::{MapChange, PacketType};
+use westiny_common::replay::{is_recorded, ReplayHeader, ReplayWriter, REPLAY_EXTENSION};
+use westiny_common::resources::Seed;
+
+use crate::components::NetworkId;
+use crate::resources::{ClientRegistry, ReplayEvent, ServerConfig};
+use super::entity_state_broadcaster::entity_state;
+
+/// The recorded frames are written to the disk at least this often
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Creates the replay file, the recording starts when the system is built
+pub struct ReplayRecorderSystemDesc {
+    writer: ReplayWriter<BufWriter<File>>,
+    path: PathBuf,
+}
+
+impl ReplayRecorderSystemDesc {
+    /// The replay file is named after the first map of the rotation and the time the server was started
+    pub fn create(replay_dir: &Path, config: &ServerConfig) -> anyhow::Result<Self> {
+        std::fs::create_dir_all(replay_dir)?;
+        let started = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let first_map = &config.map_rotation[0];
+        let path = replay_dir.join(format!("{}_{}.{}", first_map, started, REPLAY_EXTENSION));
+        // Nothing is destroyed before the server starts
+        let header = ReplayHeader::new(first_map.clone(), Seed(config.seed), vec![]);
+        let writer = ReplayWriter::create(&path, &header)?;
+        log::info!("Recording the match into {}", path.display());
+
+        Ok(ReplayRecorderSystemDesc { writer, path })
+    }
+}
+
+impl<'a, 'b> SystemDesc<'a, 'b, ReplayRecorderSystem> for ReplayRecorderSystemDesc {
+    fn build(self, world: &mut World) -> ReplayRecorderSystem {
+        <ReplayRecorderSystem as System<'_>>::SystemData::setup(world);
+
+        let reader = world
+            .fetch_mut::<EventChannel<ReplayEvent>>()
+            .register_reader();
+
+        ReplayRecorderSystem {
+            reader,
+            writer: Some(self.writer),
+            path: self.path,
+            recorded_objects: HashSet::new(),
+            map_change: None,
+            recorded_time: Duration::default(),
+            last_flush: Duration::default(),
+        }
+    }
+}
+
+/// Records the match into a replay file: the state of every entity and the packets broadcast
+/// in each tick, which the broadcasting systems emit as `ReplayEvent`s.
+/// Ticks without clients are left out, so the replay skips the empty server.
+/// Must run after every system broadcasting packets.
+pub struct ReplayRecorderSystem {
+    reader: ReaderId<ReplayEvent>,
+    writer: Option<ReplayWriter<BufWriter<File>>>,
+    path: PathBuf,
+    /// Static objects are recorded once when they appear, their destruction is an EntityDelete
+    recorded_objects: HashSet<NetworkId>,
+    /// A map loaded while the server was empty is written with the next recorded tick
+    map_change: Option<MapChange>,
+    recorded_time: Duration,
+    last_flush: Duration,
+}
+
+impl<'s> System<'s> for ReplayRecorderSystem {
+    type SystemData = (
+        Read<'s, EventChannel<ReplayEvent>>,
+        ReadExpect<'s, ClientRegistry>,
+        Read<'s, Time>,
+        ReadStorage<'s, NetworkId>,
+        ReadStorage<'s, Transform>,
+    );
+
+    fn run(&mut self, (replay_channel, client_registry, time, network_ids, transforms): Self::SystemData) {
+        // Read even when nothing is recorded, so the events of the empty server are not recorded later
+        let events: Vec<&ReplayEvent> = replay_channel.read(&mut self.reader).collect();
+        for ReplayEvent(packet) in &events {
+            if let PacketType::MapChange(change) = packet {
+                self.map_change = Some(change.clone());
+                // Every object of the new map is recorded again
+                self.recorded_objects.clear();
+            }
+        }
+        let writer = match self.writer.as_mut() {
+            Some(writer) => writer,
+            None => return,
+        };
+        if client_registry.client_count() == 0 {
+            return;
+        }
+        self.recorded_time += time.delta_time();
+
+        // Players only receive what they see, so the recorded state is collected here.
+        // Static objects standing since the last tick are left out, seeking finds them in earlier frames.
+        let standing_objects: HashSet<NetworkId> = network_ids.join()
+            .filter(|network_id| network_id.entity_type.is_static())
+            .copied()
+            .collect();
+        let recorded_objects = &self.recorded_objects;
+        let entity_states = (&network_ids, &transforms).join()
+            .filter(|(network_id, _)| !network_id.entity_type.is_static() || !recorded_objects.contains(network_id))
+            .map(|(network_id, transform)| entity_state(network_id, transform))
+            .collect();
+        self.recorded_objects = standing_objects;
+
+        // The map change comes first, so the rest of the frame is played on the new map
+        let map_change = self.map_change.take().map(PacketType::MapChange);
+        let entity_state_update = PacketType::EntityStateUpdate(entity_states);
+        let mut packets: Vec<&PacketType> = map_change.iter().collect();
+        packets.push(&entity_state_update);
+        packets.extend(events.into_iter()
+            .map(|ReplayEvent(packet)| packet)
+            .filter(|packet| is_recorded(packet) && !matches!(packet, PacketType::MapChange(_))));
+
+        let mut result = writer.write_packets(self.recorded_time, &packets);
+        if result.is_ok() && self.recorded_time >= self.last_flush + FLUSH_INTERVAL {
+            self.last_flush = self.recorded_time;
+            result = writer.flush();
+        }
+
+        if let Err(err) = result {
+            log::error!("Replay recording stopped, {} could not be written: {}", self.path.display(), err);
+            self.writer = None;
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::Error;
+    use amethyst::prelude::{WorldExt, Builder};
+    use amethyst_test::prelude::*;
+    use std::net::SocketAddr;
+    use westiny_common::components::EntityType;
+    use westiny_common::network::{ChatMessage, PlayerNotification};
+    use westiny_common::replay::Replay;
+    use westiny_common::PlayerName;
+
+    /// Tests running in parallel must not find each other's replays
+    fn unique_temp_dir() -> PathBuf {
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+        std::env::temp_dir().join(format!("westiny_test_replays_{}_{}", std::process::id(), nanos))
+    }
+
+    #[test]
+    fn records_every_entity_and_the_broadcast_events_once() -> Result<(), Error> {
+        amethyst::start_logger(Default::default());
+        let replay_dir = unique_temp_dir();
+
+        let players = [SocketAddr::from(([10, 0, 0, 1], 5000)), SocketAddr::from(([10, 0, 0, 2], 5000))];
+        let mut client_registry = ClientRegistry::new(2);
+        client_registry.add(&players[0], "Clint")?;
+        client_registry.add(&players[1], "Tuco")?;
+
+        let recorder = ReplayRecorderSystemDesc::create(&replay_dir, &ServerConfig::default()).unwrap();
+
+        AmethystApplication::blank()
+            .with_setup(|world: &mut World| {
+                world.register::<NetworkId>();
+                world.register::<Transform>();
+                world.create_entity()
+                    .with(NetworkId::new(EntityType::Player, 1))
+                    .with(Transform::default())
+                    .build();
+                world.create_entity()
+                    .with(NetworkId::new(EntityType::Barrel, 2))
+                    .with(Transform::default())
+                    .build();
+            })
+            .with_resource(client_registry)
+            .with_system_desc(recorder, "replay_recorder", &[])
+            .with_effect(|world: &mut World| {
+                let mut replay_channel = world.fetch_mut::<EventChannel<ReplayEvent>>();
+                replay_channel.single_write(ReplayEvent(PacketType::Notification(PlayerNotification { message: "Tuco joined".to_string() })));
+                replay_channel.single_write(ReplayEvent(PacketType::Chat(ChatMessage {
+                    sender: PlayerName("Tuco".to_string()),
+                    text: "flank left".to_string(),
+                    team_only: true,
+                })));
+            })
+            .run()?;
+
+        let file = std::fs::read_dir(&replay_dir)?.next().expect("Replay file is missing")?.path();
+        let replay = Replay::load(&file).unwrap();
+        std::fs::remove_dir_all(&replay_dir)?;
+        assert_eq!(replay.header.seed, Seed(ServerConfig::default().seed));
+
+        // Team chat is left out
+        let frame = &replay.frames[0];
+        assert_eq!(frame.packets.len(), 2, "{:?}", frame.packets);
+        match &frame.packets[0] {
+            PacketType::EntityStateUpdate(states) => {
+                // The barrel is recorded in the first frame only
+                let mut network_ids: Vec<_> = states.iter().map(|state| state.network_id).collect();
+                network_ids.sort_by_key(|network_id| network_id.id);
+                assert_eq!(network_ids, vec![NetworkId::new(EntityType::Player, 1), NetworkId::new(EntityType::Barrel, 2)]);
+            }
+            packet => panic!("Unexpected packet {:?}", packet),
+        }
+        assert!(matches!(&frame.packets[1], PacketType::Notification(notification) if notification.message == "Tuco joined"));
+
+        for frame in &replay.frames[1..] {
+            assert!(matches!(frame.packets.as_slice(), [PacketType::EntityStateUpdate(states)] if states.len() == 1), "{:?}", frame.packets);
+        }
+        Ok(())
+    }
+}
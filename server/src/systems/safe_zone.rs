@@ -0,0 +1,104 @@
+use amethyst::core::{Time, Transform};
+use amethyst::core::math::{Point2, Vector2};
+use amethyst::ecs::{System, ReadStorage, ReadExpect, Read, Write, WriteExpect, Entities, Join};
+use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
+use amethyst::shrev::EventChannel;
+use std::time::Duration;
+
+use crate::components::{Damage, Eliminated, Player};
+use crate::resources::{ClientRegistry, GameRules, MatchState, Phase, ReplayEvent, StreamId};
+use westiny_common::events::DamageEvent;
+use westiny_common::metric_dimension::{Second, to_meter_vec};
+use westiny_common::metric_dimension::length::{Meter, magnitude};
+use westiny_common::network::{PacketType, SafeZoneUpdate};
+use westiny_common::serialize;
+
+/// Players outside the zone are hurt and the clients are updated this often
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+const SAFE_ZONE_WEAPON_NAME: &str = "Safe zone";
+
+/// Shrinks the playing area of last man standing rounds and hurts the players caught outside of it
+#[derive(Default)]
+pub struct SafeZoneSystem {
+    next_tick_at: Duration,
+}
+
+impl<'s> System<'s> for SafeZoneSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Transform>,
+        ReadStorage<'s, Eliminated>,
+        Read<'s, MatchState>,
+        ReadExpect<'s, GameRules>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Write<'s, EventChannel<DamageEvent>>,
+        Write<'s, EventChannel<ReplayEvent>>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, players, transforms, eliminates, match_state, rules, client_registry, mut net, mut damage_channel, mut replay_channel, time): Self::SystemData) {
+        if !rules.has_single_life() {
+            return;
+        }
+
+        let now = time.absolute_time();
+        if self.next_tick_at > now {
+            return;
+        }
+        self.next_tick_at = now + TICK_INTERVAL;
+
+        let zone = &rules.safe_zone;
+        let center = Vector2::new(zone.x, zone.y);
+        let radius = match &match_state.phase {
+            Phase::Live { ends_at } => {
+                let started_at = ends_at.checked_sub(rules.round_time.into_duration()).unwrap_or_default();
+                zone.radius_at(Second::from(now.checked_sub(started_at).unwrap_or_default()))
+            }
+            _ => zone.initial_radius,
+        };
+
+        if match_state.is_live() {
+            for (entity, _, transform, _) in (&entities, &players, &transforms, !&eliminates).join() {
+                if is_outside(center, radius, to_meter_vec(transform.translation().xy())) {
+                    damage_channel.single_write(DamageEvent {
+                        damage: Damage(zone.damage_per_sec),
+                        target: entity,
+                        attacker: None,
+                        weapon: Some(SAFE_ZONE_WEAPON_NAME.to_string()),
+                    });
+                }
+            }
+        }
+
+        let packet = PacketType::SafeZone(SafeZoneUpdate { center: Point2 { coords: center }, radius });
+        let msg = serialize(&packet).expect("SafeZoneUpdate could not be serialized");
+        client_registry.get_clients().iter().for_each(|&handle| {
+            net.send_with_requirements(
+                handle.addr,
+                &msg,
+                DeliveryRequirement::ReliableSequenced(StreamId::SafeZone.into()),
+                UrgencyRequirement::OnTick,
+            )
+        });
+        replay_channel.single_write(ReplayEvent(packet));
+    }
+}
+
+fn is_outside(center: Vector2<Meter>, radius: Meter, position: Vector2<Meter>) -> bool {
+    magnitude(position - center) > radius
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn positions_beyond_the_radius_are_outside() {
+        let center = Vector2::new(Meter(10.0), Meter(10.0));
+        assert!(!is_outside(center, Meter(5.0), Vector2::new(Meter(13.0), Meter(14.0))));
+        assert!(is_outside(center, Meter(5.0), Vector2::new(Meter(14.0), Meter(14.0))));
+    }
+}
@@ -0,0 +1,117 @@
+use amethyst::{
+    derive::SystemDesc,
+    ecs::{System, SystemData, Read, ReadExpect, Write, WriteExpect, ReadStorage, Join},
+    network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement},
+    shrev::{ReaderId, EventChannel},
+};
+use derive_new::new;
+use std::collections::HashMap;
+
+use crate::components::{Client, Team};
+use crate::resources::{ClientRegistry, KillEvent, MatchState, ReplayEvent, Scoreboard};
+use super::health::is_same_team;
+use westiny_common::network::{PacketType, PlayerScore};
+use westiny_common::serialize;
+
+/// Credits the kills, deaths and assists to the players during the live rounds and keeps the
+/// clients' scoreboard up to date
+#[derive(SystemDesc, new)]
+#[system_desc(name(ScoreSystemDesc))]
+pub struct ScoreSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<KillEvent>,
+
+    /// Scoreboard revision and number of players at the last broadcast
+    #[system_desc(skip)]
+    #[new(default)]
+    last_broadcast: Option<(u32, usize)>,
+}
+
+impl<'s> System<'s> for ScoreSystem {
+    type SystemData = (
+        Read<'s, EventChannel<KillEvent>>,
+        ReadStorage<'s, Client>,
+        ReadStorage<'s, Team>,
+        Read<'s, MatchState>,
+        Write<'s, Scoreboard>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Write<'s, EventChannel<ReplayEvent>>,
+    );
+
+    fn run(&mut self, (kill_event_channel, clients, teams, match_state, mut scoreboard, client_registry, mut net, mut replay_channel): Self::SystemData) {
+        for kill in kill_event_channel.read(&mut self.reader) {
+            if !match_state.is_live() {
+                continue;
+            }
+
+            if let Some(victim) = clients.get(kill.victim) {
+                scoreboard.add_death(victim.id);
+            }
+
+            let victim_team = teams.get(kill.victim);
+            for assist in kill.assists.iter() {
+                if is_same_team(teams.get(*assist), victim_team) {
+                    continue;
+                }
+                if let Some(client) = clients.get(*assist) {
+                    scoreboard.add_assist(client.id);
+                }
+            }
+
+            let killer = match kill.killer {
+                Some(killer) if killer != kill.victim => killer,
+                // Suicide and environmental deaths are not rewarded
+                _ => continue,
+            };
+            if is_same_team(teams.get(killer), victim_team) {
+                continue;
+            }
+
+            if let Some(client) = clients.get(killer) {
+                scoreboard.add_kill(client.id);
+            }
+        }
+
+        let current = Some((scoreboard.revision(), client_registry.client_count()));
+        if current != self.last_broadcast {
+            self.last_broadcast = current;
+            let results = collect_results(&client_registry, &scoreboard, &clients, &teams);
+            let packet = PacketType::Scoreboard(results);
+            let msg = serialize(&packet).expect("Scoreboard could not be serialized");
+            client_registry.get_clients().iter().for_each(|&handle| {
+                net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick)
+            });
+            replay_channel.single_write(ReplayEvent(packet));
+        }
+    }
+}
+
+/// Scores of every connected player, the best first
+pub(super) fn collect_results(
+    client_registry: &ClientRegistry,
+    scoreboard: &Scoreboard,
+    clients: &ReadStorage<'_, Client>,
+    teams: &ReadStorage<'_, Team>,
+) -> Vec<PlayerScore> {
+    let team_of: HashMap<_, _> = (clients, teams).join()
+        .map(|(client, team)| (client.id, *team))
+        .collect();
+
+    let mut results: Vec<_> = client_registry.get_clients().iter()
+        .filter(|handle| !handle.observer)
+        .map(|handle| {
+            let stats = scoreboard.stats_of(handle.id);
+            PlayerScore {
+                player_name: handle.player_name.clone(),
+                team: team_of.get(&handle.id).copied(),
+                kills: stats.kills,
+                deaths: stats.deaths,
+                assists: stats.assists,
+                captures: stats.captures,
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| b.kills.cmp(&a.kills).then(a.deaths.cmp(&b.deaths)));
+    results
+}
@@ -1,11 +1,13 @@
-use amethyst::ecs::{Read, System, ReadStorage, ReadExpect, Entities, WriteStorage, WriteExpect};
+use amethyst::ecs::{Read, System, ReadStorage, ReadExpect, Entities, Write, WriteStorage, WriteExpect};
+use amethyst::shrev::EventChannel;
 use amethyst::core::{Transform, Time, math::{Vector3, Vector2}};
 use amethyst::ecs::prelude::{LazyUpdate, Join};
 
-use crate::components::{Damage, Client, weapon::Weapon, weapon::Holster, Input, InputFlags, BoundingCircle};
-use westiny_common::entities::spawn_bullet;
+use crate::components::{Damage, Client, weapon::Weapon, weapon::Holster, Input, InputFlags, BoundingCircle, Explosive, Owner, Grenade, EntityType, WeaponName, OnHitEffect, Stamina, StatusEffects};
+use amethyst::ecs::Entity;
+use westiny_common::entities::{spawn_bullet, spawn_grenade, GRENADE_RADIUS};
 use amethyst::prelude::Builder;
-use crate::resources::{ClientRegistry, StreamId, ClientID};
+use crate::resources::{ClientRegistry, StreamId, ClientID, NetworkIdSupplier, ReplayEvent};
 use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
 use westiny_common::serialize;
 use westiny_common::network::{PacketType, ShotEvent, PlayerUpdate};
@@ -13,6 +15,7 @@ use amethyst::core::math::Point2;
 use std::time::Duration;
 use westiny_common::metric_dimension::MeterPerSec;
 use westiny_common::metric_dimension::length::Meter;
+use super::inventory::send_inventory_update;
 
 pub struct ShooterSystem;
 
@@ -24,45 +27,49 @@ impl<'s> System<'s> for ShooterSystem {
         ReadStorage<'s, BoundingCircle>,
         WriteStorage<'s, Holster>,
         ReadStorage<'s, Client>,
+        ReadStorage<'s, StatusEffects>,
+        ReadStorage<'s, Stamina>,
         Read<'s, Time>,
         ReadExpect<'s, LazyUpdate>,
         ReadExpect<'s, ClientRegistry>,
-        WriteExpect<'s, TransportResource>
+        WriteExpect<'s, TransportResource>,
+        WriteExpect<'s, NetworkIdSupplier>,
+        Write<'s, EventChannel<ReplayEvent>>,
     );
 
-    fn run(&mut self, (entities, transforms, inputs, bounds, mut holsters, clients, time, lazy_update, client_registry, mut net): Self::SystemData) {
-        for (input, player_transform, bound, holster, client) in (&inputs, &transforms, (&bounds).maybe(), &mut holsters, (&clients).maybe()).join() {
-            if let Some(selected_slot) = Self::selected_slot(&input) {
-                if holster.active_slot() != selected_slot {
-                    if let Some(gun_name) = holster.switch(selected_slot) {
-                        let gun = holster.active_gun_mut();
+    fn run(&mut self, (entities, transforms, inputs, bounds, mut holsters, clients, status_effects, staminas, time, lazy_update, client_registry, mut net, mut net_id_supplier, mut replay_channel): Self::SystemData) {
+        for (shooter, input, player_transform, bound, holster, client, effects, stamina) in (&entities, &inputs, &transforms, (&bounds).maybe(), &mut holsters, (&clients).maybe(), (&status_effects).maybe(), (&staminas).maybe()).join() {
+            if let Some(selected_slot) = input.selected_slot() {
+                if holster.active_slot() != selected_slot && holster.switch(selected_slot) {
+                    if let Some(gun) = holster.active_gun_mut() {
                         if gun.reload_started_at.is_some() {
                             // if last switch from this happened mid-reload, restart it
-                            gun.reload_started_at = Some(time.absolute_real_time());
+                            gun.reload_started_at = Some(time.absolute_time());
                         }
+                    }
 
-                        if let Some(client) = client.and_then(|client| client_registry.find_client(client.id)) {
-                            let payload_packet = PacketType::PlayerUpdate(PlayerUpdate::WeaponSwitch {
-                                name: gun_name.to_string(),
-                                magazine_size: gun.details.magazine_size,
-                                ammo_in_magazine: gun.bullets_left_in_magazine,
-                            });
-
-                            let payload = serialize(&payload_packet).expect(&format!("Failed to serialize 'WeaponSwitch' packet: {:?}", payload_packet));
-                            net.send_with_requirements(client.addr,
-                                                       &payload,
-                                                       DeliveryRequirement::ReliableSequenced(StreamId::WeaponSwitch.into()),
-                                                       UrgencyRequirement::OnTick);
+                    if let Some(client) = client {
+                        if let Err(err) = send_inventory_update(&client.id, &client_registry, &mut net, holster) {
+                            log::error!("Failed to send inventory update to client {:?}. Error: {}", client.id, err);
                         }
                     }
                 }
             }
 
-            let mut weapon = holster.active_gun_mut();
+            let cooled_down = holster.is_cooled_down(time.absolute_time_seconds());
+            let mut cooldown_until = None;
+            let mut weapon = match holster.active_gun_mut() {
+                Some(weapon) => weapon,
+                None => continue,
+            };
 
+            // Sprinting players can not aim
+            let sprinting = stamina.map_or(false, |stamina| stamina.is_sprinting(input));
             if input.flags.intersects(InputFlags::SHOOT) {
-                if weapon.is_allowed_to_shoot(time.absolute_time_seconds()) {
-                    Self::shoot(&entities, &time, &lazy_update, &client_registry, &mut net, player_transform, bound, &mut weapon, client);
+                let fire_rate_multiplier = effects.map_or(1.0, StatusEffects::fire_rate_multiplier);
+                if !sprinting && cooled_down && weapon.is_allowed_to_shoot(time.absolute_time_seconds(), fire_rate_multiplier) {
+                    Self::shoot(&entities, &time, &lazy_update, &client_registry, &mut net, &mut replay_channel, &mut net_id_supplier, shooter, player_transform, bound, &mut weapon, client);
+                    cooldown_until = Some(weapon.last_shot_time + weapon.shoot_interval(fire_rate_multiplier));
                 }
             } else {
                 weapon.input_lifted = true;
@@ -71,6 +78,10 @@ impl<'s> System<'s> for ShooterSystem {
             if let Some(reload_start) = weapon.reload_started_at {
                 Self::check_reload_finish(&time, &client_registry, &mut net, weapon, client, &reload_start)
             }
+
+            if let Some(cooldown_until) = cooldown_until {
+                holster.start_cooldown(cooldown_until);
+            }
         }
     }
 }
@@ -100,6 +111,9 @@ impl ShooterSystem {
              lazy_update: &LazyUpdate,
              client_registry: &ClientRegistry,
              mut net: &mut TransportResource,
+             replay_channel: &mut EventChannel<ReplayEvent>,
+             net_id_supplier: &mut NetworkIdSupplier,
+             shooter: Entity,
              player_transform: &Transform,
              bound: Option<&BoundingCircle>,
              mut weapon: &mut Weapon,
@@ -116,14 +130,51 @@ impl ShooterSystem {
         }
 
         let velocity = weapon.details.bullet_speed * direction2d;
-        let bullet_builder = lazy_update.create_entity(&entities)
-            .with(Damage(weapon.details.damage));
 
-        spawn_bullet(bullet_transform.clone(),
-                     velocity.clone(),
-                     time.absolute_time(),
-                     weapon.bullet_lifespan_sec(),
-                     bullet_builder);
+        // Grenades are replicated by their NetworkId, there is no need for a shot event
+        let is_grenade = match (&weapon.details.grenade, &weapon.details.explosion) {
+            (Some(grenade_details), Some(explosion)) => {
+                let fuse = std::time::Duration::from_secs_f32(grenade_details.fuse.0);
+                let grenade = Grenade::new(grenade_details.clone(), explosion.clone(), time.absolute_time() + fuse);
+                // Placed a bit farther than the bullets so that it does not bounce off the thrower
+                let mut grenade_transform = bullet_transform.clone();
+                *grenade_transform.translation_mut() -= (GRENADE_RADIUS * 1.5).into_pixel() * direction3d;
+                spawn_grenade(grenade_transform,
+                              velocity.clone(),
+                              grenade,
+                              net_id_supplier.next(EntityType::Grenade),
+                              lazy_update.create_entity(&entities)
+                                  .with(Owner(shooter))
+                                  .with(WeaponName(weapon.details.name.clone())));
+                true
+            }
+            (Some(_), None) => {
+                log::warn!("Grenade {} has no explosion set, it is thrown as a bullet", weapon.details.name);
+                false
+            }
+            _ => false,
+        };
+
+        if !is_grenade {
+            let bullet_builder = lazy_update.create_entity(&entities)
+                .with(Damage(weapon.details.damage))
+                .with(Owner(shooter))
+                .with(WeaponName(weapon.details.name.clone()));
+            let bullet_builder = match &weapon.details.explosion {
+                Some(explosion) => bullet_builder.with(Explosive(explosion.clone())),
+                None => bullet_builder,
+            };
+            let bullet_builder = match &weapon.details.status_effect {
+                Some(effect) => bullet_builder.with(OnHitEffect(effect.clone())),
+                None => bullet_builder,
+            };
+
+            spawn_bullet(bullet_transform.clone(),
+                         velocity.clone(),
+                         time.absolute_time(),
+                         weapon.bullet_lifespan_sec(),
+                         bullet_builder);
+        }
 
         weapon.last_shot_time = time.absolute_time_seconds();
         weapon.input_lifted = false;
@@ -143,26 +194,31 @@ impl ShooterSystem {
             weapon.reload_started_at = Some(time.absolute_time());
         }
 
-        Self::broadcast_shot_event(client_registry, net, &mut weapon, &mut bullet_transform, &velocity)
+        if !is_grenade {
+            Self::broadcast_shot_event(client_registry, net, replay_channel, &mut weapon, &mut bullet_transform, &velocity)
+        }
     }
 
     fn broadcast_shot_event(client_registry: &ClientRegistry,
                             net: &mut TransportResource,
+                            replay_channel: &mut EventChannel<ReplayEvent>,
                             weapon: &mut Weapon,
                             bullet_transform: &mut Transform,
                             velocity: &Vector2<MeterPerSec>) {
-        let payload = serialize(&PacketType::ShotEvent(ShotEvent {
+        let packet = PacketType::ShotEvent(ShotEvent {
             position: Point2::new(Meter::from_pixel(bullet_transform.translation().x), Meter::from_pixel(bullet_transform.translation().y)),
             velocity: *velocity,
             bullet_time_limit_secs: weapon.bullet_lifespan_sec(),
-        })).expect("ShotEvent's serialization failed");
+        });
+        let payload = serialize(&packet).expect("ShotEvent's serialization failed");
 
         client_registry.get_clients().iter().map(|handle| handle.addr).for_each(|addr| {
             net.send_with_requirements(addr,
                                        &payload,
                                        DeliveryRequirement::ReliableSequenced(StreamId::ShotEvent.into()),
                                        UrgencyRequirement::OnTick);
-        })
+        });
+        replay_channel.single_write(ReplayEvent(packet));
     }
 
     fn check_reload_finish(time: &Time,
@@ -185,17 +241,6 @@ impl ShooterSystem {
             }
         }
     }
-
-    fn selected_slot(input: &Input) -> Option<usize> {
-        input.get_selection().and_then(|&select| {
-            match select {
-                InputFlags::SELECT1 => Some(0_usize),
-                InputFlags::SELECT2 => Some(1_usize),
-                InputFlags::SELECT3 => Some(2_usize),
-                _ => None
-            }
-        })
-    }
 }
 
 #[cfg(test)]
@@ -239,6 +284,7 @@ mod test {
                 };
 
                 let gun = WeaponDetails {
+                    name: "Weapon".to_string(),
                     damage: 5,
                     bullet_distance_limit: Meter(7.5),
                     fire_rate: f32::max_value(),
@@ -248,12 +294,15 @@ mod test {
                     shot: weapon::Shot::Single,
                     bullet_speed: MeterPerSec(12.5),
                     pellet_number: 1,
+                    explosion: None,
+                    grenade: None,
+                    status_effect: None,
                 };
 
-                let guns = [
-                    (Weapon::new(gun.clone()), "Weapon1"),
-                    (Weapon::new(gun.clone()), "Weapon2"),
-                    (Weapon::new(gun), "Weapon3"),
+                let guns = vec![
+                    Weapon::new(gun.clone()),
+                    Weapon::new(gun.clone()),
+                    Weapon::new(gun),
                 ];
 
                 world.create_entity()
@@ -265,6 +314,7 @@ mod test {
             })
             .with_resource(client_registry)
             .with_resource(TransportResource::new())
+            .with_resource(NetworkIdSupplier::new())
             .with_system(ShooterSystem, "shooter", &[])
             .with_assertion(|world: &mut World| {
                 let net = world.fetch_mut::<TransportResource>();
@@ -1,16 +1,25 @@
 use amethyst::derive::SystemDesc;
-use amethyst::core::ecs::{System, SystemData, ReadStorage, Read, Join, LazyUpdate, ReadExpect, Entities, Builder,  ReaderId, WriteExpect};
+use amethyst::core::ecs::{System, SystemData, ReadStorage, Read, Join, LazyUpdate, ReadExpect, Entities, Builder,  ReaderId, Write, WriteExpect};
 use amethyst::core::{Time, Transform};
 use crate::components;
 use amethyst::core::math::Point2;
-use std::time::Duration;
 use westiny_common::collision;
 use amethyst::core::ecs::shrev::EventChannel;
 use westiny_common::events::EntityDelete;
 use derive_new::new;
-use crate::resources::ClientRegistry;
+use crate::resources::{ClientRegistry, GameRules, MatchState, ReplayEvent};
 use westiny_common::resources::weapon::GunResource;
 use westiny_common::metric_dimension::length::Meter;
+use amethyst::network::simulation::TransportResource;
+use crate::components::weapon::Holster;
+use super::inventory::send_inventory_update;
+use super::health::send_health_update;
+use super::death::notify_spectator;
+use westiny_common::resources::map::{MapMetadata, SpawnZone};
+use westiny_common::network::{HealthState, PacketType, TeamMember};
+use westiny_common::serialize;
+use amethyst::network::simulation::{DeliveryRequirement, UrgencyRequirement};
+use std::collections::HashMap;
 
 pub struct RespawnSystem;
 
@@ -20,12 +29,14 @@ impl<'s> System<'s> for RespawnSystem {
         ReadStorage<'s, components::Eliminated>,
         ReadStorage<'s, components::NetworkId>,
         ReadStorage<'s, components::Client>,
+        ReadStorage<'s, components::Team>,
         ReadStorage<'s, Transform>,
         Read<'s, Time>,
         ReadExpect<'s, LazyUpdate>,
         Entities<'s>,
         WriteExpect<'s, EventChannel<EntityDelete>>,
         WriteExpect<'s, EventChannel<SpawnPlayerEvent>>,
+        Read<'s, MatchState>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -34,34 +45,40 @@ impl<'s> System<'s> for RespawnSystem {
             eliminates,
             net_ids,
             clients,
+            teams,
             transforms,
             time,
             lazy,
             entities,
             mut entity_delete_event_channel,
             mut spawn_player_event_channel,
+            match_state,
         ) = data;
 
-        for (respawn, eliminate, net_id, client, opt_transform, entity)
-                in (&respawns, &eliminates, &net_ids, &clients, (&transforms).maybe(), &entities).join() {
+        for (respawn, eliminate, net_id, client, team, opt_transform, entity)
+                in (&respawns, &eliminates, &net_ids, &clients, (&teams).maybe(), (&transforms).maybe(), &entities).join() {
             if opt_transform.is_some() {
                 // has not been removed yet
                 // create a new entity that is waiting until respawn time expires.
-                lazy.create_entity(&entities)
+                let waiting = lazy.create_entity(&entities)
                     .with(*respawn)
                     .with(*eliminate)
                     .with(*net_id)
-                    .with(*client)
-                    .build();
-            } else {
+                    .with(*client);
+                match team {
+                    Some(team) => waiting.with(*team).build(),
+                    None => waiting.build(),
+                };
+            } else if !match_state.is_intermission() {
                 // we're waiting for respawn time expiration
-                if time.absolute_time_seconds() - eliminate.elimination_time_sec >= respawn.respawn_duration.as_secs_f64() {
+                if is_respawn_due(respawn, eliminate, &time) {
                     // if expired
 
                     log::debug!("Request player spawn");
                     spawn_player_event_channel.single_write(SpawnPlayerEvent {
                         client: *client,
                         network_id: *net_id,
+                        team: team.copied(),
                     });
 
                     entity_delete_event_channel.single_write(EntityDelete { entity_id: entity });
@@ -71,6 +88,11 @@ impl<'s> System<'s> for RespawnSystem {
     }
 }
 
+/// The respawn placeholder of an eliminated player has waited long enough
+pub(super) fn is_respawn_due(respawn: &components::Respawn, eliminated: &components::Eliminated, time: &Time) -> bool {
+    time.absolute_time_seconds() - eliminated.elimination_time_sec >= respawn.respawn_duration.as_secs_f64()
+}
+
 #[derive(SystemDesc, new)]
 #[system_desc(name(SpawnSystemDesc))]
 pub struct SpawnSystem {
@@ -87,6 +109,12 @@ impl<'s> System<'s> for SpawnSystem {
         ReadExpect<'s, LazyUpdate>,
         ReadExpect<'s, ClientRegistry>,
         ReadExpect<'s, GunResource>,
+        WriteExpect<'s, TransportResource>,
+        ReadExpect<'s, MapMetadata>,
+        ReadStorage<'s, components::NetworkId>,
+        ReadStorage<'s, components::Team>,
+        ReadExpect<'s, GameRules>,
+        Write<'s, EventChannel<ReplayEvent>>,
     );
 
     fn run(&mut self, data: Self::SystemData) {
@@ -98,28 +126,95 @@ impl<'s> System<'s> for SpawnSystem {
             lazy,
             client_registry,
             gun_resource,
+            mut net,
+            map_metadata,
+            network_ids,
+            teams,
+            rules,
+            mut replay_channel,
         ) = data;
 
+        // Players are not respawned in single life modes, they spectate after their death
+        let respawn = if rules.has_single_life() {
+            None
+        } else {
+            Some(components::Respawn { respawn_duration: rules.respawn_time.into_duration() })
+        };
+
+        let mut spawned_members = Vec::new();
         for spawn_event in spawn_event_channel.read(&mut self.reader) {
-            let spawn_pos = SpawnSystem::find_spawn_pos(&transforms, &boundings);
+            let spawn_zones = map_metadata.spawn_zones_of(spawn_event.team);
+            let spawn_pos = SpawnSystem::find_spawn_pos(&transforms, &boundings, &spawn_zones);
+            let holster = Holster::new(&gun_resource);
+            if let Err(err) = send_inventory_update(&spawn_event.client.id, &client_registry, &mut net, &holster) {
+                log::error!("Failed to send initial inventory to client {:?}. Error: {}", spawn_event.client.id, err);
+            }
+            let max_health = components::MaxHealth(rules.max_health);
+            let health_state = HealthState { health: components::Health(max_health.0), max_health, armor: components::Armor::default() };
+            if let Err(err) = send_health_update(&spawn_event.client.id, &client_registry, &mut net, health_state) {
+                log::error!("Failed to send initial health to client {:?}. Error: {}", spawn_event.client.id, err);
+            }
+            // Spectators of the previous life or round get their own character back
+            notify_spectator(&client_registry, &mut net, spawn_event.client.id, false);
+
             SpawnSystem::spawn_player(&spawn_pos,
                                       &entities,
                                       spawn_event.client,
                                       spawn_event.network_id,
-                                      &gun_resource,
+                                      spawn_event.team,
+                                      respawn,
+                                      holster,
+                                      max_health,
                                       &lazy);
             log::info!("Player created for {}", client_registry.find_client(spawn_event.client.id).unwrap().player_name);
+
+            if let Some(team) = spawn_event.team {
+                spawned_members.push(TeamMember { network_id: spawn_event.network_id, team });
+            }
+        }
+
+        if !spawned_members.is_empty() {
+            broadcast_team_roster(&client_registry, &mut net, &mut replay_channel, &network_ids, &teams, spawned_members);
         }
     }
 }
 
+/// Sends the team of every player to the clients, so they can color the characters
+fn broadcast_team_roster(
+    client_registry: &ClientRegistry,
+    net: &mut TransportResource,
+    replay_channel: &mut EventChannel<ReplayEvent>,
+    network_ids: &ReadStorage<'_, components::NetworkId>,
+    teams: &ReadStorage<'_, components::Team>,
+    spawned_members: Vec<TeamMember>,
+) {
+    // Players waiting for respawn are also listed, so the roster is deduplicated by network id
+    let mut roster: HashMap<components::NetworkId, components::Team> = (network_ids, teams).join()
+        .map(|(network_id, team)| (*network_id, *team))
+        .collect();
+    roster.extend(spawned_members.into_iter().map(|member| (member.network_id, member.team)));
+
+    let members = roster.into_iter()
+        .map(|(network_id, team)| TeamMember { network_id, team })
+        .collect();
+    let packet = PacketType::TeamRoster(members);
+    let msg = serialize(&packet).expect("TeamRoster could not be serialized");
+    client_registry.get_clients().iter().for_each(|&handle| {
+        net.send_with_requirements(handle.addr, &msg, DeliveryRequirement::Reliable, UrgencyRequirement::OnTick)
+    });
+    replay_channel.single_write(ReplayEvent(packet));
+}
+
 impl SpawnSystem {
     fn spawn_player(
         initial_pos: &Point2<f32>,
         entities: &Entities<'_>,
         client: components::Client,
         network_id: components::NetworkId,
-        gun_resource: &GunResource,
+        team: Option<components::Team>,
+        respawn: Option<components::Respawn>,
+        holster: Holster,
+        max_health: components::MaxHealth,
         lazy_update: &LazyUpdate,
     ) {
         let transform = {
@@ -128,19 +223,30 @@ impl SpawnSystem {
             t
         };
 
-        lazy_update
+        let builder = lazy_update
             .create_entity(entities)
             .with(client)
             .with(network_id)
             .with(components::Player)
             .with(transform)
-            .with(components::Health(100))
+            .with(components::Health(max_health.0))
+            .with(max_health)
+            .with(components::Armor::default())
+            .with(components::Stamina::default())
             .with(components::Input::default())
             .with(components::Velocity::default())
             .with(components::BoundingCircle { radius: Meter(0.5) })
-            .with(components::Respawn {respawn_duration: Duration::from_secs(5)})
-            .with(components::weapon::Holster::new(&gun_resource))
-            .build();
+            .with(holster)
+            .with(components::Melee::default());
+        let builder = match respawn {
+            Some(respawn) => builder.with(respawn),
+            None => builder,
+        };
+
+        match team {
+            Some(team) => builder.with(team).build(),
+            None => builder.build(),
+        };
     }
 
     fn has_collision(
@@ -159,9 +265,11 @@ impl SpawnSystem {
         false
     }
 
+    /// Finds a free place in one of the spawn zones. Anywhere on the map if no zone is given.
     fn find_spawn_pos(
         transform_storage: &ReadStorage<'_, Transform>,
-        bounding_storage: &ReadStorage<'_, components::BoundingCircle>
+        bounding_storage: &ReadStorage<'_, components::BoundingCircle>,
+        spawn_zones: &[&SpawnZone],
     ) -> Point2<f32> {
         use rand::Rng;
 
@@ -174,9 +282,16 @@ impl SpawnSystem {
         let candidate_bounding = components::BoundingCircle { radius: Meter(0.5) };
 
         for _ in 0..MAX_TRIAL_ITERATION {
-            // TODO hardcoded range: should be calculated from map data
-            let x = rand::thread_rng().gen_range(-BOUND .. BOUND);
-            let y = rand::thread_rng().gen_range(-BOUND .. BOUND);
+            let (x, y) = if spawn_zones.is_empty() {
+                // TODO hardcoded range: should be calculated from map data
+                (rand::thread_rng().gen_range(-BOUND .. BOUND), rand::thread_rng().gen_range(-BOUND .. BOUND))
+            } else {
+                let zone = spawn_zones[rand::thread_rng().gen_range(0 .. spawn_zones.len())];
+                let angle = rand::thread_rng().gen_range(0.0 .. 2.0 * std::f32::consts::PI);
+                // The square root spreads the spawns evenly over the area instead of packing them to the center
+                let distance = zone.radius.into_pixel() * rand::thread_rng().gen::<f32>().sqrt();
+                (zone.x.into_pixel() + distance * angle.cos(), zone.y.into_pixel() + distance * angle.sin())
+            };
 
             let candidate_transform = {
                 let mut t = Transform::default();
@@ -199,19 +314,22 @@ impl SpawnSystem {
 pub struct SpawnPlayerEvent {
     pub client: components::Client,
     pub network_id: components::NetworkId,
+    /// Not set when the game mode has no teams
+    pub team: Option<components::Team>,
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::components::{Client, EntityType, Health, Respawn, BoundingCircle, Input, NetworkId, Player, Velocity,
+                            Melee, Team, Stamina, MaxHealth, Armor,
     };
     use amethyst::ecs::prelude::*;
     use amethyst::ecs::World;
     use amethyst::core::Transform;
     use crate::resources::ClientID;
     use amethyst::utils::application_root_dir;
-    use crate::components::weapon::Holster;
+    use std::time::Duration;
 
     fn create_testworld() -> World {
         let mut world = World::new();
@@ -225,6 +343,11 @@ mod test {
         world.register::<Health>();
         world.register::<Respawn>();
         world.register::<Holster>();
+        world.register::<Melee>();
+        world.register::<Team>();
+        world.register::<Stamina>();
+        world.register::<MaxHealth>();
+        world.register::<Armor>();
 
         let resources_path = application_root_dir().unwrap().join("../resources");
 
@@ -243,7 +366,10 @@ mod test {
                 &world.entities(),
                 Client{id: cli_id},
                 NetworkId {id: 0, entity_type: EntityType::Player},
-                &world.read_resource::<GunResource>(),
+                None,
+                Some(Respawn { respawn_duration: Duration::from_secs(5) }),
+                Holster::new(&world.read_resource::<GunResource>()),
+                MaxHealth(100),
                 &world.read_resource::<LazyUpdate>(),
             );
         }
@@ -266,7 +392,10 @@ mod test {
                 &world.entities(),
                 Client {id: ClientID(42)},
                 NetworkId {id: 0, entity_type: EntityType::Player},
-                &world.read_resource::<GunResource>(),
+                None,
+                Some(Respawn { respawn_duration: Duration::from_secs(5) }),
+                Holster::new(&world.read_resource::<GunResource>()),
+                MaxHealth(100),
                 &world.read_resource::<LazyUpdate>(),
             );
             SpawnSystem::spawn_player(
@@ -274,7 +403,10 @@ mod test {
                 &world.entities(),
                 Client {id: ClientID(43)},
                 NetworkId { id: 1, entity_type: EntityType::Player},
-                &world.read_resource::<GunResource>(),
+                None,
+                Some(Respawn { respawn_duration: Duration::from_secs(5) }),
+                Holster::new(&world.read_resource::<GunResource>()),
+                MaxHealth(100),
                 &world.read_resource::<LazyUpdate>(),
             );
         }
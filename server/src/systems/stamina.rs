@@ -0,0 +1,131 @@
+use amethyst::core::Time;
+use amethyst::ecs::{System, ReadStorage, WriteStorage, ReadExpect, WriteExpect, Read, Entities, Entity, Join};
+use amethyst::network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement};
+use std::collections::HashMap;
+
+use crate::components::{Client, Input, InputFlags, Stamina, MAX_STAMINA};
+use crate::resources::{ClientRegistry, StreamId};
+use westiny_common::network::{PacketType, PlayerUpdate};
+use westiny_common::serialize;
+
+const STAMINA_DRAIN_PER_SEC: f32 = 25.0;
+const STAMINA_REGEN_PER_SEC: f32 = 15.0;
+/// Exhausted players can sprint again from this much stamina without pressing the run key again
+const RECOVERED_STAMINA: f32 = 25.0;
+
+/// Drains the stamina of the sprinting players and regenerates the others'.
+/// Clients are told about the change of every whole point and of the exhaustion.
+#[derive(Default)]
+pub struct StaminaSystem {
+    last_sent: HashMap<Entity, (u16, bool)>,
+}
+
+impl<'s> System<'s> for StaminaSystem {
+    type SystemData = (
+        Entities<'s>,
+        ReadStorage<'s, Input>,
+        WriteStorage<'s, Stamina>,
+        ReadStorage<'s, Client>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (entities, inputs, mut staminas, clients, client_registry, mut net, time): Self::SystemData) {
+        self.last_sent.retain(|entity, _| entities.is_alive(*entity));
+
+        for (entity, input, stamina, client) in (&entities, &inputs, &mut staminas, (&clients).maybe()).join() {
+            *stamina = next_stamina(*stamina, input, time.delta_seconds());
+
+            let client = match client {
+                Some(client) => client,
+                None => continue,
+            };
+            let sent = (stamina.value.round() as u16, stamina.exhausted);
+            if self.last_sent.get(&entity) == Some(&sent) {
+                continue;
+            }
+            self.last_sent.insert(entity, sent);
+
+            if let Err(err) = send_stamina_update(client, &client_registry, &mut net, *stamina) {
+                log::error!("Failed to send stamina update to client {:?}. Error: {}", client.id, err);
+            }
+        }
+    }
+}
+
+/// Running out of stamina exhausts the player, otherwise a single tick of regeneration would
+/// let them sprint again. The exhaustion lasts until the stamina recovers or the run key is released.
+fn next_stamina(stamina: Stamina, input: &Input, delta_seconds: f32) -> Stamina {
+    let change = if stamina.is_sprinting(input) { -STAMINA_DRAIN_PER_SEC } else { STAMINA_REGEN_PER_SEC };
+    let value = (stamina.value + change * delta_seconds).min(MAX_STAMINA).max(0.0);
+    let exhausted = value <= 0.0
+        || (stamina.exhausted && value < RECOVERED_STAMINA && input.flags.contains(InputFlags::RUN));
+    Stamina { value, exhausted }
+}
+
+fn send_stamina_update(
+    client: &Client,
+    client_registry: &ClientRegistry,
+    net: &mut TransportResource,
+    stamina: Stamina,
+) -> anyhow::Result<()> {
+    let handle = client_registry.find_client(client.id)
+        .ok_or(anyhow::anyhow!("Client [id: {:?}] not found in registry", client.id))?;
+    let payload = serialize(&PacketType::PlayerUpdate(PlayerUpdate::StaminaUpdate(stamina)))
+        .map_err(|err| anyhow::anyhow!("Failed to serialize StaminaUpdate: {}", err))?;
+    net.send_with_requirements(
+        handle.addr,
+        &payload,
+        DeliveryRequirement::ReliableSequenced(StreamId::StaminaUpdate.into()),
+        UrgencyRequirement::OnTick,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn input(flags: InputFlags) -> Input {
+        Input { flags, ..Input::default() }
+    }
+
+    #[test]
+    fn stamina_drains_while_sprinting_and_regenerates_otherwise() {
+        let sprint = input(InputFlags::RUN | InputFlags::FORWARD);
+        let walk = input(InputFlags::FORWARD);
+        assert_eq!(next_stamina(Stamina::new(50.0), &sprint, 1.0).value, 50.0 - STAMINA_DRAIN_PER_SEC);
+        assert_eq!(next_stamina(Stamina::new(50.0), &walk, 1.0).value, 50.0 + STAMINA_REGEN_PER_SEC);
+        assert_eq!(next_stamina(Stamina::new(5.0), &sprint, 1.0).value, 0.0);
+        assert_eq!(next_stamina(Stamina::new(MAX_STAMINA), &walk, 1.0).value, MAX_STAMINA);
+    }
+
+    #[test]
+    fn exhausted_players_walk_until_recovered_or_the_run_key_is_pressed_again() {
+        let sprint = input(InputFlags::RUN | InputFlags::FORWARD);
+        let frame = 1.0 / 60.0;
+
+        let mut stamina = next_stamina(Stamina::new(0.1), &sprint, frame);
+        assert_eq!(stamina, Stamina { value: 0.0, exhausted: true });
+
+        // Holding the run key does not sprint on the regenerated stamina
+        for _ in 0..60 {
+            assert!(!stamina.is_sprinting(&sprint));
+            stamina = next_stamina(stamina, &sprint, frame);
+        }
+        assert!((stamina.value - STAMINA_REGEN_PER_SEC).abs() < 0.01, "{:?}", stamina);
+        assert!(stamina.exhausted);
+
+        // Until it recovers
+        stamina = next_stamina(stamina, &sprint, (RECOVERED_STAMINA - stamina.value) / STAMINA_REGEN_PER_SEC + frame);
+        assert!(stamina.is_sprinting(&sprint));
+
+        // Or the run key is released and pressed again
+        let exhausted = Stamina { value: 5.0, exhausted: true };
+        assert!(next_stamina(exhausted, &sprint, frame).exhausted);
+        let released = next_stamina(exhausted, &input(InputFlags::FORWARD), frame);
+        assert!(!released.exhausted);
+        assert!(released.is_sprinting(&sprint));
+    }
+}
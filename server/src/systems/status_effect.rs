@@ -0,0 +1,199 @@
+use amethyst::{
+    core::Time,
+    derive::SystemDesc,
+    ecs::{System, SystemData, Entities, Entity, Read, ReadExpect, ReadStorage, Write, WriteExpect, WriteStorage, Join},
+    network::simulation::{TransportResource, DeliveryRequirement, UrgencyRequirement},
+    shrev::{ReaderId, EventChannel},
+};
+use derive_new::new;
+use std::collections::HashSet;
+use std::time::Duration;
+
+use crate::components::{Client, Damage, Player, StatusEffects};
+use crate::resources::{ClientRegistry, StreamId};
+use westiny_common::events::{DamageEvent, StatusEffectEvent};
+use westiny_common::network::{PacketType, PlayerUpdate, StatusEffectState};
+use westiny_common::serialize;
+
+/// Damage over time is dealt this often
+const TICK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Applies the status effects on the players, deals their damage over time and removes them when
+/// they expire. The affected player's client is informed about every change.
+#[derive(SystemDesc, new)]
+#[system_desc(name(StatusEffectSystemDesc))]
+pub struct StatusEffectSystem {
+    #[system_desc(event_channel_reader)]
+    reader: ReaderId<StatusEffectEvent>,
+
+    #[system_desc(skip)]
+    #[new(default)]
+    next_tick_at: Duration,
+}
+
+impl<'s> System<'s> for StatusEffectSystem {
+    type SystemData = (
+        Read<'s, EventChannel<StatusEffectEvent>>,
+        Write<'s, EventChannel<DamageEvent>>,
+        Entities<'s>,
+        ReadStorage<'s, Player>,
+        ReadStorage<'s, Client>,
+        WriteStorage<'s, StatusEffects>,
+        ReadExpect<'s, ClientRegistry>,
+        WriteExpect<'s, TransportResource>,
+        Read<'s, Time>,
+    );
+
+    fn run(&mut self, (status_effect_channel, mut damage_channel, entities, players, clients, mut status_effects, client_registry, mut net, time): Self::SystemData) {
+        let now = time.absolute_time();
+        let mut changed = HashSet::<Entity>::new();
+
+        for event in status_effect_channel.read(&mut self.reader) {
+            if !players.contains(event.target) || !entities.is_alive(event.target) {
+                continue;
+            }
+            match status_effects.entry(event.target) {
+                Ok(entry) => entry.or_insert_with(StatusEffects::default).apply(event.effect.clone(), event.source, now),
+                Err(err) => {
+                    log::error!("Could not apply status effect on {:?}: {}", event.target, err);
+                    continue;
+                }
+            }
+            changed.insert(event.target);
+        }
+
+        let is_tick = self.next_tick_at <= now;
+        if is_tick {
+            self.next_tick_at = now + TICK_INTERVAL;
+        }
+
+        for (entity, effects) in (&entities, &mut status_effects).join() {
+            if is_tick {
+                for effect in effects.effects().iter().filter(|effect| effect.details.damage_per_sec > 0) {
+                    damage_channel.single_write(DamageEvent {
+                        damage: Damage(effect.details.damage_per_sec.saturating_mul(effect.stacks.min(u16::MAX.into()) as u16)),
+                        target: entity,
+                        attacker: effect.source,
+                        weapon: Some(format!("{:?}", effect.details.kind)),
+                    });
+                }
+            }
+
+            if effects.remove_expired(now) {
+                changed.insert(entity);
+            }
+        }
+
+        for entity in changed {
+            let state = status_effects.get(entity).map(|effects| effects.state(now)).unwrap_or_default();
+            if state.is_empty() {
+                status_effects.remove(entity);
+            }
+
+            if let Some(client) = clients.get(entity) {
+                if let Err(err) = send_status_effects(client, &client_registry, &mut net, state) {
+                    log::error!("Failed to send status effects to client {:?}. Error: {}", client.id, err);
+                }
+            }
+        }
+    }
+}
+
+fn send_status_effects(
+    client: &Client,
+    client_registry: &ClientRegistry,
+    net: &mut TransportResource,
+    state: Vec<StatusEffectState>,
+) -> anyhow::Result<()> {
+    let handle = client_registry.find_client(client.id)
+        .ok_or(anyhow::anyhow!("Client [id: {:?}] not found in registry", client.id))?;
+    let payload = serialize(&PacketType::PlayerUpdate(PlayerUpdate::StatusEffects(state)))
+        .map_err(|err| anyhow::anyhow!("Failed to serialize StatusEffects: {}", err))?;
+    net.send_with_requirements(
+        handle.addr,
+        &payload,
+        DeliveryRequirement::ReliableSequenced(StreamId::StatusEffects.into()),
+        UrgencyRequirement::OnTick,
+    );
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use amethyst::Error;
+    use amethyst::prelude::{World, WorldExt, Builder};
+    use amethyst_test::prelude::*;
+    use std::net::SocketAddr;
+    use westiny_common::components::{StatusEffectDetails, StatusEffectKind};
+    use westiny_common::deserialize;
+    use westiny_common::metric_dimension::Second;
+
+    fn effect(kind: StatusEffectKind, damage_per_sec: u16) -> StatusEffectDetails {
+        StatusEffectDetails {
+            kind,
+            // Expires in the frame it is applied, right after its first tick
+            duration: Second(0.0),
+            speed_multiplier: 1.0,
+            fire_rate_multiplier: 1.0,
+            damage_per_sec,
+            max_stacks: 3,
+        }
+    }
+
+    #[test]
+    fn effects_deal_damage_per_stack_and_expire() -> Result<(), Error> {
+        amethyst::start_logger(Default::default());
+        let address = SocketAddr::from(([10, 0, 0, 1], 5000));
+        let mut client_registry = ClientRegistry::new(1);
+        let client_id = client_registry.add(&address, "Clint")?;
+
+        AmethystApplication::blank()
+            .with_setup(|world: &mut World| {
+                world.register::<Player>();
+                world.register::<Client>();
+                world.register::<StatusEffects>();
+            })
+            .with_resource(EventChannel::<DamageEvent>::new())
+            .with_setup(|world: &mut World| {
+                let reader = world.fetch_mut::<EventChannel<DamageEvent>>().register_reader();
+                world.insert(reader);
+            })
+            .with_resource(client_registry)
+            .with_resource(TransportResource::new())
+            .with_system_desc(StatusEffectSystemDesc::default(), "status_effect", &[])
+            .with_effect(move |world: &mut World| {
+                let player = world.create_entity()
+                    .with(Player)
+                    .with(Client { id: client_id })
+                    .build();
+                world.insert(EffectReturn(player));
+
+                let mut channel = world.fetch_mut::<EventChannel<StatusEffectEvent>>();
+                for _ in 0..2 {
+                    channel.single_write(StatusEffectEvent { target: player, effect: effect(StatusEffectKind::Bleed, 5), source: None });
+                    channel.single_write(StatusEffectEvent { target: player, effect: effect(StatusEffectKind::Burn, u16::MAX), source: None });
+                }
+            })
+            .with_assertion(|world: &mut World| {
+                let player = world.read_resource::<EffectReturn<Entity>>().0;
+
+                let channel = world.fetch::<EventChannel<DamageEvent>>();
+                let mut reader = world.fetch_mut::<ReaderId<DamageEvent>>();
+                let damages: Vec<_> = channel.read(&mut reader)
+                    .map(|event| (event.target, event.damage.0, event.weapon.clone()))
+                    .collect();
+                // Two stacks of each, the burn damage is capped instead of overflowing
+                assert_eq!(damages, vec![
+                    (player, 10, Some("Bleed".to_string())),
+                    (player, u16::MAX, Some("Burn".to_string())),
+                ]);
+
+                assert!(!world.read_storage::<StatusEffects>().contains(player));
+                let net = world.fetch::<TransportResource>();
+                let last_update = net.get_messages().last().map(|message| deserialize(&message.payload).unwrap());
+                assert!(matches!(last_update, Some(PacketType::PlayerUpdate(PlayerUpdate::StatusEffects(state))) if state.is_empty()));
+            })
+            .run()
+    }
+}